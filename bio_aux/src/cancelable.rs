@@ -0,0 +1,165 @@
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha20Rng;
+
+/// Key derived permutation of the features and of the quantization bin labels of every feature.
+///
+/// Templates and probes go through [`Self::apply`] after quantization and the HELR tables are
+/// permuted the same way by [`crate::HelrTableSet::from_tables_cancelable`], so every score is
+/// unchanged. Enrolling again under a new user key revokes the old templates: their LUTs are
+/// indexed by labels the new probes do not use.
+#[derive(Clone)]
+pub struct CancelableTransform {
+    // features[i] is the original feature stored at position i
+    features: Vec<usize>,
+    // bins[i][x] is the label of bin x of the feature stored at position i
+    bins: Vec<Vec<u8>>,
+}
+
+impl CancelableTransform {
+    /// Derives the permutations of `num_features` features of `num_bins` bins from `user_key`.
+    pub fn new(user_key: &[u8; 32], num_features: usize, num_bins: usize) -> Self {
+        assert!(num_bins <= 1 << u8::BITS, "{num_bins} bins do not fit in u8 labels");
+        let mut rng = ChaCha20Rng::from_seed(*user_key);
+
+        let mut features: Vec<usize> = (0..num_features).collect();
+        features.shuffle(&mut rng);
+
+        let bins = (0..num_features).map(|_| {
+            let mut labels: Vec<u8> = (0..num_bins).map(|x| x as u8).collect();
+            labels.shuffle(&mut rng);
+            labels
+        }).collect();
+
+        Self { features, bins }
+    }
+
+    pub fn num_features(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn num_bins(&self) -> usize {
+        self.bins.first().map_or(0, Vec::len)
+    }
+
+    /// Transforms a quantized template or probe.
+    pub fn apply(&self, quantized: &[u8]) -> Vec<u8> {
+        assert_eq!(quantized.len(), self.num_features(), "feature vector does not match the transform");
+        self.features.iter().zip(self.bins.iter()).map(|(&feature, labels)| {
+            labels[quantized[feature] as usize]
+        }).collect()
+    }
+
+    /// Reorders per feature data, e.g. the tables, like [`Self::apply`] reorders the features.
+    pub fn permute_features<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        assert_eq!(values.len(), self.num_features(), "feature data does not match the transform");
+        self.features.iter().map(|&feature| values[feature].clone()).collect()
+    }
+
+    /// Relabels the rows (template values) and columns (probe values) of the table of the
+    /// feature stored at `position`, the table must already be at that position.
+    pub fn permute_table<T: Copy + Default>(&self, position: usize, table: &[Vec<T>]) -> Vec<Vec<T>> {
+        let labels = &self.bins[position];
+        assert!(
+            table.len() == labels.len() && table.iter().all(|row| row.len() == labels.len()),
+            "table {position} is not {0}x{0}", labels.len()
+        );
+
+        let mut permuted = vec![vec![T::default(); labels.len()]; labels.len()];
+        for (x, row) in table.iter().enumerate() {
+            for (y, &entry) in row.iter().enumerate() {
+                permuted[labels[x] as usize][labels[y] as usize] = entry;
+            }
+        }
+        permuted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::{Config, ProbeEncoding};
+    use rand::Rng;
+    use crate::HelrTableSet;
+
+    const NUM_FEATURES: usize = 16;
+    const NUM_BINS: usize = 16;
+
+    const CONFIG: Config = Config {
+        data_set_name: "cancelable",
+        num_blocks: 2,
+        num_blocks_sum: 4,
+        block_length: 2,
+        num_tables: NUM_FEATURES,
+        threshold: 0,
+        probe_encoding: ProbeEncoding::Block,
+    };
+
+    // Highest on the diagonal and lowest at the end of the first row where the offsetting looks
+    // for it, the offset entries fit the two blocks
+    fn helr_tables() -> Vec<Vec<Vec<i32>>> {
+        (0..NUM_FEATURES as i32).map(|feature| {
+            (0..NUM_BINS as i32).map(|x| {
+                (0..NUM_BINS as i32).map(|y| 7 - feature % 2 - (x - y).abs()).collect()
+            }).collect()
+        }).collect()
+    }
+
+    fn score(table_set: &HelrTableSet, template: &[u8], probe: &[u8]) -> u64 {
+        table_set.lut_rows(template, false).iter().zip(probe).map(|(blocks, &y)| {
+            blocks.iter().enumerate().map(|(block_num, row)| {
+                row[y as usize] << (CONFIG.block_length * block_num)
+            }).sum::<u64>()
+        }).sum()
+    }
+
+    fn random_features(rng: &mut impl Rng) -> Vec<u8> {
+        (0..NUM_FEATURES).map(|_| rng.gen_range(0..NUM_BINS as u8)).collect()
+    }
+
+    #[test]
+    fn transformed_scores_match() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let transform = CancelableTransform::new(&rng.gen(), NUM_FEATURES, NUM_BINS);
+        let plain = HelrTableSet::from_tables(helr_tables(), &CONFIG);
+        let cancelable = HelrTableSet::from_tables_cancelable(helr_tables(), &CONFIG, &transform);
+        assert_eq!(plain.threshold(), cancelable.threshold());
+
+        for _ in 0..100 {
+            let template = random_features(&mut rng);
+            // Genuine pairs and unrelated ones
+            for probe in [template.clone(), random_features(&mut rng)] {
+                assert_eq!(
+                    score(&plain, &template, &probe),
+                    score(&cancelable, &transform.apply(&template), &transform.apply(&probe)),
+                    "template {template:?} and probe {probe:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn two_keys_are_unlinkable() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let first = CancelableTransform::new(&rng.gen(), NUM_FEATURES, NUM_BINS);
+        let second = CancelableTransform::new(&rng.gen(), NUM_FEATURES, NUM_BINS);
+        let first_tables = HelrTableSet::from_tables_cancelable(helr_tables(), &CONFIG, &first);
+        let second_tables = HelrTableSet::from_tables_cancelable(helr_tables(), &CONFIG, &second);
+
+        let template = random_features(&mut rng);
+        let first_template = first.apply(&template);
+        let second_template = second.apply(&template);
+        assert_ne!(first_template, second_template);
+        assert_ne!(
+            first_tables.lut_rows(&first_template, false),
+            second_tables.lut_rows(&second_template, false)
+        );
+
+        // The genuine probe of the subject only matches under the key it was enrolled with
+        let genuine = score(&first_tables, &first_template, &first.apply(&template));
+        let other_key = score(&first_tables, &first_template, &second.apply(&template));
+        let leaked_template = score(&first_tables, &second_template, &first.apply(&template));
+        assert!(other_key < genuine, "{other_key} against {genuine}");
+        assert!(leaked_template < genuine, "{leaked_template} against {genuine}");
+    }
+}
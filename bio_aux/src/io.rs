@@ -13,7 +13,7 @@ pub fn read_qbins(path:&str) -> Vec<f64> {
     result
 }
 
-fn csv_string_to_real_vec(input_string:String) -> Vec<f64> {
+pub fn csv_string_to_real_vec(input_string:String) -> Vec<f64> {
     let mut result:Vec<f64> = Vec::new();
     for str_value in input_string.split(",") {
         let value = str_value.parse::<f64>();
@@ -56,6 +56,23 @@ pub fn read_sample_with_id_in_line_fpd(path:&str, sample_id:usize) -> Option<(us
     Some(dataset.get(new_id - 1 ).unwrap().clone())
 }
 
+/// Sample ids of every subject of a data set, in the order of the first sample of the subjects.
+/// Sample ids are line numbers starting at 1, like in [`read_sample_with_id_in_line_fpd`].
+pub fn samples_by_subject(config: &Config) -> Vec<(usize, Vec<usize>)> {
+    let csv_filename = format!("{}.csv", config.data_set_name);
+    let csv_path = [DATA_PATH, csv_filename.as_str()].join(PATH_SEPARATOR);
+    let dataset = read_dataset(csv_path.as_str()).unwrap();
+
+    let mut subjects: Vec<(usize, Vec<usize>)> = Vec::new();
+    for (line, (subject, _)) in dataset.iter().enumerate() {
+        match subjects.iter_mut().find(|(id, _)| id == subject) {
+            Some((_, samples)) => samples.push(line + 1),
+            None => subjects.push((*subject, vec![line + 1])),
+        }
+    }
+    subjects
+}
+
 fn quantize_feature(raw_feature: f64, qbins: &Vec<f64>) -> usize {
     for bin in 0 .. qbins.len() {
         if raw_feature <= qbins[bin] {
@@ -96,7 +113,32 @@ pub fn probe_and_template_generation_radix_prepare(entry_num:usize, template_num
     (probe_vec, template_vec)
 }
 
-fn csv_to_helr_table(csv_string:String) -> Option<Vec<Vec<i32>>>{
+pub fn probe_and_templates_generation_radix_prepare(entry_num:usize, template_nums: &[usize], config: &Config) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let qbin_filename = format!("{}{}.csv", config.data_set_name, QBIN_SUFFIX);
+    let qbins_path = [DATA_PATH, LOOKUP_TABLES_FOLDER, config.data_set_name, qbin_filename.as_str()].join(PATH_SEPARATOR);
+    let bins = read_qbins(qbins_path.as_str());
+
+    let csv_filename = format!("{}.csv", config.data_set_name);
+    let csv_path = [DATA_PATH, csv_filename.as_str()].join(PATH_SEPARATOR);
+
+    // Load raw samples of probe and all enrolled templates from file
+    let (_, entry) =
+        read_sample_with_id_in_line_fpd(csv_path.as_str(), entry_num).unwrap();
+    let templates = template_nums.iter().map(|&template_num| {
+        let (_, template) = read_sample_with_id_in_line_fpd(csv_path.as_str(), template_num).unwrap();
+        template
+    }).collect::<Vec<_>>();
+
+    // Quantize
+    let probe_vec:Vec<u8> = quantize_feature_vector(entry, &bins).into_iter().map(|x| x as u8).collect();
+    let template_vecs:Vec<Vec<u8>> = templates.into_iter().map(|template| {
+        quantize_feature_vector(template, &bins).into_iter().map(|x| x as u8).collect()
+    }).collect();
+
+    (probe_vec, template_vecs)
+}
+
+pub fn csv_to_helr_table(csv_string:String) -> Option<Vec<Vec<i32>>>{
     let mut data_vec = Vec::new();
     let mut reader = csv::ReaderBuilder::new().has_headers(false).from_reader(csv_string.as_bytes());
     for record in reader.records() {
@@ -1,76 +1,96 @@
+pub mod cancelable;
 pub mod io;
+pub mod signed_table_set;
+pub mod table_set;
 
 use config::*;
+pub use cancelable::CancelableTransform;
+pub use signed_table_set::SignedHelrTableSet;
+pub use table_set::HelrTableSet;
 
-pub fn generate_functions_stop_early(template: &Vec<u8>, config: &Config) -> (Vec<Vec<Box< dyn Fn(u64) -> u64>>>, usize) {
-    // read precomputed HELR tables from files
+/// Returns the largest sum the offset HELR tables of `config` can produce together with the
+/// cumulated offset of the tables.
+pub fn max_offset_score(config: &Config) -> (u64, i32) {
     let tables_path = [DATA_PATH, LOOKUP_TABLES_FOLDER, config.data_set_name, TABLE_PREFIX].join(PATH_SEPARATOR);
     let helr_tables =
         io::read_helr_tables(tables_path.as_str(), config.num_tables).unwrap();
-
-    // offset all HELR tables to only have nonnegative entries and save the cumulated offset for all tables
     let (offset_helr_tables, offset) = offset_helr_table(helr_tables);
 
-    // generate a vector of LUT/value vectors for each table
-    let mut lut_vecs = Vec::new();
-    for (idx, table) in offset_helr_tables.iter().enumerate() {
-        let decomposed_helr = decompose_helr_table(&table, config.block_length, config.num_blocks);
-        let dim = decomposed_helr[0].len() as u64;
-        let template_x = template[idx];
-        let mut lut_vec = Vec::new();
-        for block_num in 0..config.num_blocks {
-            if table[0][0] / 2u32.pow((config.block_length * block_num) as u32) <= 0 {
-                break;
+    let max_score = offset_helr_tables.iter().map(|table| {
+        table.iter().flatten().copied().max().unwrap_or(0) as u64
+    }).sum();
+    (max_score, offset)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FusionError {
+    /// The fusion has no modality
+    NoModalities,
+    /// A modality does not use the block length of the first one
+    BlockLengthMismatch {
+        modality: usize,
+        block_length: usize,
+        expected: usize,
+    },
+    /// The fused threshold is below every offset score, every probe would be accepted
+    ThresholdBelowScores(i64),
+}
+
+impl std::fmt::Display for FusionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoModalities => write!(f, "the fusion has no modality"),
+            Self::BlockLengthMismatch {
+                modality,
+                block_length,
+                expected,
+            } => write!(
+                f,
+                "modality {modality} uses blocks of {block_length} bits, the first one {expected}"
+            ),
+            Self::ThresholdBelowScores(threshold) => {
+                write!(f, "offset fused threshold {threshold} is below every score")
             }
-            let copied_table = decomposed_helr.clone();
-            let boxed: Box<dyn Fn(u64) -> u64> = Box::new(move |probe_y: u64| -> u64 {
-                if probe_y < dim {
-                    copied_table[template_x as usize][probe_y as usize][block_num]
-                } else {
-                    0
-                }
-            });
-            lut_vec.push(boxed);
         }
-        lut_vecs.push(lut_vec);
     }
-
-    let threshold = config.threshold + offset as i64;
-    (lut_vecs, threshold as usize)
 }
 
-pub fn generate_functions_const_length(template: &Vec<u8>, config: &Config) -> (Vec<Vec<Box< dyn Fn(u64) -> u64>>>, usize) {
-    // read precomputed HELR tables from files
-    let tables_path = [DATA_PATH, LOOKUP_TABLES_FOLDER, config.data_set_name, TABLE_PREFIX].join(PATH_SEPARATOR);
-    let helr_tables =
-        io::read_helr_tables(tables_path.as_str(), config.num_tables).unwrap();
+impl std::error::Error for FusionError {}
+
+/// Computes the common radix width and the fused threshold for a weighted sum of the offset
+/// HELR scores of all modalities.
+///
+/// Every modality shifts its tables by its own offset, so the fused threshold is moved by the
+/// weighted sum of these offsets. The radix holds both the largest fused score and the
+/// threshold it is compared to.
+pub fn fusion_parameters(fusion: &FusionConfig) -> Result<(usize, usize), FusionError> {
+    let block_length = fusion.modalities.first().ok_or(FusionError::NoModalities)?.config.block_length;
+    if let Some((modality, m)) = fusion.modalities.iter().enumerate().find(|(_, m)| m.config.block_length != block_length) {
+        return Err(FusionError::BlockLengthMismatch {
+            modality,
+            block_length: m.config.block_length,
+            expected: block_length,
+        });
+    }
 
-    // offset all HELR tables to only have nonnegative entries and save the cumulated offset for all tables
-    let (offset_helr_tables, offset) = offset_helr_table(helr_tables);
+    let mut max_score = 0u64;
+    let mut fused_offset = 0i64;
+    for modality in fusion.modalities {
+        let (modality_max, offset) = max_offset_score(modality.config);
+        max_score += modality.weight * modality_max;
+        fused_offset += modality.weight as i64 * offset as i64;
+    }
 
-    // generate a vector of LUT/value vectors for each table
-    let mut lut_vecs = Vec::new();
-    for (idx, table) in offset_helr_tables.iter().enumerate() {
-        let decomposed_helr = decompose_helr_table(&table, config.block_length, config.num_blocks);
-        let dim = decomposed_helr[0].len() as u64;
-        let template_x = template[idx];
-        let mut lut_vec = Vec::new();
-        for block_num in 0..config.num_blocks {
-            let copied_table = decomposed_helr.clone();
-            let boxed: Box<dyn Fn(u64) -> u64> = Box::new(move |probe_y: u64| -> u64 {
-                if probe_y < dim {
-                    copied_table[template_x as usize][probe_y as usize][block_num]
-                } else {
-                    0
-                }
-            });
-            lut_vec.push(boxed);
-        }
-        lut_vecs.push(lut_vec);
+    let threshold = fusion.threshold + fused_offset;
+    let threshold = usize::try_from(threshold).map_err(|_| FusionError::ThresholdBelowScores(threshold))?;
+
+    let widest = max_score.max(threshold as u64);
+    let mut num_blocks = 1;
+    while widest >> (block_length * num_blocks) > 0 {
+        num_blocks += 1;
     }
 
-    let threshold = config.threshold + offset as i64;
-    (lut_vecs, threshold as usize)
+    Ok((num_blocks, threshold))
 }
 
 pub fn offset_helr_table(helr_tables:Vec<Vec<Vec<i32>>>) -> (Vec<Vec<Vec<u32>>>, i32 ) {
@@ -117,6 +137,29 @@ pub fn decompose_helr_table(helr: &Vec<Vec<u32>>, block_length: usize, block_cou
 }
 
 
+/// Decomposes signed HELR values into `block_count` two's complement blocks of `block_length` bits,
+/// least significant block first. The values have to fit, see [`signed_block_count`].
+pub fn decompose_signed_helr_table(helr: &Vec<Vec<i32>>, block_length: usize, block_count: usize) -> Vec<Vec<Vec<u64>>> {
+    let block_mask = (1i64 << block_length) - 1;
+    helr.iter().map(|entry| {
+        entry.iter().map(|&value| {
+            (0..block_count).map(|block_num| ((value as i64 >> (block_length * block_num)) & block_mask) as u64).collect()
+        }).collect()
+    }).collect()
+}
+
+/// Smallest number of `block_length` bit blocks holding every value of `min..=max` in two's complement.
+pub fn signed_block_count(min: i64, max: i64, block_length: usize) -> usize {
+    let mut block_count = 1;
+    loop {
+        let half = 1i64 << (block_length * block_count - 1);
+        if -half <= min && max < half {
+            return block_count;
+        }
+        block_count += 1;
+    }
+}
+
 pub fn encrypt_feature_vec_radix<F, T: Clone>(feature_vec: Vec<u8>, encrypt: F, config: Config) -> Vec<Vec<T>>
 where
     F: Fn(u64) -> T,
@@ -0,0 +1,76 @@
+use config::*;
+use crate::{decompose_signed_helr_table, io, signed_block_count, HelrTableSet};
+use crate::table_set::check_probe_space;
+
+/// HELR tables of a data set decomposed into two's complement blocks instead of being offset.
+///
+/// Without the offsets the threshold is the one of the data set and the sum only needs the blocks
+/// of the actual score range, see [`Self::num_blocks_sum`]. No assumption is made on where the
+/// minimum of a table lies.
+#[derive(Clone)]
+pub struct SignedHelrTableSet {
+    tables: HelrTableSet,
+    num_blocks_sum: usize,
+    threshold: i64,
+}
+
+impl SignedHelrTableSet {
+    pub fn load(config: &Config) -> Self {
+        let tables_path = [DATA_PATH, LOOKUP_TABLES_FOLDER, config.data_set_name, TABLE_PREFIX].join(PATH_SEPARATOR);
+        let helr_tables =
+            io::read_helr_tables(tables_path.as_str(), config.num_tables).unwrap();
+        Self::from_tables(helr_tables, config)
+    }
+
+    /// Only the block length, the probe encoding and the threshold of `config` are used, the block
+    /// counts follow from the values of the tables.
+    pub fn from_tables(helr_tables: Vec<Vec<Vec<i32>>>, config: &Config) -> Self {
+        check_probe_space(&helr_tables, config);
+
+        let ranges: Vec<(i64, i64)> = helr_tables.iter().map(|table| {
+            let values = table.iter().flatten().map(|&value| value as i64);
+            (values.clone().min().unwrap(), values.max().unwrap())
+        }).collect();
+
+        // every table only evaluates the blocks of its own range, the server sign extends them to the sum
+        let used_blocks: Vec<usize> = ranges.iter()
+            .map(|&(min, max)| signed_block_count(min, max, config.block_length))
+            .collect();
+        let num_blocks = used_blocks.iter().copied().max().unwrap();
+
+        let min_sum: i64 = ranges.iter().map(|&(min, _)| min).sum();
+        let max_sum: i64 = ranges.iter().map(|&(_, max)| max).sum();
+        let num_blocks_sum = signed_block_count(
+            min_sum.min(config.threshold),
+            max_sum.max(config.threshold),
+            config.block_length,
+        ).max(num_blocks);
+
+        let decomposed_tables = helr_tables.iter()
+            .map(|table| decompose_signed_helr_table(table, config.block_length, num_blocks))
+            .collect();
+
+        Self {
+            // the threshold of the unsigned set is unused, comparisons go through Self::threshold
+            tables: HelrTableSet::from_decomposed(decomposed_tables, used_blocks, num_blocks, 0),
+            num_blocks_sum,
+            threshold: config.threshold,
+        }
+    }
+
+    /// Threshold to compare the signed sum of the tables against.
+    pub fn threshold(&self) -> i64 {
+        self.threshold
+    }
+
+    /// Number of blocks of the signed sum, every feature is sign extended to it.
+    pub fn num_blocks_sum(&self) -> usize {
+        self.num_blocks_sum
+    }
+
+    /// Returns for every feature of `template` the LUT value slices of its blocks, the last block
+    /// of a feature carries its sign.
+    pub fn lut_rows(&self, template: &[u8]) -> Vec<Vec<&[u64]>> {
+        self.tables.lut_rows(template, true)
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use config::*;
+use crate::{decompose_helr_table, io, offset_helr_table, CancelableTransform};
+
+/// All decomposed HELR tables of a data set, loaded once and shared between subjects.
+///
+/// The decomposed values are stored in a single flat array laid out as
+/// `[table][template_x][block][probe_y]`, so the LUT of one block of one feature for a given
+/// template value is a contiguous slice indexed by the probe value.
+#[derive(Clone)]
+pub struct HelrTableSet {
+    values: Arc<[u64]>,
+    table_starts: Vec<usize>,
+    // (rows, columns) of every table
+    dims: Vec<(usize, usize)>,
+    // number of blocks evaluated per table when stopping early
+    used_blocks: Vec<usize>,
+    num_blocks: usize,
+    threshold: usize,
+}
+
+impl HelrTableSet {
+    pub fn load(config: &Config) -> Self {
+        // read precomputed HELR tables from files
+        let tables_path = [DATA_PATH, LOOKUP_TABLES_FOLDER, config.data_set_name, TABLE_PREFIX].join(PATH_SEPARATOR);
+        let helr_tables =
+            io::read_helr_tables(tables_path.as_str(), config.num_tables).unwrap();
+        Self::from_tables(helr_tables, config)
+    }
+
+    pub fn from_tables(helr_tables: Vec<Vec<Vec<i32>>>, config: &Config) -> Self {
+        Self::build(helr_tables, config, None)
+    }
+
+    /// Same as [`Self::load`] with the tables permuted by `transform`.
+    pub fn load_cancelable(config: &Config, transform: &CancelableTransform) -> Self {
+        let tables_path = [DATA_PATH, LOOKUP_TABLES_FOLDER, config.data_set_name, TABLE_PREFIX].join(PATH_SEPARATOR);
+        let helr_tables =
+            io::read_helr_tables(tables_path.as_str(), config.num_tables).unwrap();
+        Self::from_tables_cancelable(helr_tables, config, transform)
+    }
+
+    /// Tables for templates and probes transformed by `transform`, the scores and the threshold
+    /// are the same as the untransformed tables.
+    pub fn from_tables_cancelable(helr_tables: Vec<Vec<Vec<i32>>>, config: &Config, transform: &CancelableTransform) -> Self {
+        Self::build(helr_tables, config, Some(transform))
+    }
+
+    fn build(helr_tables: Vec<Vec<Vec<i32>>>, config: &Config, transform: Option<&CancelableTransform>) -> Self {
+        check_probe_space(&helr_tables, config);
+
+        // offset all HELR tables to only have nonnegative entries and save the cumulated offset for all tables
+        let (offset_helr_tables, offset) = offset_helr_table(helr_tables);
+
+        // the offset and the number of used blocks rely on the original layout, so they are computed before permuting
+        let used_blocks: Vec<usize> = offset_helr_tables.iter().map(|table| {
+            (0..config.num_blocks)
+                .take_while(|&block_num| table[0][0] / 2u32.pow((config.block_length * block_num) as u32) > 0)
+                .count()
+        }).collect();
+        let (offset_helr_tables, used_blocks) = match transform {
+            Some(transform) => {
+                let tables = transform.permute_features(&offset_helr_tables).iter().enumerate()
+                    .map(|(position, table)| transform.permute_table(position, table))
+                    .collect();
+                (tables, transform.permute_features(&used_blocks))
+            }
+            None => (offset_helr_tables, used_blocks),
+        };
+
+        let decomposed_tables = offset_helr_tables.iter()
+            .map(|table| decompose_helr_table(table, config.block_length, config.num_blocks))
+            .collect();
+
+        let threshold = config.threshold + offset as i64;
+        Self::from_decomposed(decomposed_tables, used_blocks, config.num_blocks, threshold as usize)
+    }
+
+    /// Lays out tables already decomposed into `num_blocks` blocks, indexed `[template_x][probe_y][block]`.
+    pub(crate) fn from_decomposed(decomposed_tables: Vec<Vec<Vec<Vec<u64>>>>, used_blocks: Vec<usize>, num_blocks: usize, threshold: usize) -> Self {
+        let mut values = Vec::new();
+        let mut table_starts = Vec::with_capacity(decomposed_tables.len());
+        let mut dims = Vec::with_capacity(decomposed_tables.len());
+        for decomposed_helr in decomposed_tables.iter() {
+            let rows = decomposed_helr.len();
+            let columns = decomposed_helr[0].len();
+
+            table_starts.push(values.len());
+            dims.push((rows, columns));
+
+            for row in decomposed_helr.iter() {
+                for block_num in 0..num_blocks {
+                    values.extend(row.iter().map(|blocks| blocks[block_num]));
+                }
+            }
+        }
+
+        Self {
+            values: values.into(),
+            table_starts,
+            dims,
+            used_blocks,
+            num_blocks,
+            threshold,
+        }
+    }
+
+    /// Threshold to compare the sum of the offset tables against.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    fn row_start(&self, table: usize, template_x: usize, block_num: usize) -> usize {
+        let (rows, columns) = self.dims[table];
+        assert!(template_x < rows, "template value {template_x} out of range for table {table}");
+        self.table_starts[table] + (template_x * self.num_blocks + block_num) * columns
+    }
+
+    fn blocks_for(&self, table: usize, stop_early: bool) -> usize {
+        if stop_early {
+            self.used_blocks[table]
+        } else {
+            self.num_blocks
+        }
+    }
+
+    /// Returns for every feature of `template` the LUT value slices of all its blocks.
+    ///
+    /// A slice is indexed by the probe value and is at most as long as the probe space of the
+    /// config, see [`Config::probe_space`]. Past its end the LUTs map to 0, no valid probe gets
+    /// there.
+    pub fn lut_rows(&self, template: &[u8], stop_early: bool) -> Vec<Vec<&[u64]>> {
+        self.dims.iter().enumerate().map(|(table, &(_, columns))| {
+            let template_x = template[table];
+            (0..self.blocks_for(table, stop_early)).map(|block_num| {
+                let start = self.row_start(table, template_x as usize, block_num);
+                &self.values[start..start + columns]
+            }).collect()
+        }).collect()
+    }
+}
+
+/// Panics if a table has more probe bins than a probe feature can take with the encoding of
+/// `config`, such probe values would wrap around in the encryption and silently score as 0.
+pub(crate) fn check_probe_space(helr_tables: &[Vec<Vec<i32>>], config: &Config) {
+    for (table, helr) in helr_tables.iter().enumerate() {
+        let columns = helr[0].len();
+        assert!(
+            columns <= config.probe_space(),
+            "table {table} has {columns} bins, more than the {} probe values of {:?} encoding with block length {}",
+            config.probe_space(), config.probe_encoding, config.block_length
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::{BufReader, BufWriter};
+use std::net::{TcpStream, ToSocketAddrs};
+use tfhe::shortint;
+use tfhe::shortint::server_key::LookupTableOwned;
+use tfhe::shortint::Ciphertext;
+use crate::error::Error;
+use crate::framing::{read_frame, write_frame, DEFAULT_MAX_FRAME_LEN};
+use crate::mac::MacKey;
+use crate::message::{ProtocolError, Request, Response, TaggedDecision, PROTOCOL_VERSION};
+
+/// Reference client, the secret key never leaves the caller: only server keys, encrypted LUTs
+/// and encrypted probes are sent.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+    max_frame_len: u64,
+}
+
+impl Client {
+    /// Connects to a server and performs the version handshake.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, Error> {
+        let stream = TcpStream::connect(addr)?;
+        let mut client = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        };
+        let hello = Request::Hello {
+            version: PROTOCOL_VERSION,
+        };
+        match client.call(&hello)? {
+            Response::Hello { .. } => Ok(client),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    fn call(&mut self, request: &Request) -> Result<Response, Error> {
+        write_frame(&mut self.writer, request)?;
+        match read_frame(&mut self.reader, self.max_frame_len)? {
+            Response::Error(err) => Err(Error::Protocol(err)),
+            response => Ok(response),
+        }
+    }
+
+    /// Uploads a server key with the key of the tags of the decisions computed with it, e.g. a
+    /// fresh [`MacKey::generate`] per key id. The upload has to go over a trusted channel.
+    pub fn upload_server_key(
+        &mut self,
+        key_id: &str,
+        server_key: shortint::ServerKey,
+        mac_key: &MacKey,
+    ) -> Result<(), Error> {
+        let request = Request::UploadServerKey {
+            key_id: key_id.to_owned(),
+            server_key: Box::new(server_key),
+            mac_key: mac_key.clone(),
+        };
+        match self.call(&request)? {
+            Response::KeyStored => Ok(()),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    pub fn enroll(
+        &mut self,
+        subject_id: &str,
+        key_id: &str,
+        luts: Vec<Vec<LookupTableOwned>>,
+        threshold: usize,
+        num_sum_blocks: usize,
+    ) -> Result<(), Error> {
+        let request = Request::Enroll {
+            subject_id: subject_id.to_owned(),
+            key_id: key_id.to_owned(),
+            luts,
+            threshold,
+            num_sum_blocks,
+        };
+        match self.call(&request)? {
+            Response::Enrolled => Ok(()),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Replaces the template of an enrolled subject.
+    pub fn update(
+        &mut self,
+        subject_id: &str,
+        key_id: &str,
+        luts: Vec<Vec<LookupTableOwned>>,
+        threshold: usize,
+        num_sum_blocks: usize,
+    ) -> Result<(), Error> {
+        let request = Request::Update {
+            subject_id: subject_id.to_owned(),
+            key_id: key_id.to_owned(),
+            luts,
+            threshold,
+            num_sum_blocks,
+        };
+        match self.call(&request)? {
+            Response::Updated => Ok(()),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Removes a subject enrolled under `key_id`.
+    pub fn revoke(&mut self, subject_id: &str, key_id: &str) -> Result<(), Error> {
+        let request = Request::Revoke {
+            subject_id: subject_id.to_owned(),
+            key_id: key_id.to_owned(),
+        };
+        match self.call(&request)? {
+            Response::Revoked => Ok(()),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    pub fn list_subjects(&mut self) -> Result<Vec<String>, Error> {
+        match self.call(&Request::ListSubjects)? {
+            Response::Subjects(subject_ids) => Ok(subject_ids),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Returns the encrypted decision of the server bound to `nonce`, once its tag is checked
+    /// with the `mac_key` uploaded with the server key of the subject. The decision still has to
+    /// be checked with `tfhe::core_crypto::biometrics::cpu::challenge::verify_decision`.
+    pub fn authenticate(
+        &mut self,
+        subject_id: &str,
+        probe: Vec<Vec<Ciphertext>>,
+        nonce: Vec<Ciphertext>,
+        mac_key: &MacKey,
+    ) -> Result<Vec<Ciphertext>, Error> {
+        let request = Request::Authenticate {
+            subject_id: subject_id.to_owned(),
+            probe,
+            nonce,
+        };
+        let Response::Decision(tagged) = self.call(&request)? else {
+            return Err(Error::UnexpectedResponse);
+        };
+        let Request::Authenticate { nonce, .. } = request else {
+            unreachable!()
+        };
+        check_tag(mac_key, subject_id, &nonce, tagged)
+    }
+
+    /// 1:N counterpart of [`Self::authenticate`], returns the decision of every subject enrolled
+    /// under `key_id`, each tag checked like the one of [`Self::authenticate`], or the reason its
+    /// template could not be evaluated.
+    pub fn identify(
+        &mut self,
+        key_id: &str,
+        probe: Vec<Vec<Ciphertext>>,
+        nonce: Vec<Ciphertext>,
+        mac_key: &MacKey,
+    ) -> Result<Vec<(String, Result<Vec<Ciphertext>, ProtocolError>)>, Error> {
+        let request = Request::Identify {
+            key_id: key_id.to_owned(),
+            probe,
+            nonce,
+        };
+        let Response::Identification { decisions } = self.call(&request)? else {
+            return Err(Error::UnexpectedResponse);
+        };
+        let Request::Identify { nonce, .. } = request else {
+            unreachable!()
+        };
+        decisions
+            .into_iter()
+            .map(|(subject_id, decision)| {
+                let decision = match decision {
+                    Ok(tagged) => Ok(check_tag(mac_key, &subject_id, &nonce, tagged)?),
+                    Err(err) => Err(err),
+                };
+                Ok((subject_id, decision))
+            })
+            .collect()
+    }
+}
+
+fn check_tag(
+    mac_key: &MacKey,
+    subject_id: &str,
+    nonce: &[Ciphertext],
+    tagged: TaggedDecision,
+) -> Result<Vec<Ciphertext>, Error> {
+    if !mac_key.verify(subject_id, nonce, &tagged.decision, &tagged.tag) {
+        return Err(Error::Unauthenticated);
+    }
+    Ok(tagged.decision)
+}
@@ -0,0 +1,50 @@
+use crate::message::ProtocolError;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Serialization(bincode::Error),
+    /// The announced frame length exceeds the configured maximum
+    FrameTooLarge { len: u64, max_len: u64 },
+    /// The peer answered with an error
+    Protocol(ProtocolError),
+    /// The peer answered with a response that does not match the request
+    UnexpectedResponse,
+    /// The tag of a decision does not verify, it was not computed by the server for this request
+    Unauthenticated,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Serialization(err) => write!(f, "serialization error: {err}"),
+            Self::FrameTooLarge { len, max_len } => {
+                write!(f, "frame of {len} bytes exceeds the maximum of {max_len} bytes")
+            }
+            Self::Protocol(err) => write!(f, "{err}"),
+            Self::UnexpectedResponse => write!(f, "unexpected response"),
+            Self::Unauthenticated => write!(f, "decision tag does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+impl From<ProtocolError> for Error {
+    fn from(err: ProtocolError) -> Self {
+        Self::Protocol(err)
+    }
+}
@@ -0,0 +1,87 @@
+//! Length-prefixed bincode framing: every message is preceded by its length as a little endian
+//! `u64`.
+
+use std::io::{Read, Write};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::error::Error;
+
+/// Default upper bound of a frame, 1 GiB.
+///
+/// The largest message is the server key upload. With `PARAM_MESSAGE_3_CARRY_3_KS_PBS`, the
+/// largest supported parameter set, the Fourier bootstrap key takes about 462 MB and the key
+/// switching key about 347 MB, the other messages are far smaller.
+pub const DEFAULT_MAX_FRAME_LEN: u64 = 1 << 30;
+
+pub fn write_frame<W: Write, T: Serialize>(writer: &mut W, message: &T) -> Result<(), Error> {
+    let len = bincode::serialized_size(message)?;
+    writer.write_all(&len.to_le_bytes())?;
+    bincode::serialize_into(&mut *writer, message)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_frame<R: Read, T: DeserializeOwned>(reader: &mut R, max_len: u64) -> Result<T, Error> {
+    let mut len_bytes = [0u8; 8];
+    reader.read_exact(&mut len_bytes)?;
+    let len = u64::from_le_bytes(len_bytes);
+    if len > max_len {
+        return Err(Error::FrameTooLarge { len, max_len });
+    }
+
+    // The buffer grows with the bytes actually received, a peer announcing a large frame
+    // without sending it does not get it allocated up front
+    let mut payload = Vec::new();
+    reader.take(len).read_to_end(&mut payload)?;
+    if (payload.len() as u64) < len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(bincode::deserialize(&payload)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::{ProtocolError, Response};
+
+    #[test]
+    fn frame_round_trip() {
+        let mut buffer = Vec::new();
+        let sent = Response::Error(ProtocolError::UnknownSubject("alice".to_owned()));
+        write_frame(&mut buffer, &sent).unwrap();
+        write_frame(&mut buffer, &Response::Enrolled).unwrap();
+
+        let mut reader = buffer.as_slice();
+        let first: Response = read_frame(&mut reader, DEFAULT_MAX_FRAME_LEN).unwrap();
+        let second: Response = read_frame(&mut reader, DEFAULT_MAX_FRAME_LEN).unwrap();
+        assert!(matches!(
+            first,
+            Response::Error(ProtocolError::UnknownSubject(ref s)) if s == "alice"
+        ));
+        assert!(matches!(second, Response::Enrolled));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn frame_too_large() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &Response::Hello { version: 1 }).unwrap();
+
+        let err = read_frame::<_, Response>(&mut buffer.as_slice(), 1).unwrap_err();
+        assert!(matches!(err, Error::FrameTooLarge { max_len: 1, .. }));
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &Response::Hello { version: 1 }).unwrap();
+        buffer.pop();
+
+        let err =
+            read_frame::<_, Response>(&mut buffer.as_slice(), DEFAULT_MAX_FRAME_LEN).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Io(ref err) if err.kind() == std::io::ErrorKind::UnexpectedEof
+        ));
+    }
+}
@@ -0,0 +1,25 @@
+//! Client/server protocol for encrypted biometric authentication.
+//!
+//! The client owns the secret key. It uploads its server key, enrolls subjects with the encrypted
+//! LUTs generated from their templates and sends encrypted probes. The server answers with the
+//! encrypted decision computed by the CPU classic authenticator, bound to a nonce chosen by the
+//! client for every request and tagged with a key shared at upload, so that captured answers can
+//! neither be replayed nor forged on the network, see [`mac`].
+//!
+//! Templates are kept in a [`TemplateStore`], either for 1:1 authentication against a claimed
+//! subject or for 1:N identification against every subject enrolled under a key.
+
+pub mod client;
+pub mod error;
+pub mod framing;
+pub mod mac;
+pub mod message;
+pub mod server;
+pub mod store;
+
+pub use client::Client;
+pub use error::Error;
+pub use mac::MacKey;
+pub use message::{ProtocolError, Request, Response, PROTOCOL_VERSION};
+pub use server::Server;
+pub use store::{FileTemplateStore, MemoryTemplateStore, TemplateStore};
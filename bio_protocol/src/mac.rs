@@ -0,0 +1,67 @@
+//! Authentication of the decisions returned by the server.
+//!
+//! A bound decision on its own is no proof of anything: whoever sees the encrypted nonce of a
+//! request can answer it with `trivial(1) + 2 * nonce_bit` for every bit, without any key. The
+//! server therefore tags every decision with a key shared with the client when the server key is
+//! uploaded, over the subject, the encrypted nonce of the request and the bound decision. The
+//! client drops answers whose tag does not verify before decrypting them.
+//!
+//! # Attacker model
+//!
+//! The tag protects against a network attacker who observes, replays, reorders or rewrites the
+//! authentication traffic, but not the key upload, which like the enrollment has to go over a
+//! trusted channel. The server is trusted to evaluate the templates honestly: it holds the tag
+//! key and computes the decision, a malicious server can return any decision it likes.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tfhe::core_crypto::prelude::new_seeder;
+use tfhe::shortint::Ciphertext;
+
+/// Domain separation of the tags, changes with the tagged message layout.
+const TAG_DOMAIN: &[u8] = b"bio_protocol decision tag v1";
+
+/// Secret key of the tags, known to the client and the server only.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MacKey(pub [u8; 32]);
+
+impl MacKey {
+    /// Draws a key from the seeder of the platform.
+    pub fn generate() -> Self {
+        let mut seeder = new_seeder();
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(&seeder.seed().0.to_le_bytes());
+        key[16..].copy_from_slice(&seeder.seed().0.to_le_bytes());
+        Self(key)
+    }
+
+    /// Tag of the decision bound to `nonce` for `subject_id`.
+    ///
+    /// SHA3 is not subject to length extension, so hashing the key in front of the message is a
+    /// MAC.
+    pub fn tag(&self, subject_id: &str, nonce: &[Ciphertext], decision: &[Ciphertext]) -> [u8; 32] {
+        let message = bincode::serialize(&(subject_id, nonce, decision))
+            .expect("ciphertexts are serializable");
+        let mut hasher = Sha3_256::new();
+        hasher.update(TAG_DOMAIN);
+        hasher.update(self.0);
+        hasher.update(message);
+        hasher.finalize().into()
+    }
+
+    /// Checks the tag of a decision, in time independent of where the tags differ.
+    pub fn verify(
+        &self,
+        subject_id: &str,
+        nonce: &[Ciphertext],
+        decision: &[Ciphertext],
+        tag: &[u8; 32],
+    ) -> bool {
+        let expected = self.tag(subject_id, nonce, decision);
+        expected
+            .iter()
+            .zip(tag)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+}
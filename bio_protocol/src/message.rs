@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use tfhe::shortint;
+use tfhe::shortint::server_key::LookupTableOwned;
+use tfhe::shortint::Ciphertext;
+use crate::mac::MacKey;
+
+/// Version of the message types below, exchanged in the [`Request::Hello`] handshake.
+pub const PROTOCOL_VERSION: u16 = 6;
+
+#[derive(Serialize, Deserialize)]
+pub enum Request {
+    /// First message of every connection
+    Hello { version: u16 },
+    /// Stores the server key of a client under `key_id`, which must not hold a key yet, with the
+    /// key the decisions evaluated with it are tagged with
+    UploadServerKey {
+        key_id: String,
+        server_key: Box<shortint::ServerKey>,
+        mac_key: MacKey,
+    },
+    /// Stores the encrypted LUTs generated from the template of a new `subject_id`, they are
+    /// evaluated with the server key stored under `key_id`
+    Enroll {
+        subject_id: String,
+        key_id: String,
+        luts: Vec<Vec<LookupTableOwned>>,
+        threshold: usize,
+        num_sum_blocks: usize,
+    },
+    /// Replaces the template of a `subject_id` enrolled under `key_id`
+    Update {
+        subject_id: String,
+        key_id: String,
+        luts: Vec<Vec<LookupTableOwned>>,
+        threshold: usize,
+        num_sum_blocks: usize,
+    },
+    /// Removes a `subject_id` enrolled under `key_id`
+    Revoke { subject_id: String, key_id: String },
+    ListSubjects,
+    /// Compares an encrypted probe against the template of `subject_id`, the decision is bound
+    /// to the encrypted nonce bits, see `tfhe::core_crypto::biometrics::cpu::challenge`
+    Authenticate {
+        subject_id: String,
+        probe: Vec<Vec<Ciphertext>>,
+        nonce: Vec<Ciphertext>,
+    },
+    /// Compares an encrypted probe against the template of every subject enrolled under
+    /// `key_id`, every decision is bound to the same nonce. Templates that cannot be evaluated
+    /// get an error instead of a decision, the other subjects are still compared
+    Identify {
+        key_id: String,
+        probe: Vec<Vec<Ciphertext>>,
+        nonce: Vec<Ciphertext>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Response {
+    Hello { version: u16 },
+    KeyStored,
+    Enrolled,
+    Updated,
+    Revoked,
+    Subjects(Vec<String>),
+    Decision(TaggedDecision),
+    /// Decision or error of every compared subject, sorted by subject id
+    Identification {
+        decisions: Vec<(String, Result<TaggedDecision, ProtocolError>)>,
+    },
+    Error(ProtocolError),
+}
+
+/// Encrypted decision bound to the nonce of the request, only the owner of the client key can
+/// decrypt and verify it. `tag` authenticates it for the subject and the nonce, see
+/// [`MacKey::tag`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaggedDecision {
+    pub decision: Vec<Ciphertext>,
+    pub tag: [u8; 32],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtocolError {
+    VersionMismatch { client: u16, server: u16 },
+    /// A request other than [`Request::Hello`] was sent before the handshake
+    HandshakeRequired,
+    UnknownKey(String),
+    /// [`Request::UploadServerKey`] for a key id that already holds a key or owns stored templates
+    KeyAlreadyStored(String),
+    UnknownSubject(String),
+    /// The subject is enrolled under another key id than the one of the request
+    KeyMismatch(String),
+    AlreadyEnrolled(String),
+    /// The stored template does not match its hash
+    CorruptedTemplate(String),
+    /// The template of the subject was enrolled for other parameters than its server key
+    ParameterMismatch(String),
+    /// The probe does not have the shape of the enrolled LUTs
+    MalformedProbe { expected: Vec<usize>, got: Vec<usize> },
+    /// The nonce does not have the expected number of blocks
+    MalformedNonce { expected: usize, got: usize },
+    /// The LUTs, the threshold or the sum width of an enrolled template do not fit its server key
+    InvalidTemplate(String),
+    /// The probe has the shape of the template but was not encrypted for its server key
+    InvalidProbe(String),
+    Internal(String),
+}
+
+impl std::fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VersionMismatch { client, server } => {
+                write!(f, "protocol version mismatch, client: {client}, server: {server}")
+            }
+            Self::HandshakeRequired => write!(f, "handshake required"),
+            Self::UnknownKey(key_id) => write!(f, "unknown key: {key_id}"),
+            Self::KeyAlreadyStored(key_id) => write!(f, "key already stored: {key_id}"),
+            Self::UnknownSubject(subject_id) => write!(f, "unknown subject: {subject_id}"),
+            Self::KeyMismatch(subject_id) => {
+                write!(f, "subject enrolled under another key: {subject_id}")
+            }
+            Self::AlreadyEnrolled(subject_id) => write!(f, "already enrolled: {subject_id}"),
+            Self::CorruptedTemplate(subject_id) => write!(f, "corrupted template: {subject_id}"),
+            Self::ParameterMismatch(subject_id) => write!(
+                f,
+                "template of {subject_id} does not match the parameters of its server key"
+            ),
+            Self::MalformedProbe { expected, got } => write!(
+                f,
+                "malformed probe, expected blocks per feature {expected:?}, got {got:?}"
+            ),
+            Self::MalformedNonce { expected, got } => {
+                write!(f, "malformed nonce, expected {expected} blocks, got {got}")
+            }
+            Self::InvalidTemplate(msg) => write!(f, "invalid template: {msg}"),
+            Self::InvalidProbe(msg) => write!(f, "invalid probe: {msg}"),
+            Self::Internal(msg) => write!(f, "internal server error: {msg}"),
+        }
+    }
+}
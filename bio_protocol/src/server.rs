@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use tfhe::core_crypto::biometrics::cpu::validation::{validate_template, AuthenticationError};
+use tfhe::core_crypto::biometrics::cpu::{all_in_one_classic, challenge};
+use tfhe::shortint::Ciphertext;
+use tfhe::integer;
+use crate::error::Error;
+use crate::framing::{read_frame, write_frame, DEFAULT_MAX_FRAME_LEN};
+use crate::message::{ProtocolError, Request, Response, TaggedDecision, PROTOCOL_VERSION};
+use crate::store::{
+    MemoryTemplateStore, ParameterFingerprint, StoreError, StoredKey, StoredTemplate,
+    TemplateStore,
+};
+
+struct ServerState {
+    // Keys of the template store already loaded
+    keys: Mutex<HashMap<String, Arc<StoredKey>>>,
+    templates: Box<dyn TemplateStore>,
+}
+
+/// Reference authentication server, every connection is handled on its own thread.
+pub struct Server {
+    listener: TcpListener,
+    state: Arc<ServerState>,
+    max_frame_len: u64,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> std::io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            state: Arc::new(ServerState {
+                keys: Mutex::default(),
+                templates: Box::new(MemoryTemplateStore::default()),
+            }),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        })
+    }
+
+    pub fn with_max_frame_len(mut self, max_frame_len: u64) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Keeps the templates and the uploaded keys in `store` instead of memory, e.g. a
+    /// [`FileTemplateStore`](crate::store::FileTemplateStore) so that enrollments and their keys
+    /// outlive the server.
+    pub fn with_template_store<S: TemplateStore + 'static>(mut self, store: S) -> Self {
+        Arc::get_mut(&mut self.state)
+            .expect("the state is only shared once serving")
+            .templates = Box::new(store);
+        self
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections until the listener fails.
+    pub fn serve(self) -> std::io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let state = Arc::clone(&self.state);
+            let max_frame_len = self.max_frame_len;
+            thread::spawn(move || {
+                // Errors only concern this connection, the server keeps running
+                let _ = handle_connection(stream, &state, max_frame_len);
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs [`Self::serve`] on a background thread.
+    pub fn spawn(self) -> JoinHandle<std::io::Result<()>> {
+        thread::spawn(move || self.serve())
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    state: &ServerState,
+    max_frame_len: u64,
+) -> Result<(), Error> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    let mut handshake_done = false;
+
+    loop {
+        let request: Request = match read_frame(&mut reader, max_frame_len) {
+            Ok(request) => request,
+            Err(Error::Io(err)) if err.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let response = match request {
+            Request::Hello { version } if version == PROTOCOL_VERSION => {
+                handshake_done = true;
+                Response::Hello {
+                    version: PROTOCOL_VERSION,
+                }
+            }
+            Request::Hello { version } => Response::Error(ProtocolError::VersionMismatch {
+                client: version,
+                server: PROTOCOL_VERSION,
+            }),
+            _ if !handshake_done => Response::Error(ProtocolError::HandshakeRequired),
+            request => handle_request(request, state).unwrap_or_else(Response::Error),
+        };
+        write_frame(&mut writer, &response)?;
+    }
+}
+
+fn handle_request(request: Request, state: &ServerState) -> Result<Response, ProtocolError> {
+    match request {
+        Request::Hello { .. } => unreachable!("handshake is handled by the connection"),
+        Request::UploadServerKey {
+            key_id,
+            server_key,
+            mac_key,
+        } => {
+            // Templates are owned by their key id, replacing the key would hand them over. The
+            // store refuses key ids uploaded before a restart, templates left without their key
+            // keep the key id taken
+            let mut keys = state.keys.lock().unwrap();
+            if keys.contains_key(&key_id) || has_templates(state, &key_id)? {
+                return Err(ProtocolError::KeyAlreadyStored(key_id));
+            }
+            let stored = StoredKey {
+                server_key: *server_key,
+                mac_key,
+            };
+            state
+                .templates
+                .store_key(&key_id, &stored)
+                .map_err(store_error)?;
+            keys.insert(key_id, Arc::new(stored));
+            Ok(Response::KeyStored)
+        }
+        Request::Enroll {
+            subject_id,
+            key_id,
+            luts,
+            threshold,
+            num_sum_blocks,
+        } => {
+            let server_key = &uploaded_key(state, &key_id)?.server_key;
+            validate_template(server_key, &luts, threshold as u64, num_sum_blocks)
+                .map_err(|err| ProtocolError::InvalidTemplate(err.to_string()))?;
+            let template = StoredTemplate {
+                key_id,
+                luts,
+                threshold,
+                num_sum_blocks,
+            };
+            state
+                .templates
+                .enroll(
+                    &subject_id,
+                    &template,
+                    ParameterFingerprint::of_server_key(server_key),
+                )
+                .map_err(store_error)?;
+            Ok(Response::Enrolled)
+        }
+        Request::Update {
+            subject_id,
+            key_id,
+            luts,
+            threshold,
+            num_sum_blocks,
+        } => {
+            let server_key = &uploaded_key(state, &key_id)?.server_key;
+            validate_template(server_key, &luts, threshold as u64, num_sum_blocks)
+                .map_err(|err| ProtocolError::InvalidTemplate(err.to_string()))?;
+            let template = StoredTemplate {
+                key_id,
+                luts,
+                threshold,
+                num_sum_blocks,
+            };
+            state
+                .templates
+                .update(
+                    &subject_id,
+                    &template,
+                    ParameterFingerprint::of_server_key(server_key),
+                )
+                .map_err(store_error)?;
+            Ok(Response::Updated)
+        }
+        Request::Revoke { subject_id, key_id } => {
+            state
+                .templates
+                .revoke(&subject_id, &key_id)
+                .map_err(store_error)?;
+            Ok(Response::Revoked)
+        }
+        Request::ListSubjects => {
+            let subject_ids = state.templates.list().map_err(store_error)?;
+            Ok(Response::Subjects(subject_ids))
+        }
+        Request::Authenticate {
+            subject_id,
+            probe,
+            nonce,
+        } => {
+            let (template, uploaded) = load_template(state, &subject_id)?;
+            check_probe(&template, &probe)?;
+            check_nonce(&nonce)?;
+
+            let (decision, _) = all_in_one_classic::try_authenticate(
+                uploaded.server_key.clone(),
+                probe,
+                template.luts,
+                template.threshold,
+                template.num_sum_blocks,
+            )
+            .map_err(authentication_error)?;
+            let decision = challenge::bind_decision(&uploaded.server_key, &decision, &nonce);
+            let tag = uploaded.mac_key.tag(&subject_id, &nonce, &decision);
+            Ok(Response::Decision(TaggedDecision { decision, tag }))
+        }
+        Request::Identify {
+            key_id,
+            probe,
+            nonce,
+        } => {
+            let uploaded = uploaded_key(state, &key_id)?;
+            check_nonce(&nonce)?;
+            let big_server_key =
+                integer::ServerKey::new_radix_server_key_from_shortint(uploaded.server_key.clone());
+
+            let mut decisions = Vec::new();
+            for subject_id in state.templates.list().map_err(store_error)? {
+                // Subjects of other keys are skipped from their record header, a bad template
+                // only fails its own decision
+                let decision = match state.templates.key_id(&subject_id) {
+                    Ok(template_key_id) if template_key_id != key_id => continue,
+                    Ok(_) => identify_subject(state, &subject_id, &big_server_key, &probe, &nonce)
+                        .map(|decision| {
+                            let tag = uploaded.mac_key.tag(&subject_id, &nonce, &decision);
+                            TaggedDecision { decision, tag }
+                        }),
+                    // Revoked since the listing
+                    Err(StoreError::UnknownSubject(_)) => continue,
+                    Err(err) => Err(store_error(err)),
+                };
+                decisions.push((subject_id, decision));
+            }
+            Ok(Response::Identification { decisions })
+        }
+    }
+}
+
+fn identify_subject(
+    state: &ServerState,
+    subject_id: &str,
+    big_server_key: &integer::ServerKey,
+    probe: &[Vec<Ciphertext>],
+    nonce: &[Ciphertext],
+) -> Result<Vec<Ciphertext>, ProtocolError> {
+    let (template, _) = load_template(state, subject_id)?;
+    check_probe(&template, probe)?;
+    let decision = all_in_one_classic::try_authenticate_with_key(
+        big_server_key,
+        probe.to_vec(),
+        &template.luts,
+        template.threshold,
+        template.num_sum_blocks,
+    )
+    .map_err(authentication_error)?;
+    Ok(challenge::bind_decision(
+        big_server_key.as_ref(),
+        &decision,
+        nonce,
+    ))
+}
+
+fn uploaded_key(state: &ServerState, key_id: &str) -> Result<Arc<StoredKey>, ProtocolError> {
+    let mut keys = state.keys.lock().unwrap();
+    if let Some(key) = keys.get(key_id) {
+        return Ok(Arc::clone(key));
+    }
+    // Uploaded before a restart
+    let key = Arc::new(state.templates.load_key(key_id).map_err(store_error)?);
+    keys.insert(key_id.to_owned(), Arc::clone(&key));
+    Ok(key)
+}
+
+/// Whether a subject is enrolled under `key_id`, damaged records are left out.
+fn has_templates(state: &ServerState, key_id: &str) -> Result<bool, ProtocolError> {
+    for subject_id in state.templates.list().map_err(store_error)? {
+        match state.templates.key_id(&subject_id) {
+            Ok(template_key_id) if template_key_id == key_id => return Ok(true),
+            Ok(_) | Err(StoreError::UnknownSubject(_) | StoreError::Corrupted(_)) => {}
+            Err(err) => return Err(store_error(err)),
+        }
+    }
+    Ok(false)
+}
+
+/// Loads the template of `subject_id` and the keys it is evaluated with, the server key must
+/// have the parameters the template was enrolled for.
+fn load_template(
+    state: &ServerState,
+    subject_id: &str,
+) -> Result<(StoredTemplate, Arc<StoredKey>), ProtocolError> {
+    let (template, fingerprint) = state.templates.load(subject_id).map_err(store_error)?;
+    let uploaded = uploaded_key(state, &template.key_id)?;
+    if ParameterFingerprint::of_server_key(&uploaded.server_key) != fingerprint {
+        return Err(ProtocolError::ParameterMismatch(subject_id.to_owned()));
+    }
+    Ok((template, uploaded))
+}
+
+fn check_probe(template: &StoredTemplate, probe: &[Vec<Ciphertext>]) -> Result<(), ProtocolError> {
+    let expected = template.blocks_per_feature();
+    let got: Vec<usize> = probe.iter().map(Vec::len).collect();
+    if expected != got {
+        return Err(ProtocolError::MalformedProbe { expected, got });
+    }
+    Ok(())
+}
+
+fn check_nonce(nonce: &[Ciphertext]) -> Result<(), ProtocolError> {
+    if nonce.len() != challenge::NONCE_BITS {
+        return Err(ProtocolError::MalformedNonce {
+            expected: challenge::NONCE_BITS,
+            got: nonce.len(),
+        });
+    }
+    Ok(())
+}
+
+/// The template was validated on enrollment, what is left to fail is the probe or the server.
+fn authentication_error(err: AuthenticationError) -> ProtocolError {
+    match err {
+        AuthenticationError::WorkerPanicked => ProtocolError::Internal(err.to_string()),
+        err => ProtocolError::InvalidProbe(err.to_string()),
+    }
+}
+
+fn store_error(err: StoreError) -> ProtocolError {
+    match err {
+        StoreError::AlreadyEnrolled(subject_id) => ProtocolError::AlreadyEnrolled(subject_id),
+        StoreError::UnknownSubject(subject_id) => ProtocolError::UnknownSubject(subject_id),
+        StoreError::KeyMismatch(subject_id) => ProtocolError::KeyMismatch(subject_id),
+        StoreError::Corrupted(subject_id) => ProtocolError::CorruptedTemplate(subject_id),
+        StoreError::KeyAlreadyStored(key_id) => ProtocolError::KeyAlreadyStored(key_id),
+        StoreError::UnknownKey(key_id) => ProtocolError::UnknownKey(key_id),
+        err => ProtocolError::Internal(err.to_string()),
+    }
+}
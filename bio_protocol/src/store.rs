@@ -0,0 +1,643 @@
+//! Storage of the encrypted templates of enrolled subjects.
+//!
+//! Every template is stored as a [`StoredTemplate`] serialized with bincode, next to its key id,
+//! the [`ParameterFingerprint`] of the server key it was enrolled for and a SHA3-256 digest of
+//! all three. Loading a template checks the digest, so a truncated or accidentally damaged blob,
+//! or the blob of another subject, is reported instead of being evaluated.
+//!
+//! The digest is not keyed: whoever can write to the storage can replace a template together
+//! with its digest. It is no protection against a malicious storage backend, only the encryption
+//! of the LUTs is.
+//!
+//! The keys uploaded under a key id are stored next to the templates as a [`StoredKey`], so that
+//! the templates of a key id stay bound to the keys of their owner across restarts.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tfhe::shortint;
+use tfhe::shortint::server_key::LookupTableOwned;
+use crate::mac::MacKey;
+
+/// Extension of the template files of a [`FileTemplateStore`].
+pub const TEMPLATE_FILE_EXTENSION: &str = "tpl";
+
+/// Extension of the key files of a [`FileTemplateStore`].
+pub const KEY_FILE_EXTENSION: &str = "key";
+
+/// Keys uploaded by a client under one key id.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredKey {
+    pub server_key: shortint::ServerKey,
+    pub mac_key: MacKey,
+}
+
+/// Encrypted LUTs generated from the template of a subject, evaluated with the server key stored
+/// under `key_id`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredTemplate {
+    pub key_id: String,
+    pub luts: Vec<Vec<LookupTableOwned>>,
+    pub threshold: usize,
+    pub num_sum_blocks: usize,
+}
+
+impl StoredTemplate {
+    /// Number of probe blocks expected for every feature.
+    pub fn blocks_per_feature(&self) -> Vec<usize> {
+        self.luts.iter().map(Vec::len).collect()
+    }
+}
+
+/// Hash of the parameters of a server key, a template can only be evaluated with a key of the
+/// parameters it was encrypted for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ParameterFingerprint(pub [u8; 32]);
+
+impl ParameterFingerprint {
+    pub fn of_server_key(server_key: &shortint::ServerKey) -> Self {
+        let params = (
+            server_key.ciphertext_lwe_dimension(),
+            server_key.bootstrapping_key.glwe_size(),
+            server_key.bootstrapping_key.polynomial_size(),
+            server_key.message_modulus,
+            server_key.carry_modulus,
+            server_key.ciphertext_modulus,
+            server_key.pbs_order,
+        );
+        let serialized = bincode::serialize(&params).expect("parameters are serializable");
+        Self(Sha3_256::digest(serialized).into())
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(std::io::Error),
+    Serialization(bincode::Error),
+    /// [`TemplateStore::enroll`] was called for a subject that is already enrolled
+    AlreadyEnrolled(String),
+    UnknownSubject(String),
+    /// The subject is enrolled under another key id than the one of the request
+    KeyMismatch(String),
+    /// The stored blob does not match its digest or belongs to another subject
+    Corrupted(String),
+    /// [`TemplateStore::store_key`] was called for a key id that already has keys
+    KeyAlreadyStored(String),
+    UnknownKey(String),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Serialization(err) => write!(f, "serialization error: {err}"),
+            Self::AlreadyEnrolled(subject_id) => write!(f, "already enrolled: {subject_id}"),
+            Self::UnknownSubject(subject_id) => write!(f, "unknown subject: {subject_id}"),
+            Self::KeyMismatch(subject_id) => {
+                write!(f, "subject enrolled under another key: {subject_id}")
+            }
+            Self::Corrupted(subject_id) => write!(f, "corrupted template: {subject_id}"),
+            Self::KeyAlreadyStored(key_id) => write!(f, "key already stored: {key_id}"),
+            Self::UnknownKey(key_id) => write!(f, "unknown key: {key_id}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<std::io::Error> for StoreError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<bincode::Error> for StoreError {
+    fn from(err: bincode::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+/// Templates keyed by subject id, and the keys they are evaluated with keyed by key id.
+pub trait TemplateStore: Send + Sync {
+    /// Stores the keys of a key id that has none yet, keys are never replaced.
+    fn store_key(&self, key_id: &str, key: &StoredKey) -> Result<(), StoreError>;
+
+    /// Loads the keys stored under `key_id`.
+    fn load_key(&self, key_id: &str) -> Result<StoredKey, StoreError>;
+
+    /// Stores the template of a subject that is not enrolled yet.
+    fn enroll(
+        &self,
+        subject_id: &str,
+        template: &StoredTemplate,
+        fingerprint: ParameterFingerprint,
+    ) -> Result<(), StoreError>;
+
+    /// Replaces the template of an enrolled subject, e.g. after a new capture. The stored
+    /// template must have the key id of `template`, a subject only moves to another key by being
+    /// revoked and enrolled again.
+    fn update(
+        &self,
+        subject_id: &str,
+        template: &StoredTemplate,
+        fingerprint: ParameterFingerprint,
+    ) -> Result<(), StoreError>;
+
+    /// Removes a subject enrolled under `key_id`, its template can no longer be loaded.
+    fn revoke(&self, subject_id: &str, key_id: &str) -> Result<(), StoreError>;
+
+    /// Ids of the enrolled subjects, sorted.
+    fn list(&self) -> Result<Vec<String>, StoreError>;
+
+    /// Key id a subject is enrolled under, after checking the integrity of its record but
+    /// without deserializing its LUTs.
+    fn key_id(&self, subject_id: &str) -> Result<String, StoreError>;
+
+    /// Loads the template of a subject and the fingerprint it was enrolled with, after checking
+    /// its integrity.
+    fn load(&self, subject_id: &str) -> Result<(StoredTemplate, ParameterFingerprint), StoreError>;
+
+    /// Checks the integrity of every stored template, returns the ids of the corrupted ones.
+    fn verify(&self) -> Result<Vec<String>, StoreError> {
+        let mut corrupted = Vec::new();
+        for subject_id in self.list()? {
+            match self.load(&subject_id) {
+                Ok(_) => {}
+                Err(StoreError::Corrupted(_) | StoreError::Serialization(_)) => {
+                    corrupted.push(subject_id)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(corrupted)
+    }
+}
+
+/// What is actually stored for a subject, `digest` is the hash of the key id, the fingerprint and
+/// the blob. The key id is repeated outside of the blob to filter subjects without deserializing
+/// their LUTs.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    subject_id: String,
+    key_id: String,
+    fingerprint: ParameterFingerprint,
+    digest: [u8; 32],
+    blob: Vec<u8>,
+}
+
+fn digest(key_id: &str, fingerprint: &ParameterFingerprint, blob: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update((key_id.len() as u64).to_le_bytes());
+    hasher.update(key_id);
+    hasher.update(fingerprint.0);
+    hasher.update(blob);
+    hasher.finalize().into()
+}
+
+fn seal(
+    subject_id: &str,
+    template: &StoredTemplate,
+    fingerprint: ParameterFingerprint,
+) -> Result<Vec<u8>, StoreError> {
+    let blob = bincode::serialize(template)?;
+    let record = Record {
+        subject_id: subject_id.to_owned(),
+        key_id: template.key_id.clone(),
+        fingerprint,
+        digest: digest(&template.key_id, &fingerprint, &blob),
+        blob,
+    };
+    Ok(bincode::serialize(&record)?)
+}
+
+/// Deserializes a record and checks its digest, the blob is left serialized.
+fn open_record(subject_id: &str, bytes: &[u8]) -> Result<Record, StoreError> {
+    let corrupted = || StoreError::Corrupted(subject_id.to_owned());
+    let record: Record = bincode::deserialize(bytes).map_err(|_| corrupted())?;
+    if record.subject_id != subject_id
+        || record.digest != digest(&record.key_id, &record.fingerprint, &record.blob)
+    {
+        return Err(corrupted());
+    }
+    Ok(record)
+}
+
+fn open(
+    subject_id: &str,
+    bytes: &[u8],
+) -> Result<(StoredTemplate, ParameterFingerprint), StoreError> {
+    let record = open_record(subject_id, bytes)?;
+    let template: StoredTemplate = bincode::deserialize(&record.blob)?;
+    if template.key_id != record.key_id {
+        return Err(StoreError::Corrupted(subject_id.to_owned()));
+    }
+    Ok((template, record.fingerprint))
+}
+
+fn check_key_id(subject_id: &str, bytes: &[u8], key_id: &str) -> Result<(), StoreError> {
+    if open_record(subject_id, bytes)?.key_id != key_id {
+        return Err(StoreError::KeyMismatch(subject_id.to_owned()));
+    }
+    Ok(())
+}
+
+/// Key files hold the key id followed by the keys, the key id catches swapped files.
+fn open_key_record(key_id: &str, bytes: &[u8]) -> Result<StoredKey, StoreError> {
+    let (stored_key_id, key): (String, StoredKey) =
+        bincode::deserialize(bytes).map_err(|_| StoreError::Corrupted(key_id.to_owned()))?;
+    if stored_key_id != key_id {
+        return Err(StoreError::Corrupted(key_id.to_owned()));
+    }
+    Ok(key)
+}
+
+/// Store kept in memory, the templates and keys are lost when it is dropped.
+#[derive(Default)]
+pub struct MemoryTemplateStore {
+    records: Mutex<HashMap<String, Vec<u8>>>,
+    keys: Mutex<HashMap<String, StoredKey>>,
+}
+
+impl TemplateStore for MemoryTemplateStore {
+    fn store_key(&self, key_id: &str, key: &StoredKey) -> Result<(), StoreError> {
+        let mut keys = self.keys.lock().unwrap();
+        if keys.contains_key(key_id) {
+            return Err(StoreError::KeyAlreadyStored(key_id.to_owned()));
+        }
+        keys.insert(key_id.to_owned(), key.clone());
+        Ok(())
+    }
+
+    fn load_key(&self, key_id: &str) -> Result<StoredKey, StoreError> {
+        self.keys
+            .lock()
+            .unwrap()
+            .get(key_id)
+            .cloned()
+            .ok_or_else(|| StoreError::UnknownKey(key_id.to_owned()))
+    }
+
+    fn enroll(
+        &self,
+        subject_id: &str,
+        template: &StoredTemplate,
+        fingerprint: ParameterFingerprint,
+    ) -> Result<(), StoreError> {
+        let record = seal(subject_id, template, fingerprint)?;
+        let mut records = self.records.lock().unwrap();
+        if records.contains_key(subject_id) {
+            return Err(StoreError::AlreadyEnrolled(subject_id.to_owned()));
+        }
+        records.insert(subject_id.to_owned(), record);
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        subject_id: &str,
+        template: &StoredTemplate,
+        fingerprint: ParameterFingerprint,
+    ) -> Result<(), StoreError> {
+        let record = seal(subject_id, template, fingerprint)?;
+        match self.records.lock().unwrap().get_mut(subject_id) {
+            Some(stored) => {
+                check_key_id(subject_id, stored, &template.key_id)?;
+                *stored = record;
+                Ok(())
+            }
+            None => Err(StoreError::UnknownSubject(subject_id.to_owned())),
+        }
+    }
+
+    fn revoke(&self, subject_id: &str, key_id: &str) -> Result<(), StoreError> {
+        let mut records = self.records.lock().unwrap();
+        let stored = records
+            .get(subject_id)
+            .ok_or_else(|| StoreError::UnknownSubject(subject_id.to_owned()))?;
+        check_key_id(subject_id, stored, key_id)?;
+        records.remove(subject_id);
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<String>, StoreError> {
+        let mut subject_ids: Vec<String> = self.records.lock().unwrap().keys().cloned().collect();
+        subject_ids.sort();
+        Ok(subject_ids)
+    }
+
+    fn key_id(&self, subject_id: &str) -> Result<String, StoreError> {
+        let records = self.records.lock().unwrap();
+        let record = records
+            .get(subject_id)
+            .ok_or_else(|| StoreError::UnknownSubject(subject_id.to_owned()))?;
+        Ok(open_record(subject_id, record)?.key_id)
+    }
+
+    fn load(&self, subject_id: &str) -> Result<(StoredTemplate, ParameterFingerprint), StoreError> {
+        let records = self.records.lock().unwrap();
+        let record = records
+            .get(subject_id)
+            .ok_or_else(|| StoreError::UnknownSubject(subject_id.to_owned()))?;
+        open(subject_id, record)
+    }
+}
+
+/// Store keeping one file per subject and one file per key id in a directory.
+///
+/// File names are the hex encoded subject or key ids, so any id maps to a file of the directory.
+/// Files are written next to their final name and renamed, a crash never leaves a partial
+/// template or key.
+pub struct FileTemplateStore {
+    dir: PathBuf,
+    // Serializes the check and the write of enroll and update
+    write_lock: Mutex<()>,
+}
+
+impl FileTemplateStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, StoreError> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    fn path(&self, subject_id: &str) -> PathBuf {
+        let name: String = subject_id.bytes().map(|b| format!("{b:02x}")).collect();
+        self.dir.join(name).with_extension(TEMPLATE_FILE_EXTENSION)
+    }
+
+    fn key_path(&self, key_id: &str) -> PathBuf {
+        let name: String = key_id.bytes().map(|b| format!("{b:02x}")).collect();
+        self.dir.join(name).with_extension(KEY_FILE_EXTENSION)
+    }
+
+    fn read(&self, subject_id: &str) -> Result<Vec<u8>, StoreError> {
+        match fs::read(self.path(subject_id)) {
+            Ok(record) => Ok(record),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Err(StoreError::UnknownSubject(subject_id.to_owned()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write(&self, subject_id: &str, record: &[u8]) -> Result<(), StoreError> {
+        write_atomically(&self.path(subject_id), record)
+    }
+}
+
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), StoreError> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn decode_file_name(path: &Path) -> Option<String> {
+    if path.extension()? != TEMPLATE_FILE_EXTENSION {
+        return None;
+    }
+    let hex = path.file_stem()?.to_str()?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+impl TemplateStore for FileTemplateStore {
+    fn store_key(&self, key_id: &str, key: &StoredKey) -> Result<(), StoreError> {
+        let record = bincode::serialize(&(key_id, key))?;
+        let _guard = self.write_lock.lock().unwrap();
+        let path = self.key_path(key_id);
+        if path.exists() {
+            return Err(StoreError::KeyAlreadyStored(key_id.to_owned()));
+        }
+        write_atomically(&path, &record)
+    }
+
+    fn load_key(&self, key_id: &str) -> Result<StoredKey, StoreError> {
+        match fs::read(self.key_path(key_id)) {
+            Ok(bytes) => open_key_record(key_id, &bytes),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Err(StoreError::UnknownKey(key_id.to_owned()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn enroll(
+        &self,
+        subject_id: &str,
+        template: &StoredTemplate,
+        fingerprint: ParameterFingerprint,
+    ) -> Result<(), StoreError> {
+        let record = seal(subject_id, template, fingerprint)?;
+        let _guard = self.write_lock.lock().unwrap();
+        if self.path(subject_id).exists() {
+            return Err(StoreError::AlreadyEnrolled(subject_id.to_owned()));
+        }
+        self.write(subject_id, &record)
+    }
+
+    fn update(
+        &self,
+        subject_id: &str,
+        template: &StoredTemplate,
+        fingerprint: ParameterFingerprint,
+    ) -> Result<(), StoreError> {
+        let record = seal(subject_id, template, fingerprint)?;
+        let _guard = self.write_lock.lock().unwrap();
+        check_key_id(subject_id, &self.read(subject_id)?, &template.key_id)?;
+        self.write(subject_id, &record)
+    }
+
+    fn revoke(&self, subject_id: &str, key_id: &str) -> Result<(), StoreError> {
+        let _guard = self.write_lock.lock().unwrap();
+        check_key_id(subject_id, &self.read(subject_id)?, key_id)?;
+        fs::remove_file(self.path(subject_id))?;
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<String>, StoreError> {
+        let mut subject_ids = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            if let Some(subject_id) = decode_file_name(&entry?.path()) {
+                subject_ids.push(subject_id);
+            }
+        }
+        subject_ids.sort();
+        Ok(subject_ids)
+    }
+
+    fn key_id(&self, subject_id: &str) -> Result<String, StoreError> {
+        Ok(open_record(subject_id, &self.read(subject_id)?)?.key_id)
+    }
+
+    fn load(&self, subject_id: &str) -> Result<(StoredTemplate, ParameterFingerprint), StoreError> {
+        open(subject_id, &self.read(subject_id)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tfhe::core_crypto::biometrics::common;
+    use tfhe::shortint::engine::ShortintEngine;
+    use tfhe::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use tfhe::shortint::ClientKey;
+
+    fn template(client_key: &ClientKey, key_id: &str) -> StoredTemplate {
+        let mut engine = ShortintEngine::new();
+        let row = [1u64, 2, 3, 0];
+        let rows = vec![vec![&row[..]], vec![&row[..]]];
+        let luts = common::generate_lookup_tables_individual_from_rows(
+            &rows,
+            client_key,
+            client_key.parameters,
+            &mut engine,
+        );
+        StoredTemplate {
+            key_id: key_id.to_owned(),
+            luts,
+            threshold: 4,
+            num_sum_blocks: 2,
+        }
+    }
+
+    #[test]
+    fn file_store_lifecycle() {
+        let dir = std::env::temp_dir().join(format!("bio_protocol_store_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = FileTemplateStore::open(&dir).unwrap();
+
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let fingerprint =
+            ParameterFingerprint::of_server_key(&shortint::ServerKey::new(&client_key));
+        let alice = template(&client_key, "device");
+
+        // Ids are not restricted to valid file names
+        store.enroll("alice", &alice, fingerprint).unwrap();
+        store.enroll("../bob", &alice, fingerprint).unwrap();
+        assert!(matches!(
+            store.enroll("alice", &alice, fingerprint),
+            Err(StoreError::AlreadyEnrolled(_))
+        ));
+        assert_eq!(store.list().unwrap(), ["../bob", "alice"]);
+
+        let (loaded, loaded_fingerprint) = store.load("alice").unwrap();
+        assert_eq!(loaded_fingerprint, fingerprint);
+        assert_eq!(loaded.key_id, "device");
+        assert_eq!(loaded.blocks_per_feature(), [1, 1]);
+
+        let recaptured = template(&client_key, "device");
+        store.update("alice", &recaptured, fingerprint).unwrap();
+        assert_eq!(store.key_id("alice").unwrap(), "device");
+        assert!(matches!(
+            store.update("carol", &recaptured, fingerprint),
+            Err(StoreError::UnknownSubject(_))
+        ));
+
+        // Only the key a subject is enrolled under can replace or revoke it
+        let other = template(&client_key, "other");
+        assert!(matches!(
+            store.update("alice", &other, fingerprint),
+            Err(StoreError::KeyMismatch(_))
+        ));
+        assert!(matches!(
+            store.revoke("alice", "other"),
+            Err(StoreError::KeyMismatch(_))
+        ));
+        assert_eq!(store.load("alice").unwrap().0.key_id, "device");
+
+        // A flipped byte is caught by the hash, a swapped file by the subject id
+        let path = store.path("alice");
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, bytes).unwrap();
+        assert!(matches!(store.load("alice"), Err(StoreError::Corrupted(_))));
+        fs::copy(store.path("../bob"), &path).unwrap();
+        assert!(matches!(store.load("alice"), Err(StoreError::Corrupted(_))));
+        assert_eq!(store.verify().unwrap(), ["alice"]);
+
+        assert!(matches!(store.key_id("alice"), Err(StoreError::Corrupted(_))));
+        assert!(matches!(
+            store.revoke("alice", "device"),
+            Err(StoreError::Corrupted(_))
+        ));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            store.load("alice"),
+            Err(StoreError::UnknownSubject(_))
+        ));
+        assert!(matches!(
+            store.revoke("alice", "device"),
+            Err(StoreError::UnknownSubject(_))
+        ));
+        store.revoke("../bob", "device").unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(store.verify().unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_store_keys() {
+        let dir =
+            std::env::temp_dir().join(format!("bio_protocol_store_keys_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = FileTemplateStore::open(&dir).unwrap();
+
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+        let key = StoredKey {
+            server_key: server_key.clone(),
+            mac_key: MacKey::generate(),
+        };
+        store.store_key("device", &key).unwrap();
+        assert!(matches!(
+            store.store_key("device", &key),
+            Err(StoreError::KeyAlreadyStored(_))
+        ));
+        assert!(matches!(
+            store.load_key("other"),
+            Err(StoreError::UnknownKey(_))
+        ));
+        // Key files are not templates
+        assert!(store.list().unwrap().is_empty());
+
+        // The keys survive reopening the store
+        let store = FileTemplateStore::open(&dir).unwrap();
+        let loaded = store.load_key("device").unwrap();
+        assert_eq!(
+            ParameterFingerprint::of_server_key(&loaded.server_key),
+            ParameterFingerprint::of_server_key(&server_key)
+        );
+        let ct = client_key.encrypt(3);
+        assert_eq!(
+            loaded.mac_key.tag("alice", &[], std::slice::from_ref(&ct)),
+            key.mac_key.tag("alice", &[], std::slice::from_ref(&ct))
+        );
+
+        // A key file copied to another key id is caught by the key id
+        fs::copy(store.key_path("device"), store.key_path("other")).unwrap();
+        assert!(matches!(
+            store.load_key("other"),
+            Err(StoreError::Corrupted(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
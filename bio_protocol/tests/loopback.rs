@@ -0,0 +1,473 @@
+use std::net::TcpStream;
+use bio_protocol::framing::{read_frame, write_frame, DEFAULT_MAX_FRAME_LEN};
+use bio_protocol::{
+    Client, Error, FileTemplateStore, MacKey, ProtocolError, Request, Response, Server,
+    PROTOCOL_VERSION,
+};
+use tfhe::core_crypto::biometrics::common;
+use tfhe::core_crypto::biometrics::cpu::challenge::{
+    encrypt_nonce, verify_decision, ChallengeError,
+};
+use tfhe::core_crypto::biometrics::cpu::tfhe_functions_classic::make_keys_classic;
+use tfhe::shortint::engine::ShortintEngine;
+use tfhe::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+use tfhe::shortint::server_key::LookupTableOwned;
+use tfhe::shortint::{ClientKey, MessageModulus};
+
+// Two features with a single output block each, the score is the sum of both table entries
+const TABLES: [[[u64; 4]; 4]; 2] = [
+    [[3, 2, 1, 0], [2, 3, 2, 1], [1, 2, 3, 2], [0, 1, 2, 3]],
+    [[3, 1, 0, 0], [1, 3, 1, 0], [0, 1, 3, 1], [0, 0, 1, 3]],
+];
+const THRESHOLD: usize = 5;
+const NUM_SUM_BLOCKS: usize = 2;
+
+#[test]
+fn loopback_authentication() {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    server.spawn();
+
+    let params = PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    let mut engine = ShortintEngine::new();
+    let (client_key, server_key) = make_keys_classic(params, &mut engine);
+
+    let template = [1usize, 2];
+    let rows: Vec<Vec<&[u64]>> = TABLES
+        .iter()
+        .zip(template)
+        .map(|(table, x)| vec![&table[x][..]])
+        .collect();
+    let luts = common::generate_lookup_tables_individual_from_rows(
+        &rows,
+        &client_key,
+        params.into(),
+        &mut engine,
+    );
+
+    let mac_key = MacKey::generate();
+    let mut client = Client::connect(addr).unwrap();
+    client
+        .upload_server_key("device", server_key, &mac_key)
+        .unwrap();
+
+    // A sum without blocks can neither hold the features nor the threshold
+    let err = client
+        .enroll("alice", "device", luts.clone(), THRESHOLD, 0)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::InvalidTemplate(_))
+    ));
+    client
+        .enroll("alice", "device", luts, THRESHOLD, NUM_SUM_BLOCKS)
+        .unwrap();
+
+    let encrypt_probe = |probe: [u64; 2]| {
+        probe
+            .iter()
+            .map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))])
+            .collect::<Vec<_>>()
+    };
+
+    // 3 + 3 >= 5
+    let nonce = 0x5a3c;
+    let accepted = client
+        .authenticate(
+            "alice",
+            encrypt_probe([1, 2]),
+            encrypt_nonce(nonce, &client_key),
+            &mac_key,
+        )
+        .unwrap();
+    assert_eq!(verify_decision(&accepted, nonce, &client_key), Ok(true));
+
+    // 1 + 1 < 5
+    let nonce = 0x0f71;
+    let rejected = client
+        .authenticate(
+            "alice",
+            encrypt_probe([3, 3]),
+            encrypt_nonce(nonce, &client_key),
+            &mac_key,
+        )
+        .unwrap();
+    assert_eq!(verify_decision(&rejected, nonce, &client_key), Ok(false));
+
+    // An accepting answer replayed for a later request is detected
+    assert_eq!(
+        verify_decision(&accepted, nonce, &client_key),
+        Err(ChallengeError::Stale)
+    );
+
+    let err = client
+        .authenticate(
+            "bob",
+            encrypt_probe([1, 2]),
+            encrypt_nonce(nonce, &client_key),
+            &mac_key,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::UnknownSubject(ref s)) if s == "bob"
+    ));
+
+    let err = client
+        .authenticate("alice", encrypt_probe([1, 2]), Vec::new(), &mac_key)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::MalformedNonce { got: 0, .. })
+    ));
+
+    // Probe blocks only encrypted over the message space are not accepted
+    let probe = [1u64, 2]
+        .iter()
+        .map(|&p| vec![client_key.encrypt(p)])
+        .collect();
+    let err = client
+        .authenticate(
+            "alice",
+            probe,
+            encrypt_nonce(nonce, &client_key),
+            &mac_key,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::InvalidProbe(_))
+    ));
+}
+
+fn template_luts(
+    template: [usize; 2],
+    client_key: &ClientKey,
+    engine: &mut ShortintEngine,
+) -> Vec<Vec<LookupTableOwned>> {
+    let rows: Vec<Vec<&[u64]>> = TABLES
+        .iter()
+        .zip(template)
+        .map(|(table, x)| vec![&table[x][..]])
+        .collect();
+    common::generate_lookup_tables_individual_from_rows(
+        &rows,
+        client_key,
+        client_key.parameters,
+        engine,
+    )
+}
+
+#[test]
+fn loopback_subject_lifecycle() {
+    let dir = std::env::temp_dir().join(format!("bio_protocol_loopback_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let server = Server::bind("127.0.0.1:0")
+        .unwrap()
+        .with_template_store(FileTemplateStore::open(&dir).unwrap());
+    let addr = server.local_addr().unwrap();
+    server.spawn();
+
+    let params = PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    let mut engine = ShortintEngine::new();
+    let (client_key, server_key) = make_keys_classic(params, &mut engine);
+
+    let mac_key = MacKey::generate();
+    let mut client = Client::connect(addr).unwrap();
+    client
+        .upload_server_key("device", server_key.clone(), &mac_key)
+        .unwrap();
+    for (subject_id, template) in [("alice", [1, 2]), ("bob", [3, 0])] {
+        let luts = template_luts(template, &client_key, &mut engine);
+        client
+            .enroll(subject_id, "device", luts, THRESHOLD, NUM_SUM_BLOCKS)
+            .unwrap();
+    }
+    let err = client
+        .enroll(
+            "bob",
+            "device",
+            template_luts([1, 2], &client_key, &mut engine),
+            THRESHOLD,
+            NUM_SUM_BLOCKS,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::AlreadyEnrolled(ref s)) if s == "bob"
+    ));
+    assert_eq!(client.list_subjects().unwrap(), ["alice", "bob"]);
+
+    let probe = || {
+        [1u64, 2]
+            .iter()
+            .map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))])
+            .collect::<Vec<_>>()
+    };
+    let identify = |client: &mut Client, nonce: u64| {
+        client
+            .identify(
+                "device",
+                probe(),
+                encrypt_nonce(nonce, &client_key),
+                &mac_key,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(subject_id, decision)| {
+                let decision = decision
+                    .map(|decision| verify_decision(&decision, nonce, &client_key).unwrap());
+                (subject_id, decision)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // 3 + 3 >= 5 for alice, 1 + 0 < 5 for bob
+    assert_eq!(
+        identify(&mut client, 0x1234),
+        [("alice".to_owned(), Ok(true)), ("bob".to_owned(), Ok(false))]
+    );
+
+    // A subject of another key is neither compared nor changed by the device key
+    let (other_client_key, other_server_key) = make_keys_classic(params, &mut engine);
+    client
+        .upload_server_key("other", other_server_key, &MacKey::generate())
+        .unwrap();
+    client
+        .enroll(
+            "carol",
+            "other",
+            template_luts([1, 2], &other_client_key, &mut engine),
+            THRESHOLD,
+            NUM_SUM_BLOCKS,
+        )
+        .unwrap();
+    assert_eq!(
+        identify(&mut client, 0x2345),
+        [("alice".to_owned(), Ok(true)), ("bob".to_owned(), Ok(false))]
+    );
+    let err = client
+        .update(
+            "carol",
+            "device",
+            template_luts([1, 2], &client_key, &mut engine),
+            THRESHOLD,
+            NUM_SUM_BLOCKS,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::KeyMismatch(ref s)) if s == "carol"
+    ));
+    let err = client.revoke("carol", "device").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::KeyMismatch(ref s)) if s == "carol"
+    ));
+    let err = client
+        .upload_server_key("other", server_key.clone(), &mac_key)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::KeyAlreadyStored(ref s)) if s == "other"
+    ));
+    client.revoke("carol", "other").unwrap();
+
+    let luts = template_luts([1, 2], &client_key, &mut engine);
+    client
+        .update("bob", "device", luts, THRESHOLD, NUM_SUM_BLOCKS)
+        .unwrap();
+    client.revoke("alice", "device").unwrap();
+    assert_eq!(identify(&mut client, 0x4321), [("bob".to_owned(), Ok(true))]);
+
+    // A damaged template only fails its own decision
+    client
+        .enroll(
+            "dave",
+            "device",
+            template_luts([1, 2], &client_key, &mut engine),
+            THRESHOLD,
+            NUM_SUM_BLOCKS,
+        )
+        .unwrap();
+    let dave_path = dir.join("64617665.tpl");
+    let mut bytes = std::fs::read(&dave_path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    std::fs::write(&dave_path, bytes).unwrap();
+    assert_eq!(
+        identify(&mut client, 0x5432),
+        [
+            ("bob".to_owned(), Ok(true)),
+            (
+                "dave".to_owned(),
+                Err(ProtocolError::CorruptedTemplate("dave".to_owned()))
+            )
+        ]
+    );
+    std::fs::remove_file(&dave_path).unwrap();
+
+    // Templates and their keys outlive the server
+    let server = Server::bind("127.0.0.1:0")
+        .unwrap()
+        .with_template_store(FileTemplateStore::open(&dir).unwrap());
+    let addr = server.local_addr().unwrap();
+    server.spawn();
+    let mut client = Client::connect(addr).unwrap();
+    assert_eq!(client.list_subjects().unwrap(), ["bob"]);
+
+    let nonce = 0x0abc;
+    let decision = client
+        .authenticate("bob", probe(), encrypt_nonce(nonce, &client_key), &mac_key)
+        .unwrap();
+    assert_eq!(verify_decision(&decision, nonce, &client_key), Ok(true));
+    assert_eq!(identify(&mut client, 0x0cba), [("bob".to_owned(), Ok(true))]);
+
+    // Nobody takes over the key id of stored templates after a restart
+    let (_, intruder_server_key) = make_keys_classic(params, &mut engine);
+    let err = client
+        .upload_server_key("device", intruder_server_key, &MacKey::generate())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::KeyAlreadyStored(ref s)) if s == "device"
+    ));
+    let err = client.revoke("bob", "intruder").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::KeyMismatch(ref s)) if s == "bob"
+    ));
+
+    // Nor the key id of templates whose key file was lost
+    std::fs::remove_file(dir.join("646576696365.key")).unwrap();
+    let server = Server::bind("127.0.0.1:0")
+        .unwrap()
+        .with_template_store(FileTemplateStore::open(&dir).unwrap());
+    let addr = server.local_addr().unwrap();
+    server.spawn();
+    let mut client = Client::connect(addr).unwrap();
+    let err = client
+        .authenticate("bob", probe(), encrypt_nonce(nonce, &client_key), &mac_key)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::UnknownKey(ref s)) if s == "device"
+    ));
+    let err = client
+        .upload_server_key("device", server_key, &mac_key)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Protocol(ProtocolError::KeyAlreadyStored(ref s)) if s == "device"
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn handshake(stream: &mut TcpStream) {
+    let request = Request::Hello {
+        version: PROTOCOL_VERSION,
+    };
+    write_frame(&mut *stream, &request).unwrap();
+    let response: Response = read_frame(&mut *stream, DEFAULT_MAX_FRAME_LEN).unwrap();
+    assert!(matches!(response, Response::Hello { .. }));
+}
+
+#[test]
+fn loopback_forged_decision() {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    server.spawn();
+
+    let params = PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    let mut engine = ShortintEngine::new();
+    let (client_key, server_key) = make_keys_classic(params, &mut engine);
+    let mac_key = MacKey::generate();
+
+    let mut client = Client::connect(addr).unwrap();
+    client
+        .upload_server_key("device", server_key.clone(), &mac_key)
+        .unwrap();
+    client
+        .enroll(
+            "alice",
+            "device",
+            template_luts([1, 2], &client_key, &mut engine),
+            THRESHOLD,
+            NUM_SUM_BLOCKS,
+        )
+        .unwrap();
+
+    // The request as seen on the network, 1 + 1 < 5
+    let nonce = 0x3c5a;
+    let encrypted_nonce = encrypt_nonce(nonce, &client_key);
+    let mut stream = TcpStream::connect(addr).unwrap();
+    handshake(&mut stream);
+    let request = Request::Authenticate {
+        subject_id: "alice".to_owned(),
+        probe: [3u64, 3]
+            .iter()
+            .map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))])
+            .collect(),
+        nonce: encrypted_nonce.clone(),
+    };
+    write_frame(&mut stream, &request).unwrap();
+    let response: Response = read_frame(&mut stream, DEFAULT_MAX_FRAME_LEN).unwrap();
+    let Response::Decision(tagged) = response else {
+        panic!("expected a decision");
+    };
+    assert!(mac_key.verify("alice", &encrypted_nonce, &tagged.decision, &tagged.tag));
+    assert_eq!(
+        verify_decision(&tagged.decision, nonce, &client_key),
+        Ok(false)
+    );
+
+    // Accepting answer built from the encrypted nonce alone, it passes the nonce check
+    let forged: Vec<_> = encrypted_nonce
+        .iter()
+        .map(|nonce_bit| {
+            let mut block = server_key.unchecked_scalar_mul(nonce_bit, 2);
+            server_key.unchecked_scalar_add_assign(&mut block, 1);
+            block
+        })
+        .collect();
+    assert_eq!(verify_decision(&forged, nonce, &client_key), Ok(true));
+
+    // but neither the tag of the real answer nor one under another key verifies
+    assert!(!mac_key.verify("alice", &encrypted_nonce, &forged, &tagged.tag));
+    let forged_tag = MacKey::generate().tag("alice", &encrypted_nonce, &forged);
+    assert!(!mac_key.verify("alice", &encrypted_nonce, &forged, &forged_tag));
+    // and the real tag is bound to the subject
+    assert!(!mac_key.verify("bob", &encrypted_nonce, &tagged.decision, &tagged.tag));
+}
+
+#[test]
+fn loopback_handshake() {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    server.spawn();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+
+    let request = Request::Authenticate {
+        subject_id: "alice".to_owned(),
+        probe: Vec::new(),
+        nonce: Vec::new(),
+    };
+    write_frame(&mut stream, &request).unwrap();
+    let response: Response = read_frame(&mut stream, DEFAULT_MAX_FRAME_LEN).unwrap();
+    assert!(matches!(
+        response,
+        Response::Error(ProtocolError::HandshakeRequired)
+    ));
+
+    let request = Request::Hello {
+        version: PROTOCOL_VERSION + 1,
+    };
+    write_frame(&mut stream, &request).unwrap();
+    let response: Response = read_frame(&mut stream, DEFAULT_MAX_FRAME_LEN).unwrap();
+    assert!(matches!(
+        response,
+        Response::Error(ProtocolError::VersionMismatch { server, .. }) if server == PROTOCOL_VERSION
+    ));
+}
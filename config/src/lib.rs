@@ -5,6 +5,27 @@ pub struct Config {
     pub block_length: usize,
     pub num_tables: usize,
     pub threshold: i64,
+    pub probe_encoding: ProbeEncoding,
+}
+
+/// How a quantized probe feature is encrypted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeEncoding {
+    // one block over the message and carry space, the tables have at most 1 << (2 * block_length) bins
+    Block,
+    // a low and a high digit block, the tables have at most 1 << (4 * block_length - 1) bins
+    Wide,
+}
+
+impl Config {
+    /// Number of quantization bins a probe feature can take with the probe encoding of the config.
+    pub fn probe_space(&self) -> usize {
+        let block_space = 1 << (2 * self.block_length);
+        match self.probe_encoding {
+            ProbeEncoding::Block => block_space,
+            ProbeEncoding::Wide => block_space / 2 * block_space,
+        }
+    }
 }
 
 pub const BMDB1: Config = Config {
@@ -14,6 +35,7 @@ pub const BMDB1: Config = Config {
     block_length: 2,
     num_tables: 36,
     threshold: 14,
+    probe_encoding: ProbeEncoding::Block,
 };
 pub const BMDB2: Config = Config {
     data_set_name: "BMDB",
@@ -22,6 +44,7 @@ pub const BMDB2: Config = Config {
     block_length: 3,
     num_tables: 36,
     threshold: 14,
+    probe_encoding: ProbeEncoding::Block,
 };
 pub const PUT: Config = Config {
     data_set_name: "PUT",
@@ -30,6 +53,7 @@ pub const PUT: Config = Config {
     block_length: 3,
     num_tables: 49,
     threshold: -53,
+    probe_encoding: ProbeEncoding::Block,
 };
 
 pub const FRGC: Config = Config {
@@ -39,8 +63,20 @@ pub const FRGC: Config = Config {
     block_length: 3,
     num_tables: 94,
     threshold: -1,
+    probe_encoding: ProbeEncoding::Block,
 };
 
+pub struct Modality {
+    pub config: &'static Config,
+    pub weight: u64,
+}
+
+pub struct FusionConfig {
+    pub modalities: &'static [Modality],
+    // threshold on the weighted sum of the non-offset HELR scores
+    pub threshold: i64,
+}
+
 //constant values
 pub const TABLE_PREFIX: &str = "HELR";
 pub const QBIN_SUFFIX: &str = "_qbins";
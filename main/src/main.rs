@@ -1,62 +1,194 @@
+use std::sync::OnceLock;
 use std::time::Duration;
 use tfhe::core_crypto::biometrics::common;
 use itertools::Itertools;
 use bio_aux;
-use config::Config;
-use tfhe::core_crypto::biometrics::cpu::{all_in_one_classic, all_in_one_multibit};
+use config::{Config, FusionConfig, Modality, ProbeEncoding};
+use tfhe::core_crypto::biometrics::cpu::{all_in_one_classic, all_in_one_signed, all_in_one_multibit, all_in_one_multi_sample, all_in_one_fusion, all_in_one_wide};
+use tfhe::core_crypto::biometrics::cpu::all_in_one_multibit::{MultiBitAuthenticator, MultiBitExecution};
+use tfhe::core_crypto::biometrics::cpu::all_in_one_fusion::ModalityInput;
+use tfhe::core_crypto::biometrics::cpu::all_in_one_multi_sample::ScoreAggregation;
+use tfhe::core_crypto::biometrics::cpu::batch::{AuthenticationServer, BatchRequest, Enrollment};
+use tfhe::core_crypto::biometrics::cpu::early_reject;
+use tfhe::core_crypto::biometrics::cpu::early_reject::EarlyReject;
+use tfhe::core_crypto::biometrics::cpu::key_store::BiometricKeyStore;
 use tfhe::core_crypto::biometrics::gpu::all_in_one_classic as classic;
 use tfhe::core_crypto::biometrics::gpu::all_in_one_multibit as multibit;
+use tfhe::core_crypto::biometrics::planner;
+use tfhe::core_crypto::biometrics::planner::PipelineShape;
 use tfhe::core_crypto::biometrics::cpu_gpu::{all_in_one_multibit as multibit_cpu_gpu, all_in_one_original};
 use tfhe::integer;
 use tfhe::integer::RadixClientKey;
 use tfhe::shortint::engine::ShortintEngine;
-use tfhe::shortint::MessageModulus;
+use tfhe::shortint::{ClassicPBSParameters, MessageModulus, MultiBitPBSParameters};
 use tfhe::shortint::parameters::{PARAM_GPU_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS, PARAM_GPU_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS, PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS, PARAM_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS};
 use tfhe::shortint::prelude::{PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS};
 
+// Demo only: the fused threshold is the sum of the single modality thresholds, a probe scoring at
+// the operating point of both modalities is accepted. It is not tuned on fused scores.
+const DEMO_FUSION: FusionConfig = FusionConfig {
+    modalities: &[
+        Modality { config: &config::FRGC, weight: 1 },
+        Modality { config: &config::BMDB2, weight: 1 },
+    ],
+    threshold: config::FRGC.threshold + config::BMDB2.threshold,
+};
+
 fn main() {
     println!("Hello, world!");
 
     let debug = false;
 
     const RUNS: usize = 1;
+    const SAMPLES_PER_SUBJECT: usize = 3;
+    const MAX_LOG2_P_FAIL: f64 = -20.0;
+    const BATCH_SIZE: usize = 8;
+    const BATCH_THREADS: usize = 0;
+    //Threads shared by the features and the multi-bit PBS, 0 uses all available threads
+    const MULTI_BIT_THREADS: usize = 0;
+    const MULTI_BIT_DETERMINISTIC: bool = false;
+    const TUNING_RUNS: usize = 1;
     let config = config::FRGC;
     let template_nums = vec![1690, 4144, 2686, 1079, 1975, 2277, 3193, 1814, 1942, 743, 3178, 2004, 4538, 4215, 1126, 2038, 332, 3977, 996, 1377, 153, 2912, 4632, 3400, 1104, 394, 1276, 2053, 2276, 382, 523, 1457, 4024, 4691, 2624, 4409, 2648, 3531, 3947, 3951, 1959, 1682, 4427, 2886, 2342, 1085, 3922, 4606, 3497, 94, 3578, 4053, 6, 1019, 3226, 1576, 4699, 3923, 919, 2918, 185, 1610, 494, 57, 1695, 167, 2378, 2225, 2686, 1956, 1188, 3374, 2293, 552, 3672, 1130, 4427, 1448, 1068, 4051, 560, 3487, 2262, 609, 3854, 577, 1353, 1503, 1190, 1586, 3295, 348, 643, 3765, 2190, 4381, 2389, 2515, 3875, 2826];
     let probe_nums = vec![1690, 4144, 2686, 1079, 1975, 2277, 3193, 1814, 1942, 743, 3178, 2004, 4538, 4215, 1126, 2038, 332, 3977, 996, 1377, 153, 2912, 4632, 3400, 1104, 394, 1276, 2053, 2276, 382, 523, 1457, 4024, 4691, 2624, 4409, 2648, 3531, 3947, 3951, 1959, 1682, 4427, 2886, 2342, 1085, 3922, 4606, 3497, 94, 1940, 1676, 2364, 434, 3348, 1059, 3436, 1923, 1529, 609, 925, 3205, 3138, 392, 2932, 1763, 3419, 794, 86, 1405, 1356, 2293, 3747, 2543, 1783, 4668, 4464, 2565, 3112, 3304, 4679, 4458, 4075, 1024, 1549, 3705, 507, 468, 3491, 2814, 3212, 3117, 3866, 4560, 264, 4561, 3694, 3717, 3620, 794];
     let mut vec_res_classic_cpu_original = Vec::with_capacity(RUNS);
+    let mut vec_res_classic_cpu_accumulate = Vec::with_capacity(RUNS);
+    let mut vec_res_classic_cpu_signed = Vec::with_capacity(RUNS);
+    let mut vec_res_classic_cpu_early_reject = Vec::with_capacity(RUNS);
     let mut vec_res_multibit_cpu_original = Vec::with_capacity(RUNS);
     let mut vec_res_classic_cpu_gpu_original = Vec::with_capacity(RUNS);
     let mut vec_res_classic_gpu = Vec::with_capacity(RUNS);
 
+    //The HELR tables are shared by all subjects, they are only read once
+    let table_set = bio_aux::HelrTableSet::load(&config);
+
+    //Every measure runs with the parameter sets selected by the planner for the data set
+    let shape = pipeline_shape(&config);
+    let parameter_set = match planner::select(&shape, &[PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS], MAX_LOG2_P_FAIL) {
+        Ok((params, plan)) => {
+            println!("Selected parameters: {:?}\nPlan: {:?}", params, plan);
+            params
+        }
+        Err(errors) => {
+            println!("No parameter set fits {}: {:?}", config.data_set_name, errors);
+            return;
+        }
+    };
+    let multibit = match planner::select(&shape, &[PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS, PARAM_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS], MAX_LOG2_P_FAIL) {
+        Ok((params, plan)) => {
+            println!("Selected multi-bit parameters: {:?}\nPlan: {:?}", params, plan);
+            let execution = tune_multibit(params, &config, MULTI_BIT_THREADS, MULTI_BIT_DETERMINISTIC, TUNING_RUNS);
+            println!("Multi-bit execution: {:?}", execution);
+            Some((params, execution))
+        }
+        Err(errors) => {
+            println!("No multi-bit parameter set fits {}: {:?}", config.data_set_name, errors);
+            None
+        }
+    };
+
+    //Features with more bins than a block only run through the two level LUT tree
+    if config.probe_encoding == ProbeEncoding::Wide {
+        println!("Wide measures: ");
+        let mut vec_res_wide_cpu = Vec::with_capacity(RUNS);
+        (0..RUNS).for_each(|idx| {
+            let template = template_nums[idx];
+            let probe  = probe_nums[idx];
+            vec_res_wide_cpu.push(test_wide_cpu(idx, template, probe, parameter_set, &config, &table_set));
+        });
+        eval_measurements("wide_cpu", vec_res_wide_cpu);
+        return;
+    }
+
+    let signed_table_set = bio_aux::SignedHelrTableSet::load(&config);
     println!("Safe measures: ");
     (0..RUNS).for_each(|idx| {
         let template = template_nums[idx];
         let probe  = probe_nums[idx];
-        vec_res_classic_cpu_original.push(test_classic_cpu_original(idx, template, probe, &config, debug));
-        vec_res_multibit_cpu_original.push(test_multibit_cpu_original(idx, template, probe, &config, debug));
-        vec_res_classic_cpu_gpu_original.push(test_original(idx, template, probe, &config, debug));
-        vec_res_classic_gpu.push(test_classic_gpu(idx, template, probe, &config, debug));
+        vec_res_classic_cpu_original.push(test_classic_cpu_original(idx, template, probe, parameter_set, &config, &table_set, debug));
+        vec_res_classic_cpu_accumulate.push(test_classic_cpu_accumulate(idx, template, probe, parameter_set, &config, &table_set));
+        vec_res_classic_cpu_signed.push(test_classic_cpu_signed(idx, template, probe, parameter_set, &config, &signed_table_set));
+        vec_res_classic_cpu_early_reject.push(test_classic_cpu_early_reject(idx, template, probe, parameter_set, &config, &table_set));
+        if let Some((multibit_parameter_set, execution)) = &multibit {
+            vec_res_multibit_cpu_original.push(test_multibit_cpu_original(idx, template, probe, *multibit_parameter_set, &config, &table_set, execution, debug));
+        }
+        vec_res_classic_cpu_gpu_original.push(test_original(idx, template, probe, parameter_set, &config, &table_set, debug));
+        vec_res_classic_gpu.push(test_classic_gpu(idx, template, probe, parameter_set, &config, &table_set, debug));
     });
     eval_measurements("classic_cpu_original", vec_res_classic_cpu_original);
-    eval_measurements("multibit_cpu_original", vec_res_multibit_cpu_original);
+    eval_measurements("classic_cpu_accumulate", vec_res_classic_cpu_accumulate);
+    eval_measurements("classic_cpu_signed", vec_res_classic_cpu_signed);
+    eval_measurements("classic_cpu_early_reject", vec_res_classic_cpu_early_reject);
+    if multibit.is_some() {
+        eval_measurements("multibit_cpu_original", vec_res_multibit_cpu_original);
+    }
     eval_measurements("classic_cpu_gpu_original", vec_res_classic_cpu_gpu_original);
     eval_measurements("classic_gpu", vec_res_classic_gpu);
 
-    if config.block_length == 2 {
-        println!("Unsafe measures: ");
-        let mut vec_res_multibit_gpu_cpu = Vec::with_capacity(RUNS);
-        let mut vec_res_multibit_gpu = Vec::with_capacity(RUNS);
-        (0..RUNS).for_each(|idx| {
-            let template = template_nums[idx];
-            let probe  = probe_nums[idx];
-            vec_res_multibit_gpu_cpu.push(test_multibit_gpu_cpu(idx, template, probe, &config, debug));
-            vec_res_multibit_gpu.push(test_multibit_gpu(idx, template, probe, &config, debug));
-        });
-        eval_measurements("multibit_gpu_cpu", vec_res_multibit_gpu_cpu);
-        eval_measurements("multibit_gpu", vec_res_multibit_gpu);
+    println!("Multi-sample measures: ");
+    //Subjects enroll their first captures and are probed with the next one, which is not enrolled
+    let subjects = bio_aux::io::samples_by_subject(&config);
+    let multi_sample_subjects = subjects.iter().filter(|(_, samples)| samples.len() > SAMPLES_PER_SUBJECT).collect_vec();
+    let mut vec_res_multi_sample_cpu_genuine = Vec::with_capacity(RUNS);
+    let mut vec_res_multi_sample_cpu_impostor = Vec::with_capacity(RUNS);
+    (0..RUNS).for_each(|idx| {
+        let (_, samples) = multi_sample_subjects[idx % multi_sample_subjects.len()];
+        let templates = &samples[..SAMPLES_PER_SUBJECT];
+        let probe = samples[SAMPLES_PER_SUBJECT];
+        //The impostor is a capture of the next subject
+        let (_, impostor_samples) = multi_sample_subjects[(idx + 1) % multi_sample_subjects.len()];
+        let impostor_probe = impostor_samples[0];
+        vec_res_multi_sample_cpu_genuine.push(test_multi_sample_cpu(idx, templates, probe, ScoreAggregation::Vote(2), parameter_set, &config, &table_set));
+        vec_res_multi_sample_cpu_impostor.push(test_multi_sample_cpu(idx, templates, impostor_probe, ScoreAggregation::Vote(2), parameter_set, &config, &table_set));
+    });
+    eval_measurements("multi_sample_cpu_genuine", vec_res_multi_sample_cpu_genuine);
+    eval_measurements("multi_sample_cpu_impostor", vec_res_multi_sample_cpu_impostor);
+
+    println!("Batch measures: ");
+    test_batch_cpu(&template_nums[..BATCH_SIZE], &probe_nums[..BATCH_SIZE], parameter_set, &config, &table_set, BATCH_THREADS);
+
+    println!("Fusion measures: ");
+    let fusion = DEMO_FUSION;
+    let fusion_table_sets = fusion.modalities.iter().map(|modality| bio_aux::HelrTableSet::load(modality.config)).collect_vec();
+    match planner::select(&fusion_shape(&fusion), &[PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS], MAX_LOG2_P_FAIL) {
+        Ok((fusion_parameter_set, _)) => {
+            let mut vec_res_fusion_cpu = Vec::with_capacity(RUNS);
+            (0..RUNS).for_each(|idx| {
+                let template = template_nums[idx];
+                let probe  = probe_nums[idx];
+                vec_res_fusion_cpu.push(test_fusion_cpu(idx, template, probe, fusion_parameter_set, &fusion, &fusion_table_sets));
+            });
+            eval_measurements("fusion_cpu", vec_res_fusion_cpu);
+        }
+        Err(errors) => println!("No parameter set fits the fusion: {:?}", errors),
+    }
+
+    //The GPU multi-bit parameters only run where the planner accepts them
+    match planner::select(&shape, &[PARAM_GPU_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS, PARAM_GPU_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS], MAX_LOG2_P_FAIL) {
+        Ok((gpu_multibit_parameter_set, plan)) => {
+            println!("Multi-bit GPU measures: ");
+            println!("Selected parameters: {:?}\nPlan: {:?}", gpu_multibit_parameter_set, plan);
+            let mut vec_res_multibit_gpu_cpu = Vec::with_capacity(RUNS);
+            let mut vec_res_multibit_gpu = Vec::with_capacity(RUNS);
+            (0..RUNS).for_each(|idx| {
+                let template = template_nums[idx];
+                let probe  = probe_nums[idx];
+                vec_res_multibit_gpu_cpu.push(test_multibit_gpu_cpu(idx, template, probe, gpu_multibit_parameter_set, &config, &table_set, debug));
+                vec_res_multibit_gpu.push(test_multibit_gpu(idx, template, probe, gpu_multibit_parameter_set, &config, &table_set, debug));
+            });
+            eval_measurements("multibit_gpu_cpu", vec_res_multibit_gpu_cpu);
+            eval_measurements("multibit_gpu", vec_res_multibit_gpu);
+        }
+        Err(errors) => println!("No multi-bit GPU parameter set fits {}: {:?}", config.data_set_name, errors),
     }
 }
 
+//Keys are generated on the first run only, later runs reload them from disk
+fn key_store() -> &'static BiometricKeyStore {
+    static KEY_STORE: OnceLock<BiometricKeyStore> = OnceLock::new();
+    KEY_STORE.get_or_init(BiometricKeyStore::default)
+}
+
 pub fn eval_measurements(name: &str, measurements: Vec<(Duration, bool)>){
     let mut total = 0_f64;
     let count = measurements.len();
@@ -69,38 +201,56 @@ pub fn eval_measurements(name: &str, measurements: Vec<(Duration, bool)>){
     println!("Run times: {:?}", measurements.iter().map(|(d, _)| (d.as_secs_f64() * 1000f64) as u64 ).collect::<Vec<u64>>())
 }
 
-pub fn test_classic_cpu_original(test_idx: usize, template_idx: usize, probe_idx: usize, config: &Config, debug: bool) -> (Duration, bool) {
-    println!("classic_cpu_original {} with {} and {}", test_idx, template_idx, probe_idx);
+pub fn pipeline_shape(config: &Config) -> PipelineShape {
+    let (max_sum, _) = bio_aux::max_offset_score(config);
+    PipelineShape {
+        num_tables: config.num_tables,
+        num_blocks: config.num_blocks,
+        block_length: config.block_length,
+        num_blocks_sum: config.num_blocks_sum,
+        max_sum: Some(max_sum),
+    }
+}
 
-    let parameter_set = if config.block_length == 2 {
-        PARAM_MESSAGE_2_CARRY_2_KS_PBS
-    } else {
-        PARAM_MESSAGE_3_CARRY_3_KS_PBS
-    };
+//Shape of the fused comparison: the tables of all modalities are summed in the common radix
+pub fn fusion_shape(fusion: &FusionConfig) -> PipelineShape {
+    let (num_blocks_sum, _) = bio_aux::fusion_parameters(fusion).unwrap();
+    PipelineShape {
+        num_tables: fusion.modalities.iter().map(|modality| modality.config.num_tables).sum(),
+        num_blocks: fusion.modalities.iter().map(|modality| modality.config.num_blocks).max().unwrap(),
+        block_length: fusion.modalities[0].config.block_length,
+        num_blocks_sum,
+        max_sum: Some(fusion.modalities.iter().map(|modality| modality.weight * bio_aux::max_offset_score(modality.config).0).sum()),
+    }
+}
+
+pub fn test_classic_cpu_original(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, debug: bool) -> (Duration, bool) {
+    println!("classic_cpu_original {} with {} and {}", test_idx, template_idx, probe_idx);
 
     let mut engine = ShortintEngine::new();
 
     //Setup
-    let (client_key,server_key) = tfhe::core_crypto::biometrics::cpu::tfhe_functions_classic::make_keys_classic(parameter_set, &mut engine);
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
 
     //Fetch probe and template
     let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
 
     //Create Lookup tables from template
-    let (functions, threshold) = bio_aux::generate_functions_stop_early(&template, &config);
+    let rows = table_set.lut_rows(&template, true);
+    let threshold = table_set.threshold();
 
     if debug {
         println!("Clear LUTs: {:?}",
-            functions.iter().zip(&probe).map(|(fs, p)| {
-                fs.iter().map(|f| {
-                    f(*p as u64)
+            rows.iter().zip(&probe).map(|(rs, p)| {
+                rs.iter().map(|row| {
+                    row.get(*p as usize).copied().unwrap_or(0)
                 }).collect_vec()
             }).collect_vec()
         );
     }
 
     //Flatten and repeat
-    let r_probe = repeat_probes_to_match_functions(&probe, &functions);
+    let r_probe = repeat_probes_to_match_rows(&probe, &rows);
     if debug {
         println!("Probes decomp: {:?}", r_probe);
     }
@@ -123,7 +273,7 @@ pub fn test_classic_cpu_original(test_idx: usize, template_idx: usize, probe_idx
     }
 
     //Make lookup tables
-    let encrypted_luts = common::generate_lookup_tables_individual(functions, &client_key, parameter_set.into(), &mut engine);
+    let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
     let (d, r) = if debug {
         let key_clone = client_key.clone();
         let big_client_key = integer::ClientKey::from_raw_parts(client_key.clone());
@@ -152,31 +302,246 @@ pub fn test_classic_cpu_original(test_idx: usize, template_idx: usize, probe_idx
     (r, result)
 }
 
+pub fn test_classic_cpu_accumulate(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet) -> (Duration, bool) {
+    println!("classic_cpu_accumulate {} with {} and {}", test_idx, template_idx, probe_idx);
 
-pub fn test_multibit_cpu_original(test_idx: usize, template_idx: usize, probe_idx: usize, config: &Config, debug: bool) -> (Duration, bool) {
-    println!("multibit_cpu_original {} with {} and {}", test_idx, template_idx, probe_idx);
+    let mut engine = ShortintEngine::new();
+
+    //Setup
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+
+    //Fetch probe and template
+    let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
+
+    //Create Lookup tables from template
+    let rows = table_set.lut_rows(&template, true);
+    let threshold = table_set.threshold();
+
+    //Flatten and repeat
+    let r_probe = repeat_probes_to_match_rows(&probe, &rows);
+
+    //Encrypt probes
+    let encrypted_probes = r_probe.iter().map(|ps| {
+        ps.iter().map(|p| {
+            client_key.encrypt_with_message_modulus(*p, MessageModulus(1 << (2 * config.block_length)))
+        }).collect_vec()
+    }).collect_vec();
+
+    //Make lookup tables
+    let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
+    let (d, r) = all_in_one_classic::authenticate_accumulate(
+        server_key,
+        encrypted_probes,
+        encrypted_luts,
+        threshold,
+        config.num_blocks_sum,
+    );
+
+    //Decrypt
+    let result = common::decrypt_boolean_block_client_key(&d, &client_key);
+    (r, result)
+}
+
+pub fn test_classic_cpu_signed(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::SignedHelrTableSet) -> (Duration, bool) {
+    println!("classic_cpu_signed {} with {} and {}", test_idx, template_idx, probe_idx);
+
+    let mut engine = ShortintEngine::new();
+
+    //Setup
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+
+    //Fetch probe and template
+    let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
+
+    //Create Lookup tables from template, without offsetting the tables
+    let rows = table_set.lut_rows(&template);
+    println!("Signed sum blocks: {} (offset tables: {})", table_set.num_blocks_sum(), config.num_blocks_sum);
+
+    //Flatten and repeat
+    let r_probe = repeat_probes_to_match_rows(&probe, &rows);
+
+    //Encrypt probes
+    let encrypted_probes = r_probe.iter().map(|ps| {
+        ps.iter().map(|p| {
+            client_key.encrypt_with_message_modulus(*p, MessageModulus(1 << (2 * config.block_length)))
+        }).collect_vec()
+    }).collect_vec();
+
+    //Make lookup tables
+    let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
+    let (d, r) = all_in_one_signed::authenticate(
+        server_key,
+        encrypted_probes,
+        encrypted_luts,
+        table_set.threshold(),
+        table_set.num_blocks_sum(),
+    );
+
+    //Decrypt
+    let result = common::decrypt_boolean_block_client_key(&d, &client_key);
+    (r, result)
+}
+
+pub fn test_batch_cpu(template_idxs: &[usize], probe_idxs: &[usize], parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, num_threads: usize) {
+    println!("batch_cpu with {} requests", template_idxs.len());
+
+    let mut engine = ShortintEngine::new();
+
+    //Setup, the server keys are only derived once
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+    let server = AuthenticationServer::new(server_key, num_threads).unwrap();
+
+    //Enroll templates and encrypt probes
+    let requests = template_idxs.iter().zip(probe_idxs).map(|(&template_idx, &probe_idx)| {
+        let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
+        let rows = table_set.lut_rows(&template, true);
+        let threshold = table_set.threshold();
+        let r_probe = repeat_probes_to_match_rows(&probe, &rows);
+        let encrypted_probes = r_probe.iter().map(|ps| {
+            ps.iter().map(|p| {
+                client_key.encrypt_with_message_modulus(*p, MessageModulus(1 << (2 * config.block_length)))
+            }).collect_vec()
+        }).collect_vec();
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
+        let enrollment = Enrollment { luts, threshold, num_sum_blocks: config.num_blocks_sum };
+        BatchRequest { enrollment: enrollment.into(), probe: encrypted_probes }
+    }).collect_vec();
+
+    let report = server.try_process_batch(requests);
+
+    //Decrypt
+    let results = report.decisions.iter().map(|d| {
+        d.as_ref().map(|d| common::decrypt_boolean_block_client_key(d, &client_key))
+    }).collect_vec();
+    println!("Results: batch_cpu on {} threads", server.num_threads());
+    println!("Throughput: {} auth/s", report.throughput());
+    println!("Latency p50/p90/p99: {:?} {:?} {:?}", report.latency_percentile(50.0), report.latency_percentile(90.0), report.latency_percentile(99.0));
+    println!("Auth: {:?}", results);
+}
+
+pub fn test_classic_cpu_early_reject(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet) -> (Duration, bool) {
+    println!("classic_cpu_early_reject {} with {} and {}", test_idx, template_idx, probe_idx);
+
+    let mut engine = ShortintEngine::new();
+
+    //Setup
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+
+    //Fetch probe and template
+    let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
+
+    //Create Lookup tables from template
+    let rows = table_set.lut_rows(&template, true);
+    let threshold = table_set.threshold();
 
-    let parameter_set = if config.block_length == 2 {
-        PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS
+    //First half of the features in the first phase, with a bound that never rejects an accepted probe
+    let features = (0..config.num_tables / 2).collect_vec();
+    let max_scores = early_reject::max_scores(&rows, MessageModulus(1 << config.block_length));
+    let lower_bound = early_reject::lossless_lower_bound(&max_scores, &features, threshold);
+    let first_phase = EarlyReject { features, lower_bound };
+
+    //Flatten and repeat
+    let r_probe = repeat_probes_to_match_rows(&probe, &rows);
+
+    //Encrypt probes
+    let encrypted_probes = r_probe.iter().map(|ps| {
+        ps.iter().map(|p| {
+            client_key.encrypt_with_message_modulus(*p, MessageModulus(1 << (2 * config.block_length)))
+        }).collect_vec()
+    }).collect_vec();
+
+    //Make lookup tables
+    let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
+    let (d, r) = early_reject::authenticate(
+        server_key,
+        encrypted_probes,
+        encrypted_luts,
+        threshold,
+        config.num_blocks_sum,
+        &first_phase,
+        //The interactive round, played locally by the key holder
+        |partial| common::decrypt_boolean_block_client_key(partial, &client_key),
+    );
+
+    //Decrypt
+    let result = match d {
+        Some(d) => common::decrypt_boolean_block_client_key(&d, &client_key),
+        None => {
+            println!("Rejected after {} of {} features (lower bound {})", first_phase.features.len(), config.num_tables, first_phase.lower_bound);
+            false
+        }
+    };
+    (r, result)
+}
+
+pub fn test_wide_cpu(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet) -> (Duration, bool) {
+    println!("wide_cpu {} with {} and {}", test_idx, template_idx, probe_idx);
+
+    let mut engine = ShortintEngine::new();
+
+    //Setup
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+
+    //Fetch probe and template
+    let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
+
+    //Create Lookup tables from template
+    let rows = table_set.lut_rows(&template, true);
+    let threshold = table_set.threshold();
+
+    //Encrypt probes, every feature as a low and a high digit
+    let probe = probe.iter().map(|p| *p as u64).collect_vec();
+    let encrypted_probe = all_in_one_wide::encrypt_probe(&probe, &client_key);
+
+    //Make lookup tables, one set per high digit
+    let encrypted_luts = common::generate_wide_lookup_tables_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
+    let (d, r) = all_in_one_wide::authenticate(
+        server_key,
+        encrypted_probe,
+        encrypted_luts,
+        threshold,
+        config.num_blocks_sum,
+    );
+
+    //Decrypt
+    let result = common::decrypt_boolean_block_client_key(&d, &client_key);
+    (r, result)
+}
+
+//Picks the split of the thread budget between features and multi-bit PBS threads for this machine
+pub fn tune_multibit(parameter_set: MultiBitPBSParameters, config: &Config, total_threads: usize, deterministic_execution: bool, runs: usize) -> MultiBitExecution {
+    let total_threads = if total_threads == 0 {
+        std::thread::available_parallelism().map_or(1, |threads| threads.get())
     } else {
-        PARAM_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS
+        total_threads
     };
 
-    let thread_count_bs = 4;
+    let mut engine = ShortintEngine::new();
+    let (client_key, server_key) = key_store().multibit_keys(parameter_set, 1, deterministic_execution, &mut engine);
+    let report = all_in_one_multibit::tune(&client_key, &server_key, &pipeline_shape(config), total_threads, deterministic_execution, runs, &mut engine).unwrap();
+    for (budget, duration) in report.measurements.iter() {
+        println!("{} feature threads x {} PBS threads: {} ms", budget.feature_threads, budget.pbs_threads, duration.as_millis());
+    }
+    report.best
+}
+
+pub fn test_multibit_cpu_original(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: MultiBitPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, execution: &MultiBitExecution, debug: bool) -> (Duration, bool) {
+    println!("multibit_cpu_original {} with {} and {}", test_idx, template_idx, probe_idx);
 
     let mut engine = ShortintEngine::new();
 
     //Setup
-    let (client_key,server_key) = tfhe::core_crypto::biometrics::cpu::tfhe_functions_multibit::make_keys_multibit(parameter_set, thread_count_bs, &mut engine);
+    let (client_key,server_key) = key_store().multibit_keys(parameter_set, execution.budget.pbs_threads, execution.deterministic_execution, &mut engine);
 
     //Fetch probe and template
     let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
 
     //Create Lookup tables from template
-    let (functions, threshold) = bio_aux::generate_functions_stop_early(&template, &config);
+    let rows = table_set.lut_rows(&template, true);
+    let threshold = table_set.threshold();
 
     //Flatten and repeat
-    let r_probe = repeat_probes_to_match_functions(&probe, &functions);
+    let r_probe = repeat_probes_to_match_rows(&probe, &rows);
 
     //Encrypt probes
     let encrypted_probes = r_probe.iter().map(|ps| {
@@ -186,7 +551,7 @@ pub fn test_multibit_cpu_original(test_idx: usize, template_idx: usize, probe_id
     }).collect_vec();
 
     //Make lookup tables
-    let encrypted_luts = common::generate_lookup_tables_individual(functions, &client_key, parameter_set.into(), &mut engine);
+    let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
     let (d, r) = if debug {
         let big_client_key = integer::ClientKey::from_raw_parts(client_key.clone());
         let big_client_key = RadixClientKey::from((big_client_key, config.num_blocks_sum));
@@ -199,10 +564,10 @@ pub fn test_multibit_cpu_original(test_idx: usize, template_idx: usize, probe_id
             config.num_blocks_sum,
         )
     } else {
-        all_in_one_multibit::authenticate(
-            server_key,
+        let authenticator = MultiBitAuthenticator::new(server_key, *execution).unwrap();
+        authenticator.authenticate(
             encrypted_probes,
-            encrypted_luts,
+            &encrypted_luts,
             threshold,
             config.num_blocks_sum,
         )
@@ -214,15 +579,128 @@ pub fn test_multibit_cpu_original(test_idx: usize, template_idx: usize, probe_id
 }
 
 
+pub fn test_multi_sample_cpu(test_idx: usize, template_idxs: &[usize], probe_idx: usize, aggregation: ScoreAggregation, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet) -> (Duration, bool) {
+    println!("multi_sample_cpu {} with {:?} and {} ({:?})", test_idx, template_idxs, probe_idx, aggregation);
 
-pub fn test_original(test_idx: usize, template_idx: usize, probe_idx: usize, config: &Config, debug: bool) -> (Duration, bool) {
-    println!("original {} with {} and {}", test_idx, template_idx, probe_idx);
+    let mut engine = ShortintEngine::new();
 
-    let parameter_set = if config.block_length == 2 {
-        PARAM_MESSAGE_2_CARRY_2_KS_PBS
-    } else {
-        PARAM_MESSAGE_3_CARRY_3_KS_PBS
-    };
+    //Setup
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+
+    //Fetch probe and all enrolled templates
+    let (probe, templates) = bio_aux::io::probe_and_templates_generation_radix_prepare(probe_idx, template_idxs, &config);
+
+    //Create Lookup tables from every template
+    let threshold = table_set.threshold();
+    let mut encrypted_luts = Vec::with_capacity(templates.len());
+    let mut r_probe = Vec::new();
+    for template in templates.iter() {
+        let rows = table_set.lut_rows(template, true);
+        r_probe = repeat_probes_to_match_rows(&probe, &rows);
+        encrypted_luts.push(common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine));
+    }
+
+    //Encrypt probes
+    let encrypted_probes = r_probe.iter().map(|ps| {
+        ps.iter().map(|p| {
+            client_key.encrypt_with_message_modulus(*p, MessageModulus(1 << (2 * config.block_length)))
+        }).collect_vec()
+    }).collect_vec();
+
+    let (d, r) = all_in_one_multi_sample::authenticate(
+        server_key,
+        encrypted_probes,
+        encrypted_luts,
+        threshold,
+        config.num_blocks_sum,
+        aggregation,
+    );
+
+    //Decrypt
+    let result = common::decrypt_boolean_block_client_key(&d, &client_key);
+    (r, result)
+}
+
+pub fn test_fusion_cpu(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, fusion: &FusionConfig, table_sets: &[bio_aux::HelrTableSet]) -> (Duration, bool) {
+    println!("fusion_cpu {} with {} and {}", test_idx, template_idx, probe_idx);
+
+    let mut engine = ShortintEngine::new();
+
+    //Setup
+    let (client_key,server_key) = key_store().classic_keys(parameter_set, &mut engine);
+
+    //Common radix width and fused threshold
+    let (num_sum_blocks, threshold) = bio_aux::fusion_parameters(fusion).unwrap();
+
+    //The indices are samples of the first modality, the other ones use the samples of the paired subjects
+    let first_subjects = bio_aux::io::samples_by_subject(fusion.modalities[0].config);
+
+    let modalities = fusion.modalities.iter().enumerate().map(|(modality_idx, modality)| {
+        let config = modality.config;
+
+        //Fetch probe and template
+        let (probe_idx, template_idx) = if modality_idx == 0 {
+            (probe_idx, template_idx)
+        } else {
+            let subjects = bio_aux::io::samples_by_subject(config);
+            (chimeric_sample(&first_subjects, &subjects, probe_idx), chimeric_sample(&first_subjects, &subjects, template_idx))
+        };
+        let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, config);
+
+        //Create Lookup tables from template
+        let rows = table_sets[modality_idx].lut_rows(&template, true);
+
+        //Flatten and repeat
+        let r_probe = repeat_probes_to_match_rows(&probe, &rows);
+
+        //Encrypt probes
+        let encrypted_probes = r_probe.iter().map(|ps| {
+            ps.iter().map(|p| {
+                client_key.encrypt_with_message_modulus(*p, MessageModulus(1 << (2 * config.block_length)))
+            }).collect_vec()
+        }).collect_vec();
+
+        //Make lookup tables
+        let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameter_set.into(), &mut engine);
+
+        ModalityInput {
+            probe: encrypted_probes,
+            luts: encrypted_luts,
+            weight: modality.weight,
+        }
+    }).collect_vec();
+
+    let (d, r) = all_in_one_fusion::authenticate(
+        server_key,
+        modalities,
+        threshold,
+        num_sum_blocks,
+    );
+
+    //Decrypt
+    let result = common::decrypt_boolean_block_client_key(&d, &client_key);
+    (r, result)
+}
+
+/// Sample of a data set standing in for `sample_id` of the first data set of a fusion.
+///
+/// The data sets do not share subjects, the n-th subjects of all data sets form the n-th virtual
+/// subject and a sample is replaced by the sample of the same rank of the paired subject. Genuine
+/// pairs stay genuine and impostor pairs stay impostor pairs.
+fn chimeric_sample(first_subjects: &[(usize, Vec<usize>)], subjects: &[(usize, Vec<usize>)], sample_id: usize) -> usize {
+    //Sample ids wrap around the data set like in read_sample_with_id_in_line_fpd
+    let num_samples: usize = first_subjects.iter().map(|(_, samples)| samples.len()).sum();
+    let sample_id = (sample_id - 1) % num_samples + 1;
+    let (subject, rank) = first_subjects.iter().enumerate().find_map(|(subject, (_, samples))| {
+        samples.iter().position(|&id| id == sample_id).map(|rank| (subject, rank))
+    }).unwrap();
+    assert!(subject < subjects.len(), "subject {} has no pair, the data set only has {} subjects", subject, subjects.len());
+    let samples = &subjects[subject].1;
+    samples[rank % samples.len()]
+}
+
+pub fn test_original(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, debug: bool) -> (Duration, bool) {
+    println!("original {} with {} and {}", test_idx, template_idx, probe_idx);
 
     //Setup
     let (stream, mut engine) = common::make_context_gpu();
@@ -235,10 +713,11 @@ pub fn test_original(test_idx: usize, template_idx: usize, probe_idx: usize, con
     let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
 
     //Create Lookup tables from template
-    let (functions, threshold) = bio_aux::generate_functions_const_length(&template, &config);
+    let rows = table_set.lut_rows(&template, false);
+    let threshold = table_set.threshold();
 
     //Flatten and repeat
-    let r_probe = repeat_probes_to_match_functions(&probe, &functions);
+    let r_probe = repeat_probes_to_match_rows(&probe, &rows);
 
     //Encrypt probes
     let encrypted_probes = r_probe.iter().map(|ps| {
@@ -248,7 +727,7 @@ pub fn test_original(test_idx: usize, template_idx: usize, probe_idx: usize, con
     }).collect_vec();
 
     //Make lookup tables
-    let encrypted_luts = common::generate_lookup_tables_individual(functions, &client_key, params.into(), &mut engine);
+    let encrypted_luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, params.into(), &mut engine);
 
     let (d, r) = if debug {
         let big_client_key = integer::ClientKey::from_raw_parts(client_key.clone());
@@ -281,17 +760,11 @@ pub fn test_original(test_idx: usize, template_idx: usize, probe_idx: usize, con
 }
 
 
-pub fn test_multibit_gpu_cpu(test_idx: usize, template_idx: usize, probe_idx: usize, config: &Config, debug: bool) -> (Duration, bool) {
+pub fn test_multibit_gpu_cpu(test_idx: usize, template_idx: usize, probe_idx: usize, cuda_param_set: MultiBitPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, debug: bool) -> (Duration, bool) {
     println!("multibit_gpu_cpu {} with {} and {}", test_idx, template_idx, probe_idx);
     let thread_count_bs = 7;
     let thread_count_ks = 10;
 
-    let cuda_param_set = if config.block_length == 2 {
-        PARAM_GPU_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS
-    } else {
-        PARAM_GPU_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS
-    };
-
     //Setup
     let (stream, mut engine) = common::make_context_gpu();
     let (
@@ -305,17 +778,18 @@ pub fn test_multibit_gpu_cpu(test_idx: usize, template_idx: usize, probe_idx: us
     let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
 
     //Create Lookup tables from template
-    let (functions, threshold) = bio_aux::generate_functions_const_length(&template, &config);
-    let num_blocks = functions[0].len();
+    let rows = table_set.lut_rows(&template, false);
+    let threshold = table_set.threshold();
+    let num_blocks = rows[0].len();
 
     //Flatten and repeat
-    let (r_probe, f_functions) = flatten_and_repeat(&probe, functions);
+    let (r_probe, f_rows) = flatten_and_repeat(&probe, rows);
 
     //Encrypt probes
     let encrypted_probes = common::encrypt_ciphertextlist(r_probe, &mut engine, &glwe_secret_key.as_lwe_secret_key(), total_modulus, delta, params.into());
 
     //Make lookup tables
-    let encrypted_luts = common::generate_lookup_tables(f_functions, &glwe_secret_key, params.into(), &mut engine);
+    let encrypted_luts = common::generate_lookup_tables_from_rows(&f_rows, &glwe_secret_key, params.into(), &mut engine);
 
     let (d, r) = if debug {
         let key_clone = glwe_secret_key.clone();
@@ -360,15 +834,9 @@ pub fn test_multibit_gpu_cpu(test_idx: usize, template_idx: usize, probe_idx: us
     (r, result)
 }
 
-pub fn test_multibit_gpu(test_idx: usize, template_idx: usize, probe_idx: usize, config: &Config, debug: bool) -> (Duration, bool) {
+pub fn test_multibit_gpu(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: MultiBitPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, debug: bool) -> (Duration, bool) {
     println!("multibit_gpu {} with {} and {}", test_idx, template_idx, probe_idx);
 
-    let parameter_set = if config.block_length == 2 {
-        PARAM_GPU_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS
-    } else {
-        PARAM_GPU_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS
-    };
-
     //Setup
     let (stream, mut engine) = common::make_context_gpu();
     let ((params, glwe_secret_key), (ksk, bsk), (delta, total_modulus)) = tfhe::core_crypto::biometrics::gpu::tfhe_functions_multibit::make_keys_multibit(parameter_set, &stream, &mut engine);
@@ -377,17 +845,18 @@ pub fn test_multibit_gpu(test_idx: usize, template_idx: usize, probe_idx: usize,
     let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
 
     //Create Lookup tables from template
-    let (functions, threshold) = bio_aux::generate_functions_const_length(&template, &config);
-    let num_blocks = functions[0].len();
+    let rows = table_set.lut_rows(&template, false);
+    let threshold = table_set.threshold();
+    let num_blocks = rows[0].len();
 
     //Flatten and repeat
-    let (r_probe, f_functions) = flatten_and_repeat(&probe, functions);
+    let (r_probe, f_rows) = flatten_and_repeat(&probe, rows);
 
     //Encrypt probes
     let encrypted_probes = common::encrypt_cuda_ciphertextlist(r_probe, &mut engine, &glwe_secret_key.as_lwe_secret_key(), total_modulus, delta, params.into(), &stream);
 
     //Make lookup tables
-    let encrypted_luts = common::generate_cuda_lookup_tables(f_functions, &glwe_secret_key, params.into(), &mut engine, &stream);
+    let encrypted_luts = common::generate_cuda_lookup_tables_from_rows(&f_rows, &glwe_secret_key, params.into(), &mut engine, &stream);
 
     let (d, r) = if debug {
         let key_clone = glwe_secret_key.clone();
@@ -424,15 +893,9 @@ pub fn test_multibit_gpu(test_idx: usize, template_idx: usize, probe_idx: usize,
     (r, result)
 }
 
-fn test_classic_gpu(test_idx: usize, template_idx: usize, probe_idx: usize, config: &Config, debug: bool) -> (Duration, bool) {
+fn test_classic_gpu(test_idx: usize, template_idx: usize, probe_idx: usize, parameter_set: ClassicPBSParameters, config: &Config, table_set: &bio_aux::HelrTableSet, debug: bool) -> (Duration, bool) {
     println!("classic_gpu {} with {} and {}", test_idx, template_idx, probe_idx);
 
-    let parameter_set = if config.block_length == 2 {
-        PARAM_MESSAGE_2_CARRY_2_KS_PBS
-    } else {
-        PARAM_MESSAGE_3_CARRY_3_KS_PBS
-    };
-
     //Setup
     let (stream,mut engine) = common::make_context_gpu();
     let (_lwe_secret_key, glwe_secret_key, ksk, bsk, delta, total_modulus, params,) = tfhe::core_crypto::biometrics::gpu::tfhe_functions_classic::make_keys_no_server_key(parameter_set.into(), &stream, &mut engine);
@@ -441,17 +904,18 @@ fn test_classic_gpu(test_idx: usize, template_idx: usize, probe_idx: usize, conf
     let (probe, template) = bio_aux::io::probe_and_template_generation_radix_prepare(probe_idx, template_idx, &config);
 
     //Create Lookup tables from template
-    let (functions, threshold) = bio_aux::generate_functions_const_length(&template, &config);
-    let num_blocks = functions[0].len();
+    let rows = table_set.lut_rows(&template, false);
+    let threshold = table_set.threshold();
+    let num_blocks = rows[0].len();
 
     //Flatten and repeat
-    let (r_probe, f_functions) = flatten_and_repeat(&probe, functions);
+    let (r_probe, f_rows) = flatten_and_repeat(&probe, rows);
 
     //Encrypt probes
     let encrypted_probes = common::encrypt_cuda_ciphertextlist(r_probe, &mut engine, &glwe_secret_key.as_lwe_secret_key(), total_modulus, delta, params, &stream);
 
     //Make lookup tables
-    let encrypted_luts = common::generate_cuda_lookup_tables(f_functions, &glwe_secret_key, params, &mut engine, &stream);
+    let encrypted_luts = common::generate_cuda_lookup_tables_from_rows(&f_rows, &glwe_secret_key, params, &mut engine, &stream);
     let (d, r) = if debug {
         let key_clone = glwe_secret_key.clone();
         classic::authenticate_debug(
@@ -487,33 +951,20 @@ fn test_classic_gpu(test_idx: usize, template_idx: usize, probe_idx: usize, conf
     (r, result)
 }
 
-fn repeat_probes_to_match_functions<F>(probe: &Vec<u8>, functions: &Vec<Vec<F>>) -> Vec<Vec<u64>>
-    where
-        F: Fn(u64) -> u64
-{
-    let mut r_probe = Vec::new();
-    functions.iter().zip(probe.iter()).for_each(|(fs, p) | {
-        let mut r_probe_inner = Vec::new();
-        fs.iter().for_each(|_| {
-            r_probe_inner.push(*p as u64)
-        });
-        r_probe.push(r_probe_inner);
-    });
-    r_probe
+fn repeat_probes_to_match_rows(probe: &Vec<u8>, rows: &Vec<Vec<&[u64]>>) -> Vec<Vec<u64>> {
+    rows.iter().zip(probe.iter()).map(|(rs, p)| {
+        rs.iter().map(|_| *p as u64).collect_vec()
+    }).collect_vec()
 }
 
-
-fn flatten_and_repeat<F>(probe: &Vec<u8>, functions: Vec<Vec<F>>) -> (Vec<u64>, Vec<F>)
-    where
-    F: Fn(u64) -> u64
-{
+fn flatten_and_repeat<'a>(probe: &Vec<u8>, rows: Vec<Vec<&'a [u64]>>) -> (Vec<u64>, Vec<&'a [u64]>) {
     let mut r_probe = Vec::new();
-    let mut f_functions = Vec::new();
-    functions.into_iter().zip(probe.iter()).for_each(|(fs, p) | {
-        fs.into_iter().for_each(|f| {
-            f_functions.push(f);
+    let mut f_rows = Vec::new();
+    rows.into_iter().zip(probe.iter()).for_each(|(rs, p) | {
+        rs.into_iter().for_each(|row| {
+            f_rows.push(row);
             r_probe.push(*p as u64)
         })
     });
-    (r_probe, f_functions)
+    (r_probe, f_rows)
 }
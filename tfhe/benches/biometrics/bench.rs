@@ -0,0 +1,452 @@
+#[path = "../utilities.rs"]
+mod utilities;
+
+use crate::utilities::{write_to_json, OperatorType};
+use bio_aux::HelrTableSet;
+use config::Config;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::Rng;
+use std::sync::Arc;
+use tfhe::core_crypto::biometrics::common;
+use tfhe::core_crypto::biometrics::common::SeededLookupTables;
+use tfhe::core_crypto::biometrics::cpu::batch::{AuthenticationServer, Enrollment};
+use tfhe::core_crypto::biometrics::cpu::key_store::BiometricKeyStore;
+use tfhe::core_crypto::biometrics::cpu::tfhe_functions_classic::make_keys_classic;
+use tfhe::core_crypto::biometrics::cpu::tfhe_functions_multibit::make_keys_multibit;
+use tfhe::core_crypto::biometrics::cpu::{all_in_one_classic, all_in_one_multibit};
+use tfhe::keycache::NamedParam;
+use tfhe::shortint::engine::ShortintEngine;
+use tfhe::shortint::parameters::*;
+use tfhe::shortint::server_key::LookupTableOwned;
+use tfhe::shortint::{Ciphertext, ClientKey, ServerKey};
+
+const CONFIG_PRESETS: [(&str, Config); 4] = [
+    ("BMDB1", config::BMDB1),
+    ("BMDB2", config::BMDB2),
+    ("PUT", config::PUT),
+    ("FRGC", config::FRGC),
+];
+
+const MULTI_BIT_THREAD_COUNT: usize = 4;
+const MULTI_BIT_DETERMINISTIC: bool = false;
+
+fn classic_parameters(config: &Config) -> ClassicPBSParameters {
+    if config.block_length == 2 {
+        PARAM_MESSAGE_2_CARRY_2_KS_PBS
+    } else {
+        PARAM_MESSAGE_3_CARRY_3_KS_PBS
+    }
+}
+
+fn multi_bit_parameters(config: &Config) -> MultiBitPBSParameters {
+    if config.block_length == 2 {
+        PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS
+    } else {
+        PARAM_MULTI_BIT_MESSAGE_3_CARRY_3_GROUP_3_KS_PBS
+    }
+}
+
+/// Random HELR tables with the shape of `config`, so the benchmarks do not depend on the data
+/// sets. The entries are small enough for the sum of all tables to fit the sum blocks.
+///
+/// Like the real tables, the top right entry is the minimum, `offset_helr_table` relies on it.
+fn synthetic_tables(config: &Config) -> HelrTableSet {
+    let mut rng = rand::thread_rng();
+    let side = 1usize << config.block_length;
+    let max_sum = (1i64 << (config.block_length * config.num_blocks_sum)) - 1;
+    let max_entry = (max_sum / config.num_tables as i64) as i32;
+    let min_entry = -max_entry / 2;
+    let tables = (0..config.num_tables)
+        .map(|_| {
+            let mut table: Vec<Vec<i32>> = (0..side)
+                .map(|_| {
+                    (0..side)
+                        .map(|_| rng.gen_range(min_entry..=min_entry + max_entry))
+                        .collect()
+                })
+                .collect();
+            table[0][side - 1] = min_entry;
+            table
+        })
+        .collect();
+    HelrTableSet::from_tables(tables, config)
+}
+
+struct Inputs {
+    tables: HelrTableSet,
+    template: Vec<u8>,
+    probe: Vec<u8>,
+}
+
+impl Inputs {
+    fn new(config: &Config) -> Self {
+        let mut rng = rand::thread_rng();
+        let side = 1u8 << config.block_length;
+        Self {
+            tables: synthetic_tables(config),
+            template: (0..config.num_tables)
+                .map(|_| rng.gen_range(0..side))
+                .collect(),
+            probe: (0..config.num_tables)
+                .map(|_| rng.gen_range(0..side))
+                .collect(),
+        }
+    }
+
+    fn encrypt_probe(&self, client_key: &ClientKey, config: &Config) -> Vec<Vec<Ciphertext>> {
+        let modulus = MessageModulus(1 << (2 * config.block_length));
+        self.tables
+            .lut_rows(&self.template, true)
+            .iter()
+            .zip(self.probe.iter())
+            .map(|(rows, &p)| {
+                rows.iter()
+                    .map(|_| client_key.encrypt_with_message_modulus(p as u64, modulus))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn encrypt_luts(
+        &self,
+        client_key: &ClientKey,
+        params: PBSParameters,
+        engine: &mut ShortintEngine,
+    ) -> Vec<Vec<LookupTableOwned>> {
+        let rows = self.tables.lut_rows(&self.template, true);
+        common::generate_lookup_tables_individual_from_rows(&rows, client_key, params.into(), engine)
+    }
+
+    fn encrypt_seeded_luts(
+        &self,
+        client_key: &ClientKey,
+        params: PBSParameters,
+        engine: &mut ShortintEngine,
+    ) -> SeededLookupTables {
+        let rows = self.tables.lut_rows(&self.template, true);
+        common::generate_seeded_lookup_tables_individual_from_rows(
+            &rows,
+            client_key,
+            params.into(),
+            engine,
+        )
+    }
+}
+
+fn write_record(bench_id: &str, display_name: &str, params: PBSParameters, config: &Config) {
+    write_to_json::<u64, _>(
+        bench_id,
+        params,
+        params.name(),
+        display_name,
+        &OperatorType::Atomic,
+        (config.block_length * config.num_blocks_sum) as u32,
+        vec![config.block_length as u32],
+    );
+}
+
+fn keygen(c: &mut Criterion) {
+    let bench_name = "biometrics::keygen";
+    let mut bench_group = c.benchmark_group(bench_name);
+    bench_group
+        .sample_size(10)
+        .measurement_time(std::time::Duration::from_secs(60));
+
+    let mut engine = ShortintEngine::new();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let params = classic_parameters(config);
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                let _ = make_keys_classic(params, &mut engine);
+            })
+        });
+        write_record(&bench_id, "keygen", params.into(), config);
+
+        let params = multi_bit_parameters(config);
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                let _ = make_keys_multibit(params, MULTI_BIT_THREAD_COUNT, MULTI_BIT_DETERMINISTIC, &mut engine);
+            })
+        });
+        write_record(&bench_id, "keygen", params.into(), config);
+    }
+
+    bench_group.finish()
+}
+
+/// Reloading the keys of a parameter set from the key store, including the Fourier conversion of
+/// the bootstrapping key, as an alternative to [`keygen`].
+fn key_store_load(c: &mut Criterion) {
+    let bench_name = "biometrics::key_store_load";
+    let mut bench_group = c.benchmark_group(bench_name);
+    bench_group
+        .sample_size(10)
+        .measurement_time(std::time::Duration::from_secs(60));
+
+    let mut engine = ShortintEngine::new();
+    let key_store = BiometricKeyStore::default();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let params = classic_parameters(config);
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        let _ = key_store.classic_keys(params, &mut engine);
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                key_store.clear_in_memory_cache();
+                let _ = key_store.classic_keys(params, &mut engine);
+            })
+        });
+        write_record(&bench_id, "key_store_load", params.into(), config);
+
+        let params = multi_bit_parameters(config);
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        let _ = key_store.multibit_keys(params, MULTI_BIT_THREAD_COUNT, MULTI_BIT_DETERMINISTIC, &mut engine);
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                key_store.clear_in_memory_cache();
+                let _ = key_store.multibit_keys(params, MULTI_BIT_THREAD_COUNT, MULTI_BIT_DETERMINISTIC, &mut engine);
+            })
+        });
+        write_record(&bench_id, "key_store_load", params.into(), config);
+    }
+
+    bench_group.finish()
+}
+
+fn lut_encryption(c: &mut Criterion) {
+    let bench_name = "biometrics::lut_encryption";
+    let mut bench_group = c.benchmark_group(bench_name);
+    bench_group
+        .sample_size(10)
+        .measurement_time(std::time::Duration::from_secs(30));
+
+    let mut engine = ShortintEngine::new();
+    let key_store = BiometricKeyStore::default();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let params = classic_parameters(config);
+        let (client_key, _) = key_store.classic_keys(params, &mut engine);
+        let inputs = Inputs::new(config);
+
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                let _ = inputs.encrypt_luts(&client_key, params.into(), &mut engine);
+            })
+        });
+        write_record(&bench_id, "lut_encryption", params.into(), config);
+    }
+
+    bench_group.finish()
+}
+
+/// Measures the seeded LUT encryption and the decompression on load, and prints the serialized
+/// size of a template with and without seeds.
+fn seeded_lut_encryption(c: &mut Criterion) {
+    let bench_name = "biometrics::seeded_lut_encryption";
+    let mut bench_group = c.benchmark_group(bench_name);
+    bench_group
+        .sample_size(10)
+        .measurement_time(std::time::Duration::from_secs(30));
+
+    let mut engine = ShortintEngine::new();
+    let key_store = BiometricKeyStore::default();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let params = classic_parameters(config);
+        let (client_key, _) = key_store.classic_keys(params, &mut engine);
+        let inputs = Inputs::new(config);
+
+        let luts = inputs.encrypt_luts(&client_key, params.into(), &mut engine);
+        let seeded_luts = inputs.encrypt_seeded_luts(&client_key, params.into(), &mut engine);
+        let size = bincode::serialized_size(&luts).unwrap();
+        let seeded_size = bincode::serialized_size(&seeded_luts).unwrap();
+        println!(
+            "{preset} template: {size} bytes, seeded: {seeded_size} bytes ({:.1}% smaller)",
+            100.0 * (1.0 - seeded_size as f64 / size as f64)
+        );
+
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                let _ = inputs.encrypt_seeded_luts(&client_key, params.into(), &mut engine);
+            })
+        });
+        write_record(&bench_id, "seeded_lut_encryption", params.into(), config);
+
+        let bench_id = format!(
+            "biometrics::seeded_lut_decompression::{preset}::{}",
+            params.name()
+        );
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter_batched(
+                || seeded_luts.clone(),
+                |seeded_luts| seeded_luts.decompress(),
+                BatchSize::PerIteration,
+            )
+        });
+        write_record(&bench_id, "seeded_lut_decompression", params.into(), config);
+    }
+
+    bench_group.finish()
+}
+
+fn probe_encryption(c: &mut Criterion) {
+    let bench_name = "biometrics::probe_encryption";
+    let mut bench_group = c.benchmark_group(bench_name);
+
+    let mut engine = ShortintEngine::new();
+    let key_store = BiometricKeyStore::default();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let params = classic_parameters(config);
+        let (client_key, _) = key_store.classic_keys(params, &mut engine);
+        let inputs = Inputs::new(config);
+
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter(|| {
+                let _ = inputs.encrypt_probe(&client_key, config);
+            })
+        });
+        write_record(&bench_id, "probe_encryption", params.into(), config);
+    }
+
+    bench_group.finish()
+}
+
+type Authenticator = fn(
+    ServerKey,
+    Vec<Vec<Ciphertext>>,
+    Vec<Vec<LookupTableOwned>>,
+    usize,
+    usize,
+) -> (tfhe::integer::BooleanBlock, std::time::Duration);
+
+fn bench_authenticator(
+    c: &mut Criterion,
+    bench_name: &str,
+    display_name: &str,
+    authenticator: Authenticator,
+    multi_bit: bool,
+) {
+    let mut bench_group = c.benchmark_group(bench_name);
+    bench_group
+        .sample_size(10)
+        .measurement_time(std::time::Duration::from_secs(60));
+
+    let mut engine = ShortintEngine::new();
+    let key_store = BiometricKeyStore::default();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let (params, (client_key, server_key)): (PBSParameters, _) = if multi_bit {
+            let params = multi_bit_parameters(config);
+            (
+                params.into(),
+                key_store.multibit_keys(params, MULTI_BIT_THREAD_COUNT, MULTI_BIT_DETERMINISTIC, &mut engine),
+            )
+        } else {
+            let params = classic_parameters(config);
+            (params.into(), key_store.classic_keys(params, &mut engine))
+        };
+        let inputs = Inputs::new(config);
+        let probe = inputs.encrypt_probe(&client_key, config);
+        let luts = inputs.encrypt_luts(&client_key, params, &mut engine);
+        let threshold = inputs.tables.threshold();
+
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter_batched(
+                || (server_key.clone(), probe.clone(), luts.clone()),
+                |(server_key, probe, luts)| {
+                    authenticator(server_key, probe, luts, threshold, config.num_blocks_sum)
+                },
+                BatchSize::PerIteration,
+            )
+        });
+        write_record(&bench_id, display_name, params, config);
+    }
+
+    bench_group.finish()
+}
+
+fn authenticate_classic(c: &mut Criterion) {
+    bench_authenticator(
+        c,
+        "biometrics::authenticate::classic",
+        "authenticate",
+        all_in_one_classic::authenticate,
+        false,
+    );
+}
+
+fn authenticate_classic_accumulate(c: &mut Criterion) {
+    bench_authenticator(
+        c,
+        "biometrics::authenticate::classic_accumulate",
+        "authenticate",
+        all_in_one_classic::authenticate_accumulate,
+        false,
+    );
+}
+
+fn authenticate_multi_bit(c: &mut Criterion) {
+    bench_authenticator(
+        c,
+        "biometrics::authenticate::multi_bit",
+        "authenticate",
+        all_in_one_multibit::authenticate,
+        true,
+    );
+}
+
+fn authenticate_batch_server(c: &mut Criterion) {
+    let bench_name = "biometrics::authenticate::batch_server";
+    let mut bench_group = c.benchmark_group(bench_name);
+    bench_group
+        .sample_size(10)
+        .measurement_time(std::time::Duration::from_secs(60));
+
+    let mut engine = ShortintEngine::new();
+    let key_store = BiometricKeyStore::default();
+    for (preset, config) in CONFIG_PRESETS.iter() {
+        let params = classic_parameters(config);
+        let (client_key, server_key) = key_store.classic_keys(params, &mut engine);
+        let server = AuthenticationServer::new(server_key, 0).unwrap();
+        let inputs = Inputs::new(config);
+        let probe = inputs.encrypt_probe(&client_key, config);
+        let enrollment = Arc::new(Enrollment {
+            luts: inputs.encrypt_luts(&client_key, params.into(), &mut engine),
+            threshold: inputs.tables.threshold(),
+            num_sum_blocks: config.num_blocks_sum,
+        });
+
+        let bench_id = format!("{bench_name}::{preset}::{}", params.name());
+        bench_group.bench_function(&bench_id, |b| {
+            b.iter_batched(
+                || probe.clone(),
+                |probe| server.authenticate(&enrollment, probe),
+                BatchSize::PerIteration,
+            )
+        });
+        write_record(&bench_id, "authenticate", params.into(), config);
+    }
+
+    bench_group.finish()
+}
+
+criterion_group!(
+    client_ops,
+    keygen,
+    key_store_load,
+    lut_encryption,
+    seeded_lut_encryption,
+    probe_encryption
+);
+
+criterion_group!(
+    server_ops,
+    authenticate_classic,
+    authenticate_classic_accumulate,
+    authenticate_multi_bit,
+    authenticate_batch_server
+);
+
+criterion_main!(client_ops, server_ops);
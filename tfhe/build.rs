@@ -49,6 +49,8 @@ fn gen_c_api() {
         "shortint-c-api",
         #[cfg(feature = "high-level-c-api")]
         "high-level-c-api",
+        #[cfg(feature = "biometrics-c-api")]
+        "biometrics-c-api",
         #[cfg(feature = "boolean")]
         "boolean",
         #[cfg(feature = "shortint")]
@@ -0,0 +1,104 @@
+#include "tfhe.h"
+
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#define NUM_TABLES 2
+#define TABLE_SIDE 4
+
+// Two single block features, the entries decrease with the distance between template and probe
+static const int32_t TABLES[NUM_TABLES * TABLE_SIDE * TABLE_SIDE] = {
+    3, 2, 1, 0, 2, 3, 2, 1, 1, 2, 3, 2, 0, 1, 2, 3,
+    3, 2, 1, 0, 2, 3, 2, 1, 1, 2, 3, 2, 0, 1, 2, 3,
+};
+
+static const double QBINS[TABLE_SIDE - 1] = {0.25, 0.5, 0.75};
+
+int authenticate_test(const ClientKey *client_key, const BiometricTemplate *template,
+                      const double *probe_features, bool expected) {
+  int ok;
+  BiometricProbe *probe = NULL;
+  FheBool *decision = NULL;
+
+  ok = biometric_probe_encrypt(client_key, template, probe_features, NUM_TABLES, QBINS,
+                               TABLE_SIDE - 1, &probe);
+  assert(ok == 0);
+
+  ok = biometric_authenticate(template, probe, &decision);
+  assert(ok == 0);
+
+  bool clear;
+  ok = fhe_bool_decrypt(decision, client_key, &clear);
+  assert(ok == 0);
+
+  biometric_probe_destroy(probe);
+  fhe_bool_destroy(decision);
+
+  return clear == expected ? 0 : 1;
+}
+
+int main(void) {
+  int ok;
+  BiometricConfig *config = NULL;
+  BiometricTables *tables = NULL;
+  BiometricTemplate *template = NULL;
+  BiometricTemplate *deserialized_template = NULL;
+  ClientKey *client_key = NULL;
+  ServerKey *server_key = NULL;
+
+  // 2 tables of 1 block, sums on 2 blocks of 2 bits, accepts scores >= 5
+  ok = biometric_config_new(NUM_TABLES, 1, 2, 2, 5, &config);
+  assert(ok == 0);
+
+  ok = biometric_tables_from_values(config, TABLES, TABLE_SIDE, TABLE_SIDE, &tables);
+  assert(ok == 0);
+
+  ok = biometric_generate_keys(config, &client_key, &server_key);
+  assert(ok == 0);
+
+  // Quantized to {1, 2}
+  const double template_features[NUM_TABLES] = {0.3, 0.6};
+  ok = biometric_template_encrypt(client_key, tables, template_features, NUM_TABLES, QBINS,
+                                  TABLE_SIDE - 1, &template);
+  assert(ok == 0);
+
+  // The enrolled template goes through a buffer like it would on its way to the server
+  DynamicBuffer template_buffer = {.pointer = NULL, .length = 0, .destructor = NULL};
+  ok = biometric_template_serialize(template, &template_buffer);
+  assert(ok == 0);
+
+  DynamicBufferView template_view = {.pointer = template_buffer.pointer,
+                                     .length = template_buffer.length};
+  ok = biometric_template_deserialize(template_view, &deserialized_template);
+  assert(ok == 0);
+
+  ok = set_server_key(server_key);
+  assert(ok == 0);
+
+  // 3 + 3 >= 5
+  const double genuine_features[NUM_TABLES] = {0.4, 0.7};
+  ok = authenticate_test(client_key, deserialized_template, genuine_features, true);
+  assert(ok == 0);
+
+  // Quantized to {3, 0}, 1 + 1 < 5
+  const double impostor_features[NUM_TABLES] = {0.9, 0.1};
+  ok = authenticate_test(client_key, deserialized_template, impostor_features, false);
+  assert(ok == 0);
+
+  // Unknown presets are reported as errors
+  BiometricConfig *unknown = NULL;
+  ok = biometric_config_preset("UNKNOWN", &unknown);
+  assert(ok != 0);
+  assert(unknown == NULL);
+
+  destroy_dynamic_buffer(&template_buffer);
+  biometric_template_destroy(template);
+  biometric_template_destroy(deserialized_template);
+  biometric_tables_destroy(tables);
+  biometric_config_destroy(config);
+  client_key_destroy(client_key);
+  server_key_destroy(server_key);
+
+  return EXIT_SUCCESS;
+}
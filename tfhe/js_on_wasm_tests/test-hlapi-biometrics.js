@@ -0,0 +1,105 @@
+const test = require('node:test');
+const assert = require('node:assert').strict;
+const {
+    BiometricConfig,
+    BiometricProbe,
+    BiometricQuantizer,
+    BiometricTables,
+    BiometricTemplate,
+    FheBool,
+    TfheClientKey,
+    TfheCompressedServerKey,
+    TfhePublicKey,
+    biometric_authenticate,
+} = require("../pkg/tfhe.js");
+
+const SIZE_LIMIT = BigInt(1024) * BigInt(1024) * BigInt(1024);
+
+// Two single block features, the entries decrease with the distance between template and probe
+const TABLE = [
+    [3, 2, 1, 0],
+    [2, 3, 2, 1],
+    [1, 2, 3, 2],
+    [0, 1, 2, 3],
+];
+const TABLES_CSV = [TABLE, TABLE].map((table) => table.map((row) => row.join(',')).join('\n')).join('\n');
+
+function enroll() {
+    // 2 tables of 1 block, sums on 2 blocks of 2 bits, accepts scores >= 5
+    let config = BiometricConfig.new(2, 1, 2, 2, BigInt(5));
+    let tables = BiometricTables.from_csv(config, TABLES_CSV);
+    let quantizer = BiometricQuantizer.from_csv('0.25,0.5,0.75');
+
+    let clientKey = TfheClientKey.generate(config.tfhe_config());
+    let publicKey = TfhePublicKey.new(clientKey);
+
+    // Quantized to {1, 2}
+    let template = BiometricTemplate.encrypt(clientKey, tables, quantizer, new Float64Array([0.3, 0.6]));
+
+    return {clientKey, publicKey, quantizer, template};
+}
+
+test('hlapi_biometrics_quantize', (t) => {
+    let quantizer = BiometricQuantizer.new(new Float64Array([0.25, 0.5, 0.75]));
+
+    let bins = quantizer.quantize(new Float64Array([0.1, 0.25, 0.3, 0.9]));
+    assert.deepStrictEqual(Array.from(bins), [0, 0, 1, 3]);
+});
+
+test('hlapi_biometrics_config', (t) => {
+    let config = BiometricConfig.preset('BMDB1');
+    config.tfhe_config();
+
+    assert.throws(() => BiometricConfig.preset('UNKNOWN'));
+    assert.throws(() => BiometricConfig.new(2, 1, 2, 4, BigInt(5)).tfhe_config());
+
+    // 3 values cannot be split into 2 tables of 4 by 4
+    let custom = BiometricConfig.new(2, 1, 2, 2, BigInt(5));
+    assert.throws(() => BiometricTables.from_values(custom, new Int32Array([0, 1, 2]), 4, 4));
+    BiometricTables.from_values(custom, new Int32Array(TABLE.flat().concat(TABLE.flat())), 4, 4);
+});
+
+test('hlapi_biometrics_public_key_probe', (t) => {
+    let {clientKey, publicKey, quantizer, template} = enroll();
+
+    // The template is stored by the server, the browser gets it back to encrypt probes
+    let serializedTemplate = template.safe_serialize(SIZE_LIMIT);
+    template = BiometricTemplate.safe_deserialize(serializedTemplate, SIZE_LIMIT);
+    assert.deepStrictEqual(template.num_features(), 2);
+
+    // Quantized to {1, 2}, 3 + 3 >= 5
+    let genuine = BiometricProbe.encrypt_with_public_key(publicKey, template, quantizer, new Float64Array([0.4, 0.7]));
+    let serializedProbe = genuine.safe_serialize(SIZE_LIMIT);
+    genuine = BiometricProbe.safe_deserialize(serializedProbe, SIZE_LIMIT);
+
+    // The probe must have one value per feature of the template
+    assert.throws(() => BiometricProbe.encrypt_with_public_key(publicKey, template, quantizer, new Float64Array([0.4])));
+
+    let serverKey = TfheCompressedServerKey.new(clientKey);
+    let decision = biometric_authenticate(template, genuine, serverKey);
+    assert.deepStrictEqual(decision.decrypt(clientKey), true);
+
+    // Quantized to {3, 0}, 1 + 1 < 5
+    let impostor = BiometricProbe.encrypt_with_public_key(publicKey, template, quantizer, new Float64Array([0.9, 0.1]));
+    decision = biometric_authenticate(template, impostor, serverKey);
+    assert.deepStrictEqual(decision.decrypt(clientKey), false);
+});
+
+test('hlapi_biometrics_authenticate', (t) => {
+    let {clientKey, quantizer, template} = enroll();
+    let serverKey = TfheCompressedServerKey.new(clientKey);
+
+    // Quantized to {1, 2}, 3 + 3 >= 5
+    let genuine = BiometricProbe.encrypt_with_client_key(clientKey, template, quantizer, new Float64Array([0.4, 0.7]));
+    let decision = biometric_authenticate(template, genuine, serverKey);
+
+    // The decision travels back to the client holding the key
+    let serializedDecision = decision.safe_serialize(SIZE_LIMIT);
+    let received = FheBool.safe_deserialize(serializedDecision, SIZE_LIMIT);
+    assert.deepStrictEqual(received.decrypt(clientKey), true);
+
+    // Quantized to {3, 0}, 1 + 1 < 5
+    let impostor = BiometricProbe.encrypt_with_client_key(clientKey, template, quantizer, new Float64Array([0.9, 0.1]));
+    decision = biometric_authenticate(template, impostor, serverKey);
+    assert.deepStrictEqual(decision.decrypt(clientKey), false);
+});
@@ -0,0 +1,256 @@
+//! Bindings for [`crate::high_level_api::biometrics`], enough for a capture device to enroll
+//! templates, encrypt probes and decrypt the decision of the server.
+//!
+//! The decision is a regular [`FheBool`], it is decrypted with `fhe_bool_decrypt`.
+use super::booleans::FheBool;
+use super::keys::{ClientKey, ServerKey};
+use super::utils::*;
+use crate::c_api::utils::*;
+use crate::high_level_api::biometrics;
+use crate::shortint::parameters::{PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS};
+use std::ffi::{c_char, CStr};
+use std::os::raw::c_int;
+
+pub struct BiometricConfig(pub(in crate::c_api) config::Config);
+pub struct BiometricTables {
+    tables: bio_aux::HelrTableSet,
+    num_blocks_sum: usize,
+}
+pub struct BiometricTemplate(pub(in crate::c_api) biometrics::EncryptedTemplate);
+pub struct BiometricProbe(pub(in crate::c_api) biometrics::EncryptedProbe);
+
+impl_destroy_on_type!(BiometricConfig);
+impl_destroy_on_type!(BiometricTables);
+impl_destroy_on_type!(BiometricTemplate);
+impl_destroy_on_type!(BiometricProbe);
+
+impl_serialize_deserialize_on_type!(BiometricTemplate);
+impl_serialize_deserialize_on_type!(BiometricProbe);
+impl_safe_serialize_on_type!(BiometricTemplate);
+impl_safe_serialize_on_type!(BiometricProbe);
+
+unsafe fn quantize(
+    features: *const f64,
+    features_len: usize,
+    qbins: *const f64,
+    qbins_len: usize,
+) -> Vec<u8> {
+    check_ptr_is_non_null_and_aligned(features).unwrap();
+    check_ptr_is_non_null_and_aligned(qbins).unwrap();
+    let features = std::slice::from_raw_parts(features, features_len);
+    let qbins = std::slice::from_raw_parts(qbins, qbins_len).to_vec();
+
+    bio_aux::io::quantize_feature_vector(features.to_vec(), &qbins)
+        .into_iter()
+        .map(|bin| u8::try_from(bin).unwrap())
+        .collect()
+}
+
+/// Returns the data set configuration named `name`, one of `BMDB1`, `BMDB2`, `PUT` or `FRGC`.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_config_preset(
+    name: *const c_char,
+    result: *mut *mut BiometricConfig,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        *result = std::ptr::null_mut();
+
+        let name = CStr::from_ptr(name).to_str().unwrap();
+        let inner = match name {
+            "BMDB1" => config::BMDB1,
+            "BMDB2" => config::BMDB2,
+            "PUT" => config::PUT,
+            "FRGC" => config::FRGC,
+            _ => panic!("unknown biometric config: {name}"),
+        };
+
+        *result = Box::into_raw(Box::new(BiometricConfig(inner)));
+    })
+}
+
+/// Returns a configuration for a data set that has no preset.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_config_new(
+    num_tables: usize,
+    num_blocks: usize,
+    num_blocks_sum: usize,
+    block_length: usize,
+    threshold: i64,
+    result: *mut *mut BiometricConfig,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        *result = std::ptr::null_mut();
+
+        let inner = config::Config {
+            data_set_name: "custom",
+            num_blocks,
+            num_blocks_sum,
+            block_length,
+            num_tables,
+            threshold,
+            probe_encoding: config::ProbeEncoding::Block,
+        };
+
+        *result = Box::into_raw(Box::new(BiometricConfig(inner)));
+    })
+}
+
+/// Generates high level API keys whose blocks have the block length of `config`.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_generate_keys(
+    config: *const BiometricConfig,
+    result_client_key: *mut *mut ClientKey,
+    result_server_key: *mut *mut ServerKey,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result_client_key).unwrap();
+        check_ptr_is_non_null_and_aligned(result_server_key).unwrap();
+
+        *result_client_key = std::ptr::null_mut();
+        *result_server_key = std::ptr::null_mut();
+
+        let config = get_ref_checked(config).unwrap();
+        let params = match config.0.block_length {
+            2 => PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+            3 => PARAM_MESSAGE_3_CARRY_3_KS_PBS,
+            block_length => panic!("unsupported block length: {block_length}"),
+        };
+        let hl_config = crate::high_level_api::ConfigBuilder::default()
+            .use_custom_parameters(params, None)
+            .build();
+
+        let (cks, sks) = crate::high_level_api::generate_keys(hl_config);
+
+        *result_client_key = Box::into_raw(Box::new(ClientKey(cks)));
+        *result_server_key = Box::into_raw(Box::new(ServerKey(sks)));
+    })
+}
+
+/// Loads the HELR tables `{tables_prefix}{i}.csv` for `i` in `0..num_tables`.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_tables_load(
+    config: *const BiometricConfig,
+    tables_prefix: *const c_char,
+    result: *mut *mut BiometricTables,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        *result = std::ptr::null_mut();
+
+        let config = get_ref_checked(config).unwrap();
+        let tables_prefix = CStr::from_ptr(tables_prefix).to_str().unwrap();
+        let tables = bio_aux::io::read_helr_tables(tables_prefix, config.0.num_tables).unwrap();
+
+        *result = Box::into_raw(Box::new(BiometricTables {
+            tables: bio_aux::HelrTableSet::from_tables(tables, &config.0),
+            num_blocks_sum: config.0.num_blocks_sum,
+        }));
+    })
+}
+
+/// Builds the HELR tables from `num_tables * rows * columns` row major values, e.g. tables
+/// embedded in a firmware image.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_tables_from_values(
+    config: *const BiometricConfig,
+    values: *const i32,
+    rows: usize,
+    columns: usize,
+    result: *mut *mut BiometricTables,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        check_ptr_is_non_null_and_aligned(values).unwrap();
+        *result = std::ptr::null_mut();
+
+        let config = get_ref_checked(config).unwrap();
+        let values = std::slice::from_raw_parts(values, config.0.num_tables * rows * columns);
+        let tables = values
+            .chunks_exact(rows * columns)
+            .map(|table| table.chunks_exact(columns).map(<[i32]>::to_vec).collect())
+            .collect();
+
+        *result = Box::into_raw(Box::new(BiometricTables {
+            tables: bio_aux::HelrTableSet::from_tables(tables, &config.0),
+            num_blocks_sum: config.0.num_blocks_sum,
+        }));
+    })
+}
+
+/// Quantizes the raw `features` of a template with `qbins` and encrypts its LUTs.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_template_encrypt(
+    client_key: *const ClientKey,
+    tables: *const BiometricTables,
+    features: *const f64,
+    features_len: usize,
+    qbins: *const f64,
+    qbins_len: usize,
+    result: *mut *mut BiometricTemplate,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        *result = std::ptr::null_mut();
+
+        let client_key = get_ref_checked(client_key).unwrap();
+        let tables = get_ref_checked(tables).unwrap();
+        let template = quantize(features, features_len, qbins, qbins_len);
+
+        let rows = tables.tables.lut_rows(&template, true);
+        let inner = biometrics::EncryptedTemplate::encrypt(
+            &rows,
+            tables.tables.threshold(),
+            tables.num_blocks_sum,
+            &client_key.0,
+        );
+
+        *result = Box::into_raw(Box::new(BiometricTemplate(inner)));
+    })
+}
+
+/// Quantizes the raw `features` of a probe with `qbins` and encrypts them for `template`.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_probe_encrypt(
+    client_key: *const ClientKey,
+    template: *const BiometricTemplate,
+    features: *const f64,
+    features_len: usize,
+    qbins: *const f64,
+    qbins_len: usize,
+    result: *mut *mut BiometricProbe,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        *result = std::ptr::null_mut();
+
+        let client_key = get_ref_checked(client_key).unwrap();
+        let template = get_ref_checked(template).unwrap();
+        let probe = quantize(features, features_len, qbins, qbins_len);
+
+        let inner = biometrics::EncryptedProbe::encrypt(&probe, &template.0, &client_key.0);
+
+        *result = Box::into_raw(Box::new(BiometricProbe(inner)));
+    })
+}
+
+/// Compares `probe` against `template` with the server key set by `set_server_key`.
+#[no_mangle]
+pub unsafe extern "C" fn biometric_authenticate(
+    template: *const BiometricTemplate,
+    probe: *const BiometricProbe,
+    result: *mut *mut FheBool,
+) -> c_int {
+    catch_panic(|| {
+        check_ptr_is_non_null_and_aligned(result).unwrap();
+        *result = std::ptr::null_mut();
+
+        let template = get_ref_checked(template).unwrap();
+        let probe = get_ref_checked(probe).unwrap();
+
+        let decision = biometrics::authenticate(&template.0, probe.0.clone());
+
+        *result = Box::into_raw(Box::new(FheBool(decision)));
+    })
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "biometrics-c-api")]
+pub mod biometrics;
 #[cfg(feature = "boolean")]
 pub mod booleans;
 pub mod config;
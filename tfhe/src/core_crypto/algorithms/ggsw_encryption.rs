@@ -6,7 +6,9 @@ use crate::core_crypto::algorithms::*;
 use crate::core_crypto::commons::generators::EncryptionRandomGenerator;
 use crate::core_crypto::commons::math::decomposition::{DecompositionLevel, SignedDecomposer};
 use crate::core_crypto::commons::math::random::{ActivatedRandomGenerator, Distribution, Uniform};
-use crate::core_crypto::commons::parameters::PlaintextCount;
+use crate::core_crypto::commons::parameters::{
+    CiphertextModulus, DecompositionBaseLog, PlaintextCount,
+};
 use crate::core_crypto::commons::traits::*;
 use crate::core_crypto::entities::*;
 use rayon::prelude::*;
@@ -112,19 +114,16 @@ pub fn encrypt_constant_ggsw_ciphertext<Scalar, NoiseDistribution, KeyCont, Outp
     let decomp_base_log = output.decomposition_base_log();
     let ciphertext_modulus = output.ciphertext_modulus();
 
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
     for (level_index, (mut level_matrix, mut generator)) in
         output.iter_mut().zip(gen_iter).enumerate()
     {
         let decomp_level = DecompositionLevel(level_index + 1);
-        // We scale the factor down from the native torus to whatever our torus is, the
-        // encryption process will scale it back up
-        let factor = encoded
-            .0
-            .wrapping_neg()
-            .wrapping_mul(Scalar::ONE << (Scalar::BITS - (decomp_base_log.0 * decomp_level.0)))
-            .wrapping_div(ciphertext_modulus.get_power_of_two_scaling_to_native_torus());
+        let factor = ggsw_encryption_multiplicative_factor(
+            ciphertext_modulus,
+            decomp_level,
+            decomp_base_log,
+            encoded,
+        );
 
         // We iterate over the rows of the level matrix, the last row needs special treatment
         let gen_iter = generator
@@ -254,18 +253,15 @@ pub fn par_encrypt_constant_ggsw_ciphertext<Scalar, NoiseDistribution, KeyCont,
     let decomp_base_log = output.decomposition_base_log();
     let ciphertext_modulus = output.ciphertext_modulus();
 
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
     output.par_iter_mut().zip(gen_iter).enumerate().for_each(
         |(level_index, (mut level_matrix, mut generator))| {
             let decomp_level = DecompositionLevel(level_index + 1);
-            // We scale the factor down from the native torus to whatever our torus is, the
-            // encryption process will scale it back up
-            let factor = encoded
-                .0
-                .wrapping_neg()
-                .wrapping_mul(Scalar::ONE << (Scalar::BITS - (decomp_base_log.0 * decomp_level.0)))
-                .wrapping_div(ciphertext_modulus.get_power_of_two_scaling_to_native_torus());
+            let factor = ggsw_encryption_multiplicative_factor(
+                ciphertext_modulus,
+                decomp_level,
+                decomp_base_log,
+                encoded,
+            );
 
             // We iterate over the rows of the level matrix, the last row needs special
             // treatment
@@ -314,6 +310,8 @@ fn encrypt_constant_ggsw_level_matrix_row<Scalar, NoiseDistribution, KeyCont, Ou
     OutputCont: ContainerMut<Element = Scalar>,
     Gen: ByteRandomGenerator,
 {
+    let ciphertext_modulus = row_as_glwe.ciphertext_modulus();
+
     if row_index < last_row_index {
         // Not the last row
         let sk_poly_list = glwe_secret_key.as_polynomial_list();
@@ -323,17 +321,61 @@ fn encrypt_constant_ggsw_level_matrix_row<Scalar, NoiseDistribution, KeyCont, Ou
         let mut body = row_as_glwe.get_mut_body();
         body.as_mut().copy_from_slice(sk_poly.as_ref());
 
-        slice_wrapping_scalar_mul_assign(body.as_mut(), factor);
+        if ciphertext_modulus.is_compatible_with_native_modulus() {
+            slice_wrapping_scalar_mul_assign(body.as_mut(), factor);
+        } else {
+            slice_wrapping_scalar_mul_assign_custom_mod(
+                body.as_mut(),
+                factor,
+                ciphertext_modulus.get_custom_modulus().cast_into(),
+            );
+        }
     } else {
         // The last row needs a slightly different treatment
         let mut body = row_as_glwe.get_mut_body();
 
         body.as_mut().fill(Scalar::ZERO);
-        body.as_mut()[0] = factor.wrapping_neg();
+        body.as_mut()[0] = if ciphertext_modulus.is_compatible_with_native_modulus() {
+            factor.wrapping_neg()
+        } else {
+            factor.wrapping_neg_custom_mod(ciphertext_modulus.get_custom_modulus().cast_into())
+        };
     }
     encrypt_glwe_ciphertext_assign(glwe_secret_key, row_as_glwe, noise_distribution, generator);
 }
 
+/// Compute the factor multiplying the secret key in the rows of the level matrix of a GGSW
+/// ciphertext, i.e. the opposite of the encoded value times the gadget of the level.
+///
+/// For non power of two moduli the gadget is $\lfloor q / B^{j} \rfloor$, matching the
+/// recomposition of
+/// [`SignedDecomposerNonNative`](`crate::core_crypto::commons::math::decomposition::SignedDecomposerNonNative`).
+fn ggsw_encryption_multiplicative_factor<Scalar: UnsignedInteger>(
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    decomp_level: DecompositionLevel,
+    decomp_base_log: DecompositionBaseLog,
+    encoded: Plaintext<Scalar>,
+) -> Scalar {
+    if ciphertext_modulus.is_compatible_with_native_modulus() {
+        // We scale the factor down from the native torus to whatever our torus is, the
+        // encryption process will scale it back up
+        encoded
+            .0
+            .wrapping_neg()
+            .wrapping_mul(Scalar::ONE << (Scalar::BITS - (decomp_base_log.0 * decomp_level.0)))
+            .wrapping_div(ciphertext_modulus.get_power_of_two_scaling_to_native_torus())
+    } else {
+        let modulus: Scalar = ciphertext_modulus.get_custom_modulus().cast_into();
+        let gadget: Scalar = (ciphertext_modulus.get_custom_modulus()
+            >> (decomp_base_log.0 * decomp_level.0))
+            .cast_into();
+        encoded
+            .0
+            .wrapping_mul_custom_mod(gadget, modulus)
+            .wrapping_neg_custom_mod(modulus)
+    }
+}
+
 /// Convenience function to share the core logic of the seeded GGSW encryption between all
 /// functions needing it.
 ///
@@ -862,7 +904,18 @@ where
     let plaintext_ref = decrypted_plaintext_list.get(0);
 
     let ciphertext_modulus = ggsw_ciphertext.ciphertext_modulus();
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
+
+    if !ciphertext_modulus.is_compatible_with_native_modulus() {
+        // The last level was scaled by floor(q / B^l), see the encryption, round to its closest
+        // multiple
+        let gadget =
+            ciphertext_modulus.get_custom_modulus() >> (decomp_base_log.0 * decomp_level.0);
+        let plaintext: u128 = (*plaintext_ref.0).cast_into();
+        let decoded =
+            ((plaintext + gadget / 2) / gadget) % (1 << (decomp_base_log.0 * decomp_level.0));
+
+        return Plaintext(Scalar::cast_from(decoded));
+    }
 
     // Glwe decryption maps to a smaller torus potentially, map back to the native torus
     let rounded = decomposer.closest_representable(
@@ -3,7 +3,8 @@
 
 use crate::core_crypto::algorithms::polynomial_algorithms::*;
 use crate::core_crypto::algorithms::slice_algorithms::{
-    slice_wrapping_scalar_div_assign, slice_wrapping_scalar_mul_assign,
+    slice_wrapping_add_assign_custom_mod, slice_wrapping_scalar_div_assign,
+    slice_wrapping_scalar_mul_assign,
 };
 use crate::core_crypto::commons::generators::EncryptionRandomGenerator;
 use crate::core_crypto::commons::math::random::{ActivatedRandomGenerator, Distribution, Uniform};
@@ -44,8 +45,6 @@ pub fn fill_glwe_mask_and_body_for_encryption_assign<
 
     let ciphertext_modulus = output_body.ciphertext_modulus();
 
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
     generator
         .fill_slice_with_random_uniform_mask_custom_mod(output_mask.as_mut(), ciphertext_modulus);
     generator.unsigned_integer_slice_wrapping_add_random_noise_from_distribution_custom_mod_assign(
@@ -54,6 +53,18 @@ pub fn fill_glwe_mask_and_body_for_encryption_assign<
         ciphertext_modulus,
     );
 
+    if !ciphertext_modulus.is_compatible_with_native_modulus() {
+        let ciphertext_modulus_as_scalar: Scalar =
+            ciphertext_modulus.get_custom_modulus().cast_into();
+        polynomial_wrapping_add_multisum_assign_custom_mod(
+            &mut output_body.as_mut_polynomial(),
+            &output_mask.as_polynomial_list(),
+            &glwe_secret_key.as_polynomial_list(),
+            ciphertext_modulus_as_scalar,
+        );
+        return;
+    }
+
     if !ciphertext_modulus.is_native_modulus() {
         let torus_scaling = ciphertext_modulus.get_power_of_two_scaling_to_native_torus();
         slice_wrapping_scalar_mul_assign(output_mask.as_mut(), torus_scaling);
@@ -203,6 +214,12 @@ pub fn encrypt_seeded_glwe_ciphertext_assign_with_existing_generator<
     OutputCont: ContainerMut<Element = Scalar>,
     Gen: ByteRandomGenerator,
 {
+    assert!(
+        output
+            .ciphertext_modulus()
+            .is_compatible_with_native_modulus(),
+        "This operation currently only supports power of 2 moduli"
+    );
     assert!(
         output.glwe_size().to_glwe_dimension() == glwe_secret_key.glwe_dimension(),
         "Mismatch between GlweDimension of output ciphertext and input secret key. \
@@ -273,8 +290,6 @@ pub fn fill_glwe_mask_and_body_for_encryption<
 
     let ciphertext_modulus = output_body.ciphertext_modulus();
 
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
     generator
         .fill_slice_with_random_uniform_mask_custom_mod(output_mask.as_mut(), ciphertext_modulus);
     generator.fill_slice_with_random_noise_from_distribution_custom_mod(
@@ -283,6 +298,23 @@ pub fn fill_glwe_mask_and_body_for_encryption<
         ciphertext_modulus,
     );
 
+    if !ciphertext_modulus.is_compatible_with_native_modulus() {
+        let ciphertext_modulus_as_scalar: Scalar =
+            ciphertext_modulus.get_custom_modulus().cast_into();
+        slice_wrapping_add_assign_custom_mod(
+            output_body.as_mut(),
+            encoded.as_ref(),
+            ciphertext_modulus_as_scalar,
+        );
+        polynomial_wrapping_add_multisum_assign_custom_mod(
+            &mut output_body.as_mut_polynomial(),
+            &output_mask.as_polynomial_list(),
+            &glwe_secret_key.as_polynomial_list(),
+            ciphertext_modulus_as_scalar,
+        );
+        return;
+    }
+
     polynomial_wrapping_add_assign(
         &mut output_body.as_mut_polynomial(),
         &encoded.as_polynomial(),
@@ -609,12 +641,21 @@ pub fn decrypt_glwe_ciphertext<Scalar, KeyCont, InputCont, OutputCont>(
 
     let ciphertext_modulus = input_glwe_ciphertext.ciphertext_modulus();
 
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
     let (mask, body) = input_glwe_ciphertext.get_mask_and_body();
     output_plaintext_list
         .as_mut()
         .copy_from_slice(body.as_ref());
+
+    if !ciphertext_modulus.is_compatible_with_native_modulus() {
+        polynomial_wrapping_sub_multisum_assign_custom_mod(
+            &mut output_plaintext_list.as_mut_polynomial(),
+            &mask.as_polynomial_list(),
+            &glwe_secret_key.as_polynomial_list(),
+            ciphertext_modulus.get_custom_modulus().cast_into(),
+        );
+        return;
+    }
+
     polynomial_wrapping_sub_multisum_assign(
         &mut output_plaintext_list.as_mut_polynomial(),
         &mask.as_polynomial_list(),
@@ -759,9 +800,7 @@ pub fn trivially_encrypt_glwe_ciphertext<Scalar, InputCont, OutputCont>(
 
     let ciphertext_modulus = body.ciphertext_modulus();
 
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
-    if !ciphertext_modulus.is_native_modulus() {
+    if ciphertext_modulus.is_non_native_power_of_two() {
         slice_wrapping_scalar_mul_assign(
             body.as_mut(),
             ciphertext_modulus.get_power_of_two_scaling_to_native_torus(),
@@ -842,8 +881,6 @@ where
     Scalar: UnsignedTorus,
     InputCont: Container<Element = Scalar>,
 {
-    assert!(ciphertext_modulus.is_compatible_with_native_modulus());
-
     let polynomial_size = PolynomialSize(encoded.plaintext_count().0);
 
     let mut new_ct =
@@ -852,7 +889,7 @@ where
     let mut body = new_ct.get_mut_body();
     body.as_mut().copy_from_slice(encoded.as_ref());
 
-    if !ciphertext_modulus.is_native_modulus() {
+    if ciphertext_modulus.is_non_native_power_of_two() {
         slice_wrapping_scalar_mul_assign(
             body.as_mut(),
             ciphertext_modulus.get_power_of_two_scaling_to_native_torus(),
@@ -885,6 +922,12 @@ pub fn encrypt_seeded_glwe_ciphertext_with_existing_generator<
     InputCont: Container<Element = Scalar>,
     Gen: ByteRandomGenerator,
 {
+    assert!(
+        output_glwe_ciphertext
+            .ciphertext_modulus()
+            .is_compatible_with_native_modulus(),
+        "This operation currently only supports power of 2 moduli"
+    );
     assert!(
         output_glwe_ciphertext.polynomial_size().0 == input_plaintext_list.plaintext_count().0,
         "Mismatch between PolynomialSize of output ciphertext PlaintextCount of input. \
@@ -1069,6 +1112,12 @@ pub fn encrypt_seeded_glwe_ciphertext_list_with_existing_generator<
     InputCont: Container<Element = Scalar>,
     Gen: ByteRandomGenerator,
 {
+    assert!(
+        output
+            .ciphertext_modulus()
+            .is_compatible_with_native_modulus(),
+        "This operation currently only supports power of 2 moduli"
+    );
     assert!(
         output.glwe_size().to_glwe_dimension() == glwe_secret_key.glwe_dimension(),
         "Mismatched GlweDimension between input GlweSecretKey {:?} and output \
@@ -130,6 +130,7 @@ pub fn extract_lwe_sample_from_glwe_ciphertext<Scalar, InputCont, OutputCont>(
     // We compute the number of elements which must be
     // turned into their opposite
     let opposite_count = input_glwe.polynomial_size().0 - nth.0 - 1;
+    let ciphertext_modulus = input_glwe.ciphertext_modulus();
 
     // We loop through the polynomials
     for lwe_mask_poly in lwe_mask
@@ -139,7 +140,14 @@ pub fn extract_lwe_sample_from_glwe_ciphertext<Scalar, InputCont, OutputCont>(
         // We reverse the polynomial
         lwe_mask_poly.reverse();
         // We compute the opposite of the proper coefficients
-        slice_wrapping_opposite_assign(&mut lwe_mask_poly[0..opposite_count]);
+        if ciphertext_modulus.is_compatible_with_native_modulus() {
+            slice_wrapping_opposite_assign(&mut lwe_mask_poly[0..opposite_count]);
+        } else {
+            slice_wrapping_opposite_assign_custom_mod(
+                &mut lwe_mask_poly[0..opposite_count],
+                ciphertext_modulus.get_custom_modulus().cast_into(),
+            );
+        }
         // We rotate the polynomial properly
         lwe_mask_poly.rotate_left(opposite_count);
     }
@@ -389,6 +397,7 @@ pub fn par_extract_lwe_sample_from_glwe_ciphertext_with_thread_count<
     );
 
     let polynomial_size = input_glwe.polynomial_size();
+    let ciphertext_modulus = input_glwe.ciphertext_modulus();
     let (glwe_mask, glwe_body) = input_glwe.get_mask_and_body();
 
     let thread_count = thread_count.0.min(rayon::current_num_threads());
@@ -428,7 +437,14 @@ pub fn par_extract_lwe_sample_from_glwe_ciphertext_with_thread_count<
                         // We reverse the polynomial
                         lwe_mask_poly.reverse();
                         // We compute the opposite of the proper coefficients
-                        slice_wrapping_opposite_assign(&mut lwe_mask_poly[0..opposite_count]);
+                        if ciphertext_modulus.is_compatible_with_native_modulus() {
+                            slice_wrapping_opposite_assign(&mut lwe_mask_poly[0..opposite_count]);
+                        } else {
+                            slice_wrapping_opposite_assign_custom_mod(
+                                &mut lwe_mask_poly[0..opposite_count],
+                                ciphertext_modulus.get_custom_modulus().cast_into(),
+                            );
+                        }
                         // We rotate the polynomial properly
                         lwe_mask_poly.rotate_left(opposite_count);
                     }
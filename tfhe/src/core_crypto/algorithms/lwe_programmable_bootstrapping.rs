@@ -16,6 +16,10 @@ use crate::core_crypto::fft_impl::fft64::crypto::ggsw::{
 };
 use crate::core_crypto::fft_impl::fft64::crypto::wop_pbs::blind_rotate_assign_scratch;
 use crate::core_crypto::fft_impl::fft64::math::fft::{Fft, FftView};
+#[cfg(feature = "experimental-ntt")]
+use crate::core_crypto::ntt_impl::ntt64::crypto::bootstrap::bootstrap_scratch as bootstrap_scratch_ntt64;
+#[cfg(feature = "experimental-ntt")]
+use crate::core_crypto::ntt_impl::ntt64::math::ntt::{Ntt64, Ntt64View};
 use concrete_fft::c64;
 use dyn_stack::{PodStack, SizeOverflow, StackReq};
 
@@ -1430,3 +1434,232 @@ pub fn programmable_bootstrap_f128_lwe_ciphertext_mem_optimized_requirement<Scal
 ) -> Result<StackReq, SizeOverflow> {
     bootstrap_scratch_f128::<Scalar>(glwe_size, polynomial_size, fft)
 }
+
+/// Perform a programmable bootstrap given an input [`LWE ciphertext`](`LweCiphertext`), a
+/// look-up table passed as a [`GLWE ciphertext`](`GlweCiphertext`) and an [`LWE bootstrap
+/// key`](`LweBootstrapKey`) in the NTT domain see [`NTT LWE bootstrap
+/// key`](`NttLweBootstrapKey`). The result is written in the provided output [`LWE
+/// ciphertext`](`LweCiphertext`).
+///
+/// The ciphertexts and the key must share a prime ciphertext modulus supported by [`Ntt64`], such
+/// as $2^{64} - 2^{32} + 1$.
+///
+/// If you want to manage the computation memory manually you can use
+/// [`programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized`].
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::commons::numeric::UnsignedInteger;
+/// use tfhe::core_crypto::prelude::*;
+///
+/// // DISCLAIMER: these toy example parameters are not guaranteed to be secure or yield correct
+/// // computations
+/// // Define the parameters for a 3 bits message able to hold the doubled 2 bits message
+/// let small_lwe_dimension = LweDimension(742);
+/// let glwe_dimension = GlweDimension(1);
+/// let polynomial_size = PolynomialSize(2048);
+/// let lwe_noise_distribution =
+///     Gaussian::from_dispersion_parameter(StandardDev(0.000007069849454709433), 0.0);
+/// let glwe_noise_distribution =
+///     Gaussian::from_dispersion_parameter(StandardDev(0.00000000000000029403601535432533), 0.0);
+/// let pbs_base_log = DecompositionBaseLog(23);
+/// let pbs_level = DecompositionLevelCount(1);
+/// // The Solinas prime 2^64 - 2^32 + 1
+/// let ciphertext_modulus = CiphertextModulus::try_new((1 << 64) - (1 << 32) + 1).unwrap();
+/// let modulus = ciphertext_modulus.get_custom_modulus() as u64;
+///
+/// // Request the best seeder possible, starting with hardware entropy sources and falling back to
+/// // /dev/random on Unix systems if enabled via cargo features
+/// let mut boxed_seeder = new_seeder();
+/// // Get a mutable reference to the seeder as a trait object from the Box returned by new_seeder
+/// let seeder = boxed_seeder.as_mut();
+///
+/// // Create a generator which uses a CSPRNG to generate secret keys
+/// let mut secret_generator =
+///     SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+///
+/// // Create a generator which uses two CSPRNGs to generate public masks and secret encryption
+/// // noise
+/// let mut encryption_generator =
+///     EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+///
+/// // Generate an LweSecretKey with binary coefficients
+/// let small_lwe_sk =
+///     LweSecretKey::generate_new_binary(small_lwe_dimension, &mut secret_generator);
+///
+/// // Generate a GlweSecretKey with binary coefficients
+/// let glwe_sk =
+///     GlweSecretKey::generate_new_binary(glwe_dimension, polynomial_size, &mut secret_generator);
+///
+/// // Create a copy of the GlweSecretKey re-interpreted as an LweSecretKey
+/// let big_lwe_sk = glwe_sk.clone().into_lwe_secret_key();
+///
+/// let std_bootstrapping_key = par_allocate_and_generate_new_lwe_bootstrap_key(
+///     &small_lwe_sk,
+///     &glwe_sk,
+///     pbs_base_log,
+///     pbs_level,
+///     glwe_noise_distribution,
+///     ciphertext_modulus,
+///     &mut encryption_generator,
+/// );
+///
+/// // Create the empty bootstrapping key in the NTT domain
+/// let mut ntt_bsk = NttLweBootstrapKey::new(
+///     std_bootstrapping_key.input_lwe_dimension(),
+///     std_bootstrapping_key.glwe_size(),
+///     std_bootstrapping_key.polynomial_size(),
+///     std_bootstrapping_key.decomposition_base_log(),
+///     std_bootstrapping_key.decomposition_level_count(),
+///     ciphertext_modulus,
+/// );
+///
+/// // Use the conversion function to convert the standard bootstrapping key to the NTT domain
+/// convert_standard_lwe_bootstrap_key_to_ntt64(&std_bootstrapping_key, &mut ntt_bsk);
+/// // We don't need the standard bootstrapping key anymore
+/// drop(std_bootstrapping_key);
+///
+/// // Our 3 bits message space
+/// let message_modulus = 1u64 << 3;
+///
+/// // Our input message
+/// let input_message = 3u64;
+///
+/// // Delta used to encode 3 bits of message + a bit of padding modulo the prime
+/// let delta = (modulus / 2) / message_modulus;
+///
+/// // Apply our encoding
+/// let plaintext = Plaintext(input_message * delta);
+///
+/// // Allocate a new LweCiphertext and encrypt our plaintext
+/// let lwe_ciphertext_in: LweCiphertextOwned<u64> = allocate_and_encrypt_new_lwe_ciphertext(
+///     &small_lwe_sk,
+///     plaintext,
+///     lwe_noise_distribution,
+///     ciphertext_modulus,
+///     &mut encryption_generator,
+/// );
+///
+/// // Build the accumulator of the multiplication by 2, as for the other bootstrapping keys but
+/// // computing the opposite of the first half box modulo the prime
+/// let box_size = polynomial_size.0 / message_modulus as usize;
+/// let mut accumulator_u64 = vec![0_u64; polynomial_size.0];
+/// for (i, lut_box) in accumulator_u64.chunks_exact_mut(box_size).enumerate() {
+///     lut_box.fill(2 * i as u64 * delta);
+/// }
+/// let half_box_size = box_size / 2;
+/// for a_i in accumulator_u64[0..half_box_size].iter_mut() {
+///     *a_i = (*a_i).wrapping_neg_custom_mod(modulus);
+/// }
+/// accumulator_u64.rotate_left(half_box_size);
+///
+/// let accumulator = allocate_and_trivially_encrypt_new_glwe_ciphertext(
+///     glwe_dimension.to_glwe_size(),
+///     &PlaintextList::from_container(accumulator_u64),
+///     ciphertext_modulus,
+/// );
+///
+/// // Allocate the LweCiphertext to store the result of the PBS
+/// let mut pbs_multiplication_ct = LweCiphertext::new(
+///     0u64,
+///     big_lwe_sk.lwe_dimension().to_lwe_size(),
+///     ciphertext_modulus,
+/// );
+/// programmable_bootstrap_ntt64_lwe_ciphertext(
+///     &lwe_ciphertext_in,
+///     &mut pbs_multiplication_ct,
+///     &accumulator,
+///     &ntt_bsk,
+/// );
+///
+/// // Decrypt the PBS multiplication result
+/// let pbs_multiplication_plaintext: Plaintext<u64> =
+///     decrypt_lwe_ciphertext(&big_lwe_sk, &pbs_multiplication_ct);
+///
+/// // Round and remove our encoding
+/// let pbs_multiplication_result =
+///     ((pbs_multiplication_plaintext.0 + delta / 2) / delta) % message_modulus;
+///
+/// assert_eq!(6, pbs_multiplication_result);
+/// ```
+#[cfg(feature = "experimental-ntt")]
+pub fn programmable_bootstrap_ntt64_lwe_ciphertext<InputCont, OutputCont, AccCont, KeyCont>(
+    input: &LweCiphertext<InputCont>,
+    output: &mut LweCiphertext<OutputCont>,
+    accumulator: &GlweCiphertext<AccCont>,
+    ntt_bsk: &NttLweBootstrapKey<KeyCont>,
+) where
+    InputCont: Container<Element = u64>,
+    OutputCont: ContainerMut<Element = u64>,
+    AccCont: Container<Element = u64>,
+    KeyCont: Container<Element = u64>,
+{
+    assert_eq!(input.ciphertext_modulus(), output.ciphertext_modulus());
+    assert_eq!(
+        output.ciphertext_modulus(),
+        accumulator.ciphertext_modulus()
+    );
+
+    let mut buffers = ComputationBuffers::new();
+
+    let ntt = Ntt64::new(ntt_bsk.polynomial_size(), ntt_bsk.ciphertext_modulus());
+    let ntt = ntt.as_view();
+
+    buffers.resize(
+        programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized_requirement(
+            ntt_bsk.glwe_size(),
+            ntt_bsk.polynomial_size(),
+            ntt_bsk.decomposition_level_count(),
+        )
+        .unwrap()
+        .unaligned_bytes_required(),
+    );
+
+    let stack = buffers.stack();
+
+    programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized(
+        input,
+        output,
+        accumulator,
+        ntt_bsk,
+        ntt,
+        stack,
+    );
+}
+
+/// Memory optimized version of [`programmable_bootstrap_ntt64_lwe_ciphertext`], the caller must
+/// provide a properly configured [`Ntt64View`] object and a `PodStack` used as a memory buffer
+/// having a capacity at least as large as the result of
+/// [`programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized_requirement`].
+#[cfg(feature = "experimental-ntt")]
+pub fn programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized<
+    InputCont,
+    OutputCont,
+    AccCont,
+    KeyCont,
+>(
+    input: &LweCiphertext<InputCont>,
+    output: &mut LweCiphertext<OutputCont>,
+    accumulator: &GlweCiphertext<AccCont>,
+    ntt_bsk: &NttLweBootstrapKey<KeyCont>,
+    ntt: Ntt64View<'_>,
+    stack: PodStack<'_>,
+) where
+    InputCont: Container<Element = u64>,
+    OutputCont: ContainerMut<Element = u64>,
+    AccCont: Container<Element = u64>,
+    KeyCont: Container<Element = u64>,
+{
+    ntt_bsk.bootstrap(output, input, accumulator, ntt, stack);
+}
+
+/// Return the required memory for [`programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized`].
+#[cfg(feature = "experimental-ntt")]
+pub fn programmable_bootstrap_ntt64_lwe_ciphertext_mem_optimized_requirement(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_level_count: DecompositionLevelCount,
+) -> Result<StackReq, SizeOverflow> {
+    bootstrap_scratch_ntt64(glwe_size, polynomial_size, decomposition_level_count)
+}
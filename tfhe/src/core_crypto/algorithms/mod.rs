@@ -30,6 +30,7 @@ pub mod lwe_wopbs;
 #[cfg(feature = "zk-pok-experimental")]
 pub mod lwe_zero_knowledge_verification;
 pub mod misc;
+pub mod noise_measurement;
 pub mod polynomial_algorithms;
 pub mod seeded_ggsw_ciphertext_decompression;
 pub mod seeded_ggsw_ciphertext_list_decompression;
@@ -77,6 +78,7 @@ pub use lwe_secret_key_generation::*;
 pub use lwe_wopbs::*;
 #[cfg(feature = "zk-pok-experimental")]
 pub use lwe_zero_knowledge_verification::*;
+pub use noise_measurement::*;
 pub use seeded_ggsw_ciphertext_decompression::*;
 pub use seeded_ggsw_ciphertext_list_decompression::*;
 pub use seeded_glwe_ciphertext_decompression::*;
@@ -0,0 +1,196 @@
+//! Module containing primitives pertaining to the measurement of the noise of ciphertexts.
+//!
+//! These functions decrypt a ciphertext with its secret key and compare the result to the
+//! plaintext the caller expects, they are meant for tests and noise analysis, not for decoding.
+
+use crate::core_crypto::algorithms::*;
+use crate::core_crypto::commons::parameters::*;
+use crate::core_crypto::commons::traits::*;
+use crate::core_crypto::entities::*;
+
+/// Return the difference `value - expected` modulo the ciphertext modulus $q$, as the
+/// representative in $]-q/2, q/2]$.
+///
+/// Both inputs are expressed in units of $q$, as returned by the decryption algorithms.
+pub fn centered_modular_difference<Scalar: UnsignedInteger>(
+    value: Scalar,
+    expected: Scalar,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+) -> Scalar::Signed {
+    if ciphertext_modulus.is_native_modulus() {
+        // The two's complement representation is already centered
+        return value.wrapping_sub(expected).into_signed();
+    }
+
+    let modulus: Scalar = ciphertext_modulus.get_custom_modulus().cast_into();
+    let difference = (value % modulus).wrapping_sub_custom_mod(expected % modulus, modulus);
+
+    if difference <= modulus / Scalar::TWO {
+        difference.into_signed()
+    } else {
+        -(modulus - difference).into_signed()
+    }
+}
+
+/// Decrypt an [`LWE ciphertext`](`LweCiphertext`) and return its noise with respect to an expected
+/// [`Plaintext`], as a signed integer in units of the ciphertext modulus.
+///
+/// The noise is the centered difference between the decrypted plaintext and the expected one, see
+/// [`centered_modular_difference`]. For a non native power of two modulus the plaintext is
+/// expressed modulo that power of two, as with [`decrypt_lwe_ciphertext`].
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::prelude::*;
+///
+/// // DISCLAIMER: these toy example parameters are not guaranteed to be secure or yield correct
+/// // computations
+/// // Define parameters for LweCiphertext creation
+/// let lwe_dimension = LweDimension(742);
+/// let lwe_noise_distribution =
+///     Gaussian::from_dispersion_parameter(StandardDev(0.000007069849454709433), 0.0);
+/// let ciphertext_modulus = CiphertextModulus::new_native();
+///
+/// // Create the PRNG
+/// let mut seeder = new_seeder();
+/// let seeder = seeder.as_mut();
+/// let mut encryption_generator =
+///     EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+/// let mut secret_generator =
+///     SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+///
+/// // Create the LweSecretKey
+/// let lwe_secret_key =
+///     allocate_and_generate_new_binary_lwe_secret_key(lwe_dimension, &mut secret_generator);
+///
+/// // Create the plaintext
+/// let msg = 3u64;
+/// let plaintext = Plaintext(msg << 60);
+///
+/// let mut lwe = LweCiphertext::new(0u64, lwe_dimension.to_lwe_size(), ciphertext_modulus);
+///
+/// encrypt_lwe_ciphertext(
+///     &lwe_secret_key,
+///     &mut lwe,
+///     plaintext,
+///     lwe_noise_distribution,
+///     &mut encryption_generator,
+/// );
+///
+/// let noise: i64 = lwe_ciphertext_noise(&lwe_secret_key, &lwe, plaintext);
+///
+/// // The noise is far below the 2^59 bound which would make the decryption incorrect
+/// assert!(noise.unsigned_abs() < 1 << 59);
+///
+/// // Shifting the body shifts the noise by the same amount
+/// let body = *lwe.get_body().data;
+/// *lwe.get_mut_body().data = body.wrapping_sub(1000);
+/// assert_eq!(
+///     lwe_ciphertext_noise(&lwe_secret_key, &lwe, plaintext),
+///     noise - 1000
+/// );
+/// ```
+pub fn lwe_ciphertext_noise<Scalar, KeyCont, InputCont>(
+    lwe_secret_key: &LweSecretKey<KeyCont>,
+    lwe_ciphertext: &LweCiphertext<InputCont>,
+    expected_plaintext: Plaintext<Scalar>,
+) -> Scalar::Signed
+where
+    Scalar: UnsignedInteger,
+    KeyCont: Container<Element = Scalar>,
+    InputCont: Container<Element = Scalar>,
+{
+    let decrypted = decrypt_lwe_ciphertext(lwe_secret_key, lwe_ciphertext);
+
+    centered_modular_difference(
+        decrypted.0,
+        expected_plaintext.0,
+        lwe_ciphertext.ciphertext_modulus(),
+    )
+}
+
+/// Decrypt a [`GLWE ciphertext`](`GlweCiphertext`) and return the noise of each of its
+/// coefficients with respect to an expected [`PlaintextList`], see [`lwe_ciphertext_noise`].
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::prelude::*;
+///
+/// // DISCLAIMER: these toy example parameters are not guaranteed to be secure or yield correct
+/// // computations
+/// // Define parameters for GlweCiphertext creation
+/// let glwe_size = GlweSize(2);
+/// let polynomial_size = PolynomialSize(1024);
+/// let glwe_noise_distribution =
+///     Gaussian::from_dispersion_parameter(StandardDev(0.00000000000000029403601535432533), 0.0);
+/// let ciphertext_modulus = CiphertextModulus::new_native();
+///
+/// // Create the PRNG
+/// let mut seeder = new_seeder();
+/// let seeder = seeder.as_mut();
+/// let mut encryption_generator =
+///     EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+/// let mut secret_generator =
+///     SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+///
+/// // Create the GlweSecretKey
+/// let glwe_secret_key = allocate_and_generate_new_binary_glwe_secret_key(
+///     glwe_size.to_glwe_dimension(),
+///     polynomial_size,
+///     &mut secret_generator,
+/// );
+///
+/// // Create the plaintext
+/// let msg = 3u64;
+/// let plaintext_list = PlaintextList::new(msg << 60, PlaintextCount(polynomial_size.0));
+///
+/// let mut glwe = GlweCiphertext::new(0u64, glwe_size, polynomial_size, ciphertext_modulus);
+///
+/// encrypt_glwe_ciphertext(
+///     &glwe_secret_key,
+///     &mut glwe,
+///     &plaintext_list,
+///     glwe_noise_distribution,
+///     &mut encryption_generator,
+/// );
+///
+/// let noise: Vec<i64> = glwe_ciphertext_noise(&glwe_secret_key, &glwe, &plaintext_list);
+///
+/// assert_eq!(noise.len(), polynomial_size.0);
+/// assert!(noise.iter().all(|e| e.unsigned_abs() < 1 << 59));
+/// ```
+pub fn glwe_ciphertext_noise<Scalar, KeyCont, InputCont, PlaintextCont>(
+    glwe_secret_key: &GlweSecretKey<KeyCont>,
+    glwe_ciphertext: &GlweCiphertext<InputCont>,
+    expected_plaintext_list: &PlaintextList<PlaintextCont>,
+) -> Vec<Scalar::Signed>
+where
+    Scalar: UnsignedTorus,
+    KeyCont: Container<Element = Scalar>,
+    InputCont: Container<Element = Scalar>,
+    PlaintextCont: Container<Element = Scalar>,
+{
+    assert!(
+        expected_plaintext_list.plaintext_count().0 == glwe_ciphertext.polynomial_size().0,
+        "Mismatched expected PlaintextCount {:?} and input PolynomialSize {:?}",
+        expected_plaintext_list.plaintext_count(),
+        glwe_ciphertext.polynomial_size()
+    );
+
+    let ciphertext_modulus = glwe_ciphertext.ciphertext_modulus();
+
+    let mut decrypted = PlaintextList::new(
+        Scalar::ZERO,
+        PlaintextCount(glwe_ciphertext.polynomial_size().0),
+    );
+    decrypt_glwe_ciphertext(glwe_secret_key, glwe_ciphertext, &mut decrypted);
+
+    decrypted
+        .as_ref()
+        .iter()
+        .zip(expected_plaintext_list.as_ref().iter())
+        .map(|(&value, &expected)| centered_modular_difference(value, expected, ciphertext_modulus))
+        .collect()
+}
@@ -2,8 +2,12 @@
 
 use crate::core_crypto::algorithms::slice_algorithms::*;
 use crate::core_crypto::commons::parameters::MonomialDegree;
+#[cfg(feature = "experimental-ntt")]
+use crate::core_crypto::commons::parameters::{CiphertextModulus, PolynomialSize};
 use crate::core_crypto::commons::traits::*;
 use crate::core_crypto::entities::*;
+#[cfg(feature = "experimental-ntt")]
+use crate::core_crypto::ntt_impl::ntt64::math::ntt::Ntt64;
 
 /// Add a polynomial to the output polynomial.
 ///
@@ -280,6 +284,75 @@ pub fn polynomial_wrapping_monic_monomial_mul_assign<Scalar, OutputCont>(
         .for_each(|a| *a = a.wrapping_neg());
 }
 
+/// Divides (mod $(X^{N}+1)$), the output polynomial with a monic monomial of a given degree i.e.
+/// $X^{degree}$, with coefficients modulo a custom modulus.
+///
+/// # Examples
+///
+/// ```rust
+/// use tfhe::core_crypto::algorithms::polynomial_algorithms::*;
+/// use tfhe::core_crypto::commons::parameters::*;
+/// use tfhe::core_crypto::entities::*;
+/// let mut poly = Polynomial::from_container(vec![1u8, 2, 3]);
+/// polynomial_wrapping_monic_monomial_div_assign_custom_mod(&mut poly, MonomialDegree(2), 251);
+/// assert_eq!(poly.as_ref(), &[3, 250, 249]);
+/// ```
+pub fn polynomial_wrapping_monic_monomial_div_assign_custom_mod<Scalar, OutputCont>(
+    output: &mut Polynomial<OutputCont>,
+    monomial_degree: MonomialDegree,
+    custom_modulus: Scalar,
+) where
+    Scalar: UnsignedInteger,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    let full_cycles_count = monomial_degree.0 / output.as_ref().container_len();
+    if full_cycles_count % 2 != 0 {
+        slice_wrapping_opposite_assign_custom_mod(output.as_mut(), custom_modulus);
+    }
+    let remaining_degree = monomial_degree.0 % output.as_ref().container_len();
+    output.as_mut().rotate_left(remaining_degree);
+    output
+        .as_mut()
+        .iter_mut()
+        .rev()
+        .take(remaining_degree)
+        .for_each(|a| *a = a.wrapping_neg_custom_mod(custom_modulus));
+}
+
+/// Multiply (mod $(X^{N}+1)$), the output polynomial with a monic monomial of a given degree i.e.
+/// $X^{degree}$, with coefficients modulo a custom modulus.
+///
+/// # Examples
+///
+/// ```rust
+/// use tfhe::core_crypto::algorithms::polynomial_algorithms::*;
+/// use tfhe::core_crypto::commons::parameters::*;
+/// use tfhe::core_crypto::entities::*;
+/// let mut poly = Polynomial::from_container(vec![1u8, 2, 3]);
+/// polynomial_wrapping_monic_monomial_mul_assign_custom_mod(&mut poly, MonomialDegree(2), 251);
+/// assert_eq!(poly.as_ref(), &[249, 248, 1]);
+/// ```
+pub fn polynomial_wrapping_monic_monomial_mul_assign_custom_mod<Scalar, OutputCont>(
+    output: &mut Polynomial<OutputCont>,
+    monomial_degree: MonomialDegree,
+    custom_modulus: Scalar,
+) where
+    Scalar: UnsignedInteger,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    let full_cycles_count = monomial_degree.0 / output.as_ref().container_len();
+    if full_cycles_count % 2 != 0 {
+        slice_wrapping_opposite_assign_custom_mod(output.as_mut(), custom_modulus);
+    }
+    let remaining_degree = monomial_degree.0 % output.as_ref().container_len();
+    output.as_mut().rotate_right(remaining_degree);
+    output
+        .as_mut()
+        .iter_mut()
+        .take(remaining_degree)
+        .for_each(|a| *a = a.wrapping_neg_custom_mod(custom_modulus));
+}
+
 /// performs the operation: dst = -src, with wrapping arithmetic
 fn copy_with_neg<Scalar: UnsignedInteger>(dst: &mut [Scalar], src: &[Scalar]) {
     for (dst, src) in dst.iter_mut().zip(src) {
@@ -623,6 +696,257 @@ pub fn polynomial_wrapping_sub_mul_assign<Scalar, OutputCont, InputCont1, InputC
     }
 }
 
+/// Add the results of the products of two lists of polynomials, reduced modulo $(X^{N}+1)$, to the
+/// output polynomial, with coefficients modulo a custom modulus.
+///
+/// See [`polynomial_wrapping_add_mul_assign_custom_mod`] for the algorithm used.
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::algorithms::polynomial_algorithms::*;
+/// use tfhe::core_crypto::commons::parameters::*;
+/// use tfhe::core_crypto::entities::*;
+/// let poly_list = PolynomialList::from_container(vec![100_u8, 20, 3, 4, 5, 6], PolynomialSize(3));
+/// let bin_poly_list = PolynomialList::from_container(vec![0, 1, 1, 1, 0, 0], PolynomialSize(3));
+/// let mut output = Polynomial::new(250, PolynomialSize(3));
+/// polynomial_wrapping_add_multisum_assign_custom_mod(&mut output, &poly_list, &bin_poly_list, 251);
+/// assert_eq!(output.as_ref(), &[231, 101, 125]);
+/// ```
+pub fn polynomial_wrapping_add_multisum_assign_custom_mod<
+    Scalar,
+    OutputCont,
+    InputCont1,
+    InputCont2,
+>(
+    output: &mut Polynomial<OutputCont>,
+    poly_list_1: &PolynomialList<InputCont1>,
+    poly_list_2: &PolynomialList<InputCont2>,
+    custom_modulus: Scalar,
+) where
+    Scalar: UnsignedInteger,
+    OutputCont: ContainerMut<Element = Scalar>,
+    InputCont1: Container<Element = Scalar>,
+    InputCont2: Container<Element = Scalar>,
+{
+    for (poly_1, poly_2) in poly_list_1.iter().zip(poly_list_2.iter()) {
+        polynomial_wrapping_add_mul_assign_custom_mod(output, &poly_1, &poly_2, custom_modulus);
+    }
+}
+
+/// Subtract the results of the products of two lists of polynomials, reduced modulo $(X^{N}+1)$,
+/// from the output polynomial, with coefficients modulo a custom modulus.
+///
+/// See [`polynomial_wrapping_add_mul_assign_custom_mod`] for the algorithm used.
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::algorithms::polynomial_algorithms::*;
+/// use tfhe::core_crypto::commons::parameters::*;
+/// use tfhe::core_crypto::entities::*;
+/// let poly_list = PolynomialList::from_container(vec![100_u8, 20, 3, 4, 5, 6], PolynomialSize(3));
+/// let bin_poly_list = PolynomialList::from_container(vec![0, 1, 1, 1, 0, 0], PolynomialSize(3));
+/// let mut output = Polynomial::new(250, PolynomialSize(3));
+/// polynomial_wrapping_sub_multisum_assign_custom_mod(&mut output, &poly_list, &bin_poly_list, 251);
+/// assert_eq!(output.as_ref(), &[18, 148, 124]);
+/// ```
+pub fn polynomial_wrapping_sub_multisum_assign_custom_mod<
+    Scalar,
+    OutputCont,
+    InputCont1,
+    InputCont2,
+>(
+    output: &mut Polynomial<OutputCont>,
+    poly_list_1: &PolynomialList<InputCont1>,
+    poly_list_2: &PolynomialList<InputCont2>,
+    custom_modulus: Scalar,
+) where
+    Scalar: UnsignedInteger,
+    OutputCont: ContainerMut<Element = Scalar>,
+    InputCont1: Container<Element = Scalar>,
+    InputCont2: Container<Element = Scalar>,
+{
+    for (poly_1, poly_2) in poly_list_1.iter().zip(poly_list_2.iter()) {
+        polynomial_wrapping_sub_mul_assign_custom_mod(output, &poly_1, &poly_2, custom_modulus);
+    }
+}
+
+fn polynomial_wrapping_mul_custom_mod_schoolbook<Scalar: UnsignedInteger>(
+    output: &mut [Scalar],
+    lhs: &[Scalar],
+    rhs: &[Scalar],
+    custom_modulus: Scalar,
+) {
+    let polynomial_size = output.len();
+    output.fill(Scalar::ZERO);
+
+    for (lhs_degree, &lhs_coeff) in lhs.iter().enumerate() {
+        for (rhs_degree, &rhs_coeff) in rhs.iter().enumerate() {
+            let target_degree = lhs_degree + rhs_degree;
+            let product = lhs_coeff.wrapping_mul_custom_mod(rhs_coeff, custom_modulus);
+            let output_coefficient = &mut output[target_degree % polynomial_size];
+
+            *output_coefficient = if target_degree < polynomial_size {
+                (*output_coefficient).wrapping_add_custom_mod(product, custom_modulus)
+            } else {
+                (*output_coefficient).wrapping_sub_custom_mod(product, custom_modulus)
+            };
+        }
+    }
+}
+
+/// Fill `output` with the product of `lhs` and `rhs` modulo $(X^{N}+1)$ and `custom_modulus`, with
+/// the NTT for 64 bits primes supported by [`Ntt64`] and the schoolbook algorithm otherwise.
+#[cfg(feature = "experimental-ntt")]
+fn polynomial_wrapping_mul_custom_mod<Scalar: UnsignedInteger>(
+    output: &mut [Scalar],
+    lhs: &[Scalar],
+    rhs: &[Scalar],
+    custom_modulus: Scalar,
+) {
+    let polynomial_size = PolynomialSize(output.len());
+
+    if Scalar::BITS == 64 {
+        let modulus: u128 = custom_modulus.cast_into();
+        let ciphertext_modulus = CiphertextModulus::<u64>::try_new(modulus).unwrap();
+
+        if Ntt64::is_supported(polynomial_size, ciphertext_modulus) {
+            let ntt = Ntt64::new(polynomial_size, ciphertext_modulus);
+            let ntt = ntt.as_view();
+            let to_ntt = |poly: &[Scalar]| -> Vec<u64> {
+                let mut values: Vec<u64> = poly
+                    .iter()
+                    .map(|&coeff| {
+                        let coeff: u128 = coeff.wrapping_rem(custom_modulus).cast_into();
+                        coeff as u64
+                    })
+                    .collect();
+                ntt.forward(&mut values);
+                values
+            };
+
+            let (lhs_ntt, rhs_ntt) = (to_ntt(lhs), to_ntt(rhs));
+            let mut product_ntt = vec![0u64; polynomial_size.0];
+            ntt.mul_accumulate(&mut product_ntt, &lhs_ntt, &rhs_ntt);
+            ntt.backward(&mut product_ntt);
+
+            for (out, value) in output.iter_mut().zip(product_ntt) {
+                *out = Scalar::cast_from(value as u128);
+            }
+            return;
+        }
+    }
+
+    polynomial_wrapping_mul_custom_mod_schoolbook(output, lhs, rhs, custom_modulus);
+}
+
+/// Fill `output` with the product of `lhs` and `rhs` modulo $(X^{N}+1)$ and `custom_modulus`, with
+/// the schoolbook algorithm.
+#[cfg(not(feature = "experimental-ntt"))]
+fn polynomial_wrapping_mul_custom_mod<Scalar: UnsignedInteger>(
+    output: &mut [Scalar],
+    lhs: &[Scalar],
+    rhs: &[Scalar],
+    custom_modulus: Scalar,
+) {
+    polynomial_wrapping_mul_custom_mod_schoolbook(output, lhs, rhs, custom_modulus);
+}
+
+/// Add the result of the product between two polynomials, reduced modulo $(X^{N}+1)$, to the
+/// output polynomial, with coefficients modulo a custom modulus.
+///
+/// # Note
+///
+/// With the `experimental-ntt` feature, for 64 bits primes $p$ with $2N | p - 1$, e.g.
+/// $2^{64} - 2^{32} + 1$, the product is computed with an exact negacyclic NTT, otherwise with the
+/// schoolbook algorithm.
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::algorithms::polynomial_algorithms::*;
+/// use tfhe::core_crypto::entities::*;
+/// let poly_1 = Polynomial::from_container(vec![1_u8, 2, 3]);
+/// let poly_2 = Polynomial::from_container(vec![0, 1, 1]);
+/// let mut res = Polynomial::from_container(vec![1, 0, 249]);
+/// polynomial_wrapping_add_mul_assign_custom_mod(&mut res, &poly_1, &poly_2, 251);
+/// assert_eq!(res.as_ref(), &[247, 249, 1]);
+/// ```
+pub fn polynomial_wrapping_add_mul_assign_custom_mod<Scalar, OutputCont, InputCont1, InputCont2>(
+    output: &mut Polynomial<OutputCont>,
+    lhs: &Polynomial<InputCont1>,
+    rhs: &Polynomial<InputCont2>,
+    custom_modulus: Scalar,
+) where
+    Scalar: UnsignedInteger,
+    OutputCont: ContainerMut<Element = Scalar>,
+    InputCont1: Container<Element = Scalar>,
+    InputCont2: Container<Element = Scalar>,
+{
+    assert!(
+        output.polynomial_size() == lhs.polynomial_size(),
+        "Output polynomial size {:?} is not the same as input lhs polynomial {:?}.",
+        output.polynomial_size(),
+        lhs.polynomial_size(),
+    );
+    assert!(
+        output.polynomial_size() == rhs.polynomial_size(),
+        "Output polynomial size {:?} is not the same as input rhs polynomial {:?}.",
+        output.polynomial_size(),
+        rhs.polynomial_size(),
+    );
+
+    let mut tmp = Polynomial::new(Scalar::ZERO, output.polynomial_size());
+    polynomial_wrapping_mul_custom_mod(tmp.as_mut(), lhs.as_ref(), rhs.as_ref(), custom_modulus);
+    slice_wrapping_add_assign_custom_mod(output.as_mut(), tmp.as_ref(), custom_modulus);
+}
+
+/// Subtract the result of the product between two polynomials, reduced modulo $(X^{N}+1)$, from
+/// the output polynomial, with coefficients modulo a custom modulus.
+///
+/// See [`polynomial_wrapping_add_mul_assign_custom_mod`] for the algorithm used.
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::algorithms::polynomial_algorithms::*;
+/// use tfhe::core_crypto::entities::*;
+/// let poly_1 = Polynomial::from_container(vec![1_u8, 2, 3]);
+/// let poly_2 = Polynomial::from_container(vec![0, 1, 1]);
+/// let mut res = Polynomial::from_container(vec![250, 250, 1]);
+/// polynomial_wrapping_sub_mul_assign_custom_mod(&mut res, &poly_1, &poly_2, 251);
+/// assert_eq!(res.as_ref(), &[4, 1, 249]);
+/// ```
+pub fn polynomial_wrapping_sub_mul_assign_custom_mod<Scalar, OutputCont, InputCont1, InputCont2>(
+    output: &mut Polynomial<OutputCont>,
+    lhs: &Polynomial<InputCont1>,
+    rhs: &Polynomial<InputCont2>,
+    custom_modulus: Scalar,
+) where
+    Scalar: UnsignedInteger,
+    OutputCont: ContainerMut<Element = Scalar>,
+    InputCont1: Container<Element = Scalar>,
+    InputCont2: Container<Element = Scalar>,
+{
+    assert!(
+        output.polynomial_size() == lhs.polynomial_size(),
+        "Output polynomial size {:?} is not the same as input lhs polynomial {:?}.",
+        output.polynomial_size(),
+        lhs.polynomial_size(),
+    );
+    assert!(
+        output.polynomial_size() == rhs.polynomial_size(),
+        "Output polynomial size {:?} is not the same as input rhs polynomial {:?}.",
+        output.polynomial_size(),
+        rhs.polynomial_size(),
+    );
+
+    let mut tmp = Polynomial::new(Scalar::ZERO, output.polynomial_size());
+    polynomial_wrapping_mul_custom_mod(tmp.as_mut(), lhs.as_ref(), rhs.as_ref(), custom_modulus);
+    slice_wrapping_sub_assign_custom_mod(output.as_mut(), tmp.as_ref(), custom_modulus);
+}
+
 /// Fill the output polynomial, with the result of the product of two polynomials, reduced modulo
 /// $(X^{N} + 1)$ with the schoolbook algorithm Complexity: $O(N^{2})$
 ///
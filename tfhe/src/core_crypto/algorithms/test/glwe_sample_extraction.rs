@@ -118,4 +118,6 @@ fn glwe_encrypt_sample_extract_decrypt_custom_mod<Scalar: UnsignedTorus + Send +
     }
 }
 
-create_parametrized_test!(glwe_encrypt_sample_extract_decrypt_custom_mod);
+create_parametrized_test_with_non_native_parameters!(
+    glwe_encrypt_sample_extract_decrypt_custom_mod
+);
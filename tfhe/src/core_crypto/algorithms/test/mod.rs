@@ -25,6 +25,7 @@ mod lwe_private_functional_packing_keyswitch;
 pub(crate) mod lwe_programmable_bootstrapping;
 mod modulus_switch_compression;
 mod noise_distribution;
+mod noise_measurement;
 
 pub struct TestResources {
     pub seeder: Box<dyn Seeder>,
@@ -0,0 +1,190 @@
+use super::*;
+
+#[cfg(not(tarpaulin))]
+const NB_TESTS: usize = 10;
+#[cfg(tarpaulin)]
+const NB_TESTS: usize = 1;
+
+fn lwe_encrypt_shift_noise_custom_mod<Scalar: UnsignedTorus>(params: ClassicTestParams<Scalar>) {
+    let lwe_dimension = params.lwe_dimension;
+    let lwe_noise_distribution = params.lwe_noise_distribution;
+    let ciphertext_modulus = params.ciphertext_modulus;
+    let message_modulus_log = params.message_modulus_log;
+    let encoding_with_padding = get_encoding_with_padding(ciphertext_modulus);
+
+    let mut rsc = TestResources::new();
+
+    let msg_modulus = Scalar::ONE.shl(message_modulus_log.0);
+    let mut msg = msg_modulus;
+    let delta: Scalar = encoding_with_padding / msg_modulus;
+    let noise_bound = (delta / Scalar::TWO).into_signed();
+
+    let offsets = [
+        Scalar::ONE,
+        Scalar::cast_from(1000u128),
+        delta / Scalar::cast_from(4u128),
+    ];
+
+    while msg != Scalar::ZERO {
+        msg = msg.wrapping_sub(Scalar::ONE);
+        for _ in 0..NB_TESTS {
+            let lwe_sk = allocate_and_generate_new_binary_lwe_secret_key(
+                lwe_dimension,
+                &mut rsc.secret_random_generator,
+            );
+
+            let plaintext = Plaintext(msg * delta);
+
+            // A trivial encryption has no noise whatever the key
+            let trivial = allocate_and_trivially_encrypt_new_lwe_ciphertext(
+                lwe_dimension.to_lwe_size(),
+                plaintext,
+                ciphertext_modulus,
+            );
+            assert_eq!(
+                lwe_ciphertext_noise(&lwe_sk, &trivial, plaintext),
+                Scalar::Signed::ZERO
+            );
+
+            let mut ct = LweCiphertext::new(
+                Scalar::ZERO,
+                lwe_dimension.to_lwe_size(),
+                ciphertext_modulus,
+            );
+
+            encrypt_lwe_ciphertext(
+                &lwe_sk,
+                &mut ct,
+                plaintext,
+                lwe_noise_distribution,
+                &mut rsc.encryption_random_generator,
+            );
+
+            let noise = lwe_ciphertext_noise(&lwe_sk, &ct, plaintext);
+
+            // The noise is small enough for the decryption to be correct and agrees with it
+            assert!(-noise_bound < noise && noise < noise_bound);
+            let decrypted = decrypt_lwe_ciphertext(&lwe_sk, &ct);
+            let decoded = round_decode(decrypted.0, delta) % msg_modulus;
+            assert_eq!(decoded, msg);
+
+            // Adding or subtracting a plaintext shifts the noise by exactly the same amount
+            for offset in offsets {
+                let mut shifted = ct.clone();
+                lwe_ciphertext_plaintext_add_assign(&mut shifted, Plaintext(offset));
+                assert_eq!(
+                    lwe_ciphertext_noise(&lwe_sk, &shifted, plaintext),
+                    noise + offset.into_signed()
+                );
+
+                let mut shifted = ct.clone();
+                lwe_ciphertext_plaintext_sub_assign(&mut shifted, Plaintext(offset));
+                assert_eq!(
+                    lwe_ciphertext_noise(&lwe_sk, &shifted, plaintext),
+                    noise - offset.into_signed()
+                );
+            }
+        }
+    }
+}
+
+create_parametrized_test_with_non_native_parameters!(lwe_encrypt_shift_noise_custom_mod);
+
+fn glwe_encrypt_noise_custom_mod<Scalar: UnsignedTorus>(params: ClassicTestParams<Scalar>) {
+    let glwe_dimension = params.glwe_dimension;
+    let polynomial_size = params.polynomial_size;
+    let glwe_noise_distribution = params.glwe_noise_distribution;
+    let ciphertext_modulus = params.ciphertext_modulus;
+    let message_modulus_log = params.message_modulus_log;
+    let encoding_with_padding = get_encoding_with_padding(ciphertext_modulus);
+
+    let mut rsc = TestResources::new();
+
+    let msg_modulus = Scalar::ONE.shl(message_modulus_log.0);
+    let mut msg = msg_modulus;
+    let delta: Scalar = encoding_with_padding / msg_modulus;
+
+    let modulus_as_scalar: Scalar = if ciphertext_modulus.is_native_modulus() {
+        Scalar::ZERO
+    } else {
+        ciphertext_modulus.get_custom_modulus().cast_into()
+    };
+
+    while msg != Scalar::ZERO {
+        msg = msg.wrapping_sub(Scalar::ONE);
+        for _ in 0..NB_TESTS {
+            let glwe_sk = allocate_and_generate_new_binary_glwe_secret_key(
+                glwe_dimension,
+                polynomial_size,
+                &mut rsc.secret_random_generator,
+            );
+
+            let equivalent_lwe_sk = glwe_sk.clone().into_lwe_secret_key();
+
+            let plaintext_list = PlaintextList::new(msg * delta, PlaintextCount(polynomial_size.0));
+
+            // The i-th coefficient of the trivial encryption is shifted by i, alternatively up and
+            // down, which must be the measured noise
+            let mut shifted_plaintext_list = plaintext_list.clone();
+            for (index, value) in shifted_plaintext_list.as_mut().iter_mut().enumerate() {
+                let offset = Scalar::cast_from(index as u128);
+                *value = match (index % 2 == 0, ciphertext_modulus.is_native_modulus()) {
+                    (true, true) => value.wrapping_add(offset),
+                    (false, true) => value.wrapping_sub(offset),
+                    (true, false) => value.wrapping_add_custom_mod(offset, modulus_as_scalar),
+                    (false, false) => value.wrapping_sub_custom_mod(offset, modulus_as_scalar),
+                };
+            }
+
+            let trivial = allocate_and_trivially_encrypt_new_glwe_ciphertext(
+                glwe_dimension.to_glwe_size(),
+                &shifted_plaintext_list,
+                ciphertext_modulus,
+            );
+
+            let trivial_noise = glwe_ciphertext_noise(&glwe_sk, &trivial, &plaintext_list);
+            for (index, noise) in trivial_noise.into_iter().enumerate() {
+                let offset = Scalar::cast_from(index as u128).into_signed();
+                if index % 2 == 0 {
+                    assert_eq!(noise, offset);
+                } else {
+                    assert_eq!(noise, -offset);
+                }
+            }
+
+            let mut glwe = GlweCiphertext::new(
+                Scalar::ZERO,
+                glwe_dimension.to_glwe_size(),
+                polynomial_size,
+                ciphertext_modulus,
+            );
+
+            encrypt_glwe_ciphertext(
+                &glwe_sk,
+                &mut glwe,
+                &plaintext_list,
+                glwe_noise_distribution,
+                &mut rsc.encryption_random_generator,
+            );
+
+            let glwe_noise = glwe_ciphertext_noise(&glwe_sk, &glwe, &plaintext_list);
+            assert_eq!(glwe_noise.len(), polynomial_size.0);
+
+            // Each coefficient has the noise of the LWE sample extracted from it
+            let mut lwe = LweCiphertext::new(
+                Scalar::ZERO,
+                equivalent_lwe_sk.lwe_dimension().to_lwe_size(),
+                ciphertext_modulus,
+            );
+            for (index, noise) in glwe_noise.into_iter().enumerate() {
+                extract_lwe_sample_from_glwe_ciphertext(&glwe, &mut lwe, MonomialDegree(index));
+                assert_eq!(
+                    lwe_ciphertext_noise(&equivalent_lwe_sk, &lwe, Plaintext(msg * delta)),
+                    noise
+                );
+            }
+        }
+    }
+}
+
+create_parametrized_test_with_non_native_parameters!(glwe_encrypt_noise_custom_mod);
@@ -1,15 +1,22 @@
 use itertools::Itertools;
-use crate::boolean::parameters::PolynomialSize;
-use crate::core_crypto::algorithms::{allocate_and_encrypt_new_lwe_ciphertext, decrypt_lwe_ciphertext, decrypt_lwe_ciphertext_list, encrypt_glwe_ciphertext_assign};
-use crate::core_crypto::entities::{GlweCiphertext, GlweCiphertextList, GlweCiphertextListOwned, GlweCiphertextOwned, GlweSecretKeyOwned, LweCiphertextListOwned, LweCiphertextOwned, LweSecretKey, LweSecretKeyOwned, Plaintext, PlaintextList, PlaintextRef};
+use serde::{Deserialize, Serialize};
+use crate::core_crypto::algorithms::{allocate_and_encrypt_new_lwe_ciphertext, decrypt_lwe_ciphertext, decrypt_lwe_ciphertext_list, encrypt_glwe_ciphertext_assign, encrypt_seeded_glwe_ciphertext_list};
+use crate::core_crypto::commons::math::random::Seeder;
+use crate::core_crypto::entities::{GlweCiphertext, GlweCiphertextList, GlweCiphertextListOwned, GlweCiphertextOwned, GlweSecretKeyOwned, LweCiphertextListOwned, LweCiphertextOwned, LweSecretKey, LweSecretKeyOwned, Plaintext, PlaintextList, PlaintextRef, SeededGlweCiphertextListOwned};
+#[cfg(feature = "gpu")]
 use crate::core_crypto::gpu::{CudaDevice, CudaStream};
+#[cfg(feature = "gpu")]
 use crate::core_crypto::gpu::glwe_ciphertext_list::CudaGlweCiphertextList;
+#[cfg(feature = "gpu")]
 use crate::core_crypto::gpu::lwe_ciphertext_list::CudaLweCiphertextList;
+#[cfg(feature = "gpu")]
 use crate::core_crypto::gpu::vec::CudaVec;
-use crate::core_crypto::prelude::{ContiguousEntityContainer, ContiguousEntityContainerMut, GlweCiphertextCount, GlweSize, LweCiphertextCount, LweCiphertextList, LweSize, PlaintextCount};
+use crate::core_crypto::prelude::{ContiguousEntityContainer, ContiguousEntityContainerMut, GlweCiphertextCount, GlweSize, LweCiphertextCount, LweCiphertextList, LweSize, PlaintextCount, PolynomialSize};
 use crate::integer::BooleanBlock;
 use crate::integer::encryption::KnowsMessageModulus;
+#[cfg(feature = "gpu")]
 use crate::integer::gpu::ciphertext::boolean_value::CudaBooleanBlock;
+#[cfg(feature = "gpu")]
 use crate::integer::gpu::CudaServerKey;
 use crate::shortint;
 use crate::shortint::engine::ShortintEngine;
@@ -18,6 +25,7 @@ use crate::shortint::parameters::Degree;
 use crate::shortint::server_key::LookupTableOwned;
 
 
+#[cfg(feature = "gpu")]
 pub struct Keys {
     pub lwe_secret_key: LweSecretKeyOwned<u64>,
     pub glwe_secret_key: GlweSecretKeyOwned<u64>,
@@ -67,6 +75,7 @@ pub fn decrypt_boolean_block_client_key(block: &BooleanBlock, client_key: &short
 
 }
 
+#[cfg(feature = "gpu")]
 pub fn decrypt_cuda_boolean_block_client_key(cuda_block: &CudaBooleanBlock, client_key: &shortint::ClientKey, stream: &CudaStream) -> bool {
     let block = cuda_block.to_boolean_block(&stream);
     let delta = (1_u64 << 63)
@@ -80,6 +89,7 @@ pub fn decrypt_cuda_boolean_block_client_key(cuda_block: &CudaBooleanBlock, clie
 
 }
 
+#[cfg(feature = "gpu")]
 pub fn decrypt_boolean_block(cuda_block: &CudaBooleanBlock, lwe_secret_key: &LweSecretKey<&[u64]>, params: ShortintParameterSet, delta: u64, stream: &CudaStream) -> bool {
     let block = cuda_block.to_boolean_block(&stream);
     let v = decrypt_lwe_ciphertext(
@@ -90,6 +100,7 @@ pub fn decrypt_boolean_block(cuda_block: &CudaBooleanBlock, lwe_secret_key: &Lwe
     (d % params.message_modulus().0 as u64) != 0
 }
 
+#[cfg(feature = "gpu")]
 pub fn make_context_gpu() -> (CudaStream, ShortintEngine) {
     let gpu_index = 0;
     let device = CudaDevice::new(gpu_index);
@@ -108,6 +119,7 @@ pub fn make_lweciphertextlist(ct_count: usize, lwe_size: LweSize, ct_mod: Cipher
     )
 }
 
+#[cfg(feature = "gpu")]
 pub fn make_cuda_lweciphertextlist(ct_count: usize, lwe_size: LweSize, ct_mod: CiphertextModulus, stream: &CudaStream) -> CudaLweCiphertextList<u64> {
     let ct_list_out = LweCiphertextListOwned::new(
         0,
@@ -139,6 +151,7 @@ pub fn encrypt_ciphertextlist(values: Vec<u64>, mut engine: &mut ShortintEngine,
 }
 
 
+#[cfg(feature = "gpu")]
 pub fn encrypt_cuda_ciphertextlist(values: Vec<u64>, mut engine: &mut ShortintEngine, secret_key: &LweSecretKey<&[u64]>, total_mod: u64, delta: u64, params: ShortintParameterSet, stream: &CudaStream) -> CudaLweCiphertextList<u64> {
     let raw_list = values.iter().map(|v|{
         encrypt(*v, &mut engine, secret_key, params, total_mod, delta)
@@ -184,6 +197,7 @@ pub fn decrypt_ciphertextlist(lwe_cts: &LweCiphertextList<Vec<u64>>, secret_key:
     }).collect_vec()
 }
 
+#[cfg(feature = "gpu")]
 pub fn decrypt_cuda_ciphertextlist(cts: &CudaLweCiphertextList<u64>, keys: &Keys, delta: u64) -> Vec<u64> {
     let count = cts.lwe_ciphertext_count().0;
     let lwe_cts = cts.to_lwe_ciphertext_list(&keys.stream);
@@ -204,6 +218,7 @@ pub fn decrypt_cuda_ciphertextlist(cts: &CudaLweCiphertextList<u64>, keys: &Keys
     }).collect_vec()
 }
 
+#[cfg(feature = "gpu")]
 pub fn make_indices(max: u64, total: usize, slots: usize, keys: &Keys) -> Vec<CudaVec<u64>> {
     let indices_raw = (0..max).collect_vec();
     let mut result: Vec<CudaVec<u64>> = Vec::with_capacity(total);
@@ -224,20 +239,24 @@ pub fn generate_lookup_tables_individual<F>(fs: Vec<Vec<F>>, client_key: &shorti
     where
         F: Fn(u64) -> u64,
 {
-    let gen_acc = |f| {
+    let glwe_size = client_key.glwe_secret_key.glwe_dimension().to_glwe_size();
+    encrypt_lookup_tables_individual(&fs, client_key, params, engine, |f| {
         generate_accumulator(
-            client_key.glwe_secret_key.glwe_dimension().to_glwe_size(),
+            glwe_size,
             params.polynomial_size(),
             params.message_modulus(),
             params.ciphertext_modulus(),
             params.carry_modulus(),
             f,
         )
-    };
+    })
+}
 
-    let lut_list = fs.iter().map(|f_vec| {
-        f_vec.iter().map(|f| {
-            let (mut acc, max_value)  = gen_acc(f);
+/// Encrypts the accumulators `gen_acc` generates for every entry of `luts`.
+fn encrypt_lookup_tables_individual<T>(luts: &[Vec<T>], client_key: &shortint::ClientKey, params: ShortintParameterSet, engine: &mut ShortintEngine, gen_acc: impl Fn(&T) -> (GlweCiphertextOwned<u64>, u64)) -> Vec<Vec<LookupTableOwned>> {
+    let lut_list = luts.iter().map(|lut_vec| {
+        lut_vec.iter().map(|lut| {
+            let (mut acc, max_value)  = gen_acc(lut);
             encrypt_glwe_ciphertext_assign(
                 &client_key.glwe_secret_key,
                 &mut acc,
@@ -254,30 +273,188 @@ pub fn generate_lookup_tables_individual<F>(fs: Vec<Vec<F>>, client_key: &shorti
     lut_list
 }
 
+/// Encrypted LUTs of a template with the masks of the accumulators replaced by a seed, one seeded
+/// list per feature. Roughly `glwe_dimension + 1` times smaller than the LUTs of
+/// [`generate_lookup_tables_individual`], which the server gets back with [`Self::decompress`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SeededLookupTables {
+    lists: Vec<SeededGlweCiphertextListOwned<u64>>,
+    degrees: Vec<Vec<Degree>>,
+}
+
+impl SeededLookupTables {
+    pub fn num_features(&self) -> usize {
+        self.lists.len()
+    }
+
+    /// Number of blocks every feature is evaluated on.
+    pub fn blocks_per_feature(&self) -> Vec<usize> {
+        self.degrees.iter().map(Vec::len).collect()
+    }
+
+    /// Regenerates the masks from the seeds, the result is the same as the LUTs of
+    /// [`generate_lookup_tables_individual`].
+    pub fn decompress(self) -> Vec<Vec<LookupTableOwned>> {
+        self.lists.into_iter().zip(self.degrees).map(|(list, degrees)| {
+            let list = list.decompress_into_glwe_ciphertext_list();
+            list.iter().zip(degrees).map(|(acc, degree)| {
+                LookupTableOwned {
+                    acc: GlweCiphertext::from_container(acc.as_ref().to_vec(), acc.polynomial_size(), acc.ciphertext_modulus()),
+                    degree,
+                }
+            }).collect_vec()
+        }).collect_vec()
+    }
+}
+
+/// Seeded counterpart of [`generate_lookup_tables_individual`], for templates that are stored
+/// before being evaluated.
+pub fn generate_seeded_lookup_tables_individual<F>(fs: Vec<Vec<F>>, client_key: &shortint::ClientKey, params: ShortintParameterSet, engine: &mut ShortintEngine) -> SeededLookupTables
+    where
+        F: Fn(u64) -> u64,
+{
+    let glwe_size = client_key.glwe_secret_key.glwe_dimension().to_glwe_size();
+    encrypt_seeded_lookup_tables_individual(&fs, client_key, params, engine, |f| {
+        generate_accumulator(
+            glwe_size,
+            params.polynomial_size(),
+            params.message_modulus(),
+            params.ciphertext_modulus(),
+            params.carry_modulus(),
+            f,
+        )
+    })
+}
+
+/// Seeded counterpart of [`encrypt_lookup_tables_individual`].
+fn encrypt_seeded_lookup_tables_individual<T>(luts: &[Vec<T>], client_key: &shortint::ClientKey, params: ShortintParameterSet, engine: &mut ShortintEngine, gen_acc: impl Fn(&T) -> (GlweCiphertextOwned<u64>, u64)) -> SeededLookupTables {
+    let glwe_size = client_key.glwe_secret_key.glwe_dimension().to_glwe_size();
+    let polynomial_size = params.polynomial_size();
+
+    let (lists, degrees) = luts.iter().map(|lut_vec| {
+        //The accumulators are trivial, their bodies are the plaintexts to encrypt
+        let mut encoded = PlaintextList::new(0, PlaintextCount(lut_vec.len() * polynomial_size.0));
+        let degrees = encoded.as_mut().chunks_exact_mut(polynomial_size.0).zip(lut_vec).map(|(plaintexts, lut)| {
+            let (acc, max_value) = gen_acc(lut);
+            plaintexts.copy_from_slice(acc.get_body().as_ref());
+            Degree::new(max_value as usize)
+        }).collect_vec();
+
+        let mut list = SeededGlweCiphertextListOwned::new(
+            0,
+            glwe_size,
+            polynomial_size,
+            GlweCiphertextCount(lut_vec.len()),
+            engine.seeder.seed().into(),
+            params.ciphertext_modulus(),
+        );
+        encrypt_seeded_glwe_ciphertext_list(
+            &client_key.glwe_secret_key,
+            &mut list,
+            &encoded,
+            params.glwe_noise_distribution(),
+            &mut engine.seeder,
+        );
+        (list, degrees)
+    }).unzip();
+
+    SeededLookupTables { lists, degrees }
+}
+
+/// Same as [`generate_seeded_lookup_tables_individual`] with LUTs given by their values.
+pub fn generate_seeded_lookup_tables_individual_from_rows(rows: &[Vec<&[u64]>], client_key: &shortint::ClientKey, params: ShortintParameterSet, engine: &mut ShortintEngine) -> SeededLookupTables {
+    let glwe_size = client_key.glwe_secret_key.glwe_dimension().to_glwe_size();
+    encrypt_seeded_lookup_tables_individual(rows, client_key, params, engine, |row| {
+        generate_accumulator_from_row(glwe_size, params, row)
+    })
+}
+
+/// Same as [`generate_lookup_tables_individual`] with LUTs given by their values, e.g. rows of a
+/// shared table set, instead of closures.
+pub fn generate_lookup_tables_individual_from_rows(rows: &[Vec<&[u64]>], client_key: &shortint::ClientKey, params: ShortintParameterSet, engine: &mut ShortintEngine) -> Vec<Vec<LookupTableOwned>> {
+    let glwe_size = client_key.glwe_secret_key.glwe_dimension().to_glwe_size();
+    encrypt_lookup_tables_individual(rows, client_key, params, engine, |row| {
+        generate_accumulator_from_row(glwe_size, params, row)
+    })
+}
+
+/// Number of values of the low digit of a feature with more bins than a block holds, half of the
+/// block space so that the selection of the high digit fits next to it, see
+/// [`all_in_one_wide`](super::cpu::all_in_one_wide).
+pub fn wide_low_digit_count(message_modulus: MessageModulus, carry_modulus: CarryModulus) -> usize {
+    message_modulus.0 * carry_modulus.0 / 2
+}
+
+/// LUTs for features with more bins than a block holds, indexed `[feature][high digit][block]`.
+///
+/// The LUT of a high digit `h` maps `low + low_digit_count` to the entry `h * low_digit_count + low`
+/// of the row and the low digit alone to 0, so that only the LUTs of the actual high digit of the
+/// probe contribute to the score. Rows can be as long as `low_digit_count` times the block space.
+pub fn generate_wide_lookup_tables_from_rows(rows: &[Vec<&[u64]>], client_key: &shortint::ClientKey, params: ShortintParameterSet, engine: &mut ShortintEngine) -> Vec<Vec<Vec<LookupTableOwned>>> {
+    let low_count = wide_low_digit_count(params.message_modulus(), params.carry_modulus());
+    let block_space = params.message_modulus().0 * params.carry_modulus().0;
+
+    rows.iter().map(|row_vec| {
+        let columns = row_vec.iter().map(|row| row.len()).max().unwrap_or(0);
+        let high_count = columns.div_ceil(low_count);
+        assert!(high_count <= block_space, "{columns} bins do not fit two blocks");
+
+        let high_rows = (0..high_count).map(|high| {
+            row_vec.iter().map(|row| {
+                let digits = row.get(high * low_count..).unwrap_or(&[]);
+                let mut high_row = vec![0; low_count];
+                high_row.extend_from_slice(&digits[..digits.len().min(low_count)]);
+                high_row
+            }).collect_vec()
+        }).collect_vec();
+        let high_rows = high_rows.iter().map(|rows| rows.iter().map(Vec::as_slice).collect_vec()).collect_vec();
+        generate_lookup_tables_individual_from_rows(&high_rows, client_key, params, engine)
+    }).collect_vec()
+}
+
+/// Same as [`generate_lookup_tables`] with LUTs given by their values instead of closures.
+pub fn generate_lookup_tables_from_rows(rows: &[&[u64]], glwe_secret_key: &GlweSecretKeyOwned<u64>, params: ShortintParameterSet, engine: &mut ShortintEngine) -> GlweCiphertextListOwned<u64> {
+    let glwe_size = glwe_secret_key.glwe_dimension().to_glwe_size();
+    encrypt_lookup_table_list(rows, glwe_secret_key, params, engine, |row| {
+        generate_accumulator_from_row(glwe_size, params, row).0
+    })
+}
+
+/// Same as [`generate_cuda_lookup_tables`] with LUTs given by their values instead of closures.
+#[cfg(feature = "gpu")]
+pub fn generate_cuda_lookup_tables_from_rows(rows: &[&[u64]], glwe_secret_key: &GlweSecretKeyOwned<u64>, params: ShortintParameterSet, engine: &mut ShortintEngine, stream: &CudaStream) -> CudaGlweCiphertextList<u64> {
+    let lut_list = generate_lookup_tables_from_rows(rows, glwe_secret_key, params, engine);
+    CudaGlweCiphertextList::from_glwe_ciphertext_list(&lut_list, stream)
+}
+
 pub fn generate_lookup_tables<F>(fs: Vec<F>, glwe_secret_key: &GlweSecretKeyOwned<u64>, params: ShortintParameterSet, engine: &mut ShortintEngine) -> GlweCiphertextListOwned<u64>
     where
         F: Fn(u64) -> u64,
 {
-    let gen_acc = |f| {
+    let glwe_size = glwe_secret_key.glwe_dimension().to_glwe_size();
+    encrypt_lookup_table_list(&fs, glwe_secret_key, params, engine, |f| {
         generate_accumulator(
-            glwe_secret_key.glwe_dimension().to_glwe_size(),
+            glwe_size,
             params.polynomial_size(),
             params.message_modulus(),
             params.ciphertext_modulus(),
             params.carry_modulus(),
             f,
         ).0
-    };
+    })
+}
 
+/// Encrypts the accumulators `gen_acc` generates for `luts` into a single list.
+fn encrypt_lookup_table_list<T>(luts: &[T], glwe_secret_key: &GlweSecretKeyOwned<u64>, params: ShortintParameterSet, engine: &mut ShortintEngine, gen_acc: impl Fn(&T) -> GlweCiphertextOwned<u64>) -> GlweCiphertextListOwned<u64> {
     let mut lut_list = GlweCiphertextList::new(
         0,
         params.glwe_dimension().to_glwe_size(),
         params.polynomial_size(),
-        GlweCiphertextCount(fs.len()),
+        GlweCiphertextCount(luts.len()),
         params.ciphertext_modulus(),
     );
 
-    lut_list.iter_mut().zip(fs).for_each(|(mut lut, f)| {
+    lut_list.iter_mut().zip(luts).for_each(|(mut lut, f)| {
         let mut acc = gen_acc(f);
         encrypt_glwe_ciphertext_assign(
             &glwe_secret_key,
@@ -293,6 +470,7 @@ pub fn generate_lookup_tables<F>(fs: Vec<F>, glwe_secret_key: &GlweSecretKeyOwne
 
 
 
+#[cfg(feature = "gpu")]
 pub fn generate_cuda_lookup_tables<F>(fs: Vec<F>, glwe_secret_key: &GlweSecretKeyOwned<u64>, params: ShortintParameterSet, engine: &mut ShortintEngine, stream: &CudaStream) -> CudaGlweCiphertextList<u64>
     where
         F: Fn(u64) -> u64,
@@ -343,6 +521,39 @@ pub fn generate_accumulator<F>(
 ) -> (GlweCiphertextOwned<u64>, u64) where
     F: Fn(u64) -> u64,
 {
+    let modulus_sup = message_modulus.0 * carry_modulus.0;
+    accumulator_from_values(
+        glwe_size,
+        polynomial_size,
+        message_modulus,
+        ciphertext_modulus,
+        carry_modulus,
+        (0..modulus_sup as u64).map(f),
+    )
+}
+
+/// Same as [`generate_accumulator`] with the LUT given by its values, inputs past the end of `row`
+/// map to 0.
+pub fn generate_accumulator_from_row(glwe_size: GlweSize, params: ShortintParameterSet, row: &[u64]) -> (GlweCiphertextOwned<u64>, u64) {
+    accumulator_from_values(
+        glwe_size,
+        params.polynomial_size(),
+        params.message_modulus(),
+        params.ciphertext_modulus(),
+        params.carry_modulus(),
+        row.iter().copied().chain(std::iter::repeat(0)),
+    )
+}
+
+/// Fills the boxes of the accumulator with the first `message_modulus * carry_modulus` values.
+fn accumulator_from_values(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    message_modulus: MessageModulus,
+    ciphertext_modulus: CiphertextModulus,
+    carry_modulus: CarryModulus,
+    values: impl Iterator<Item = u64>,
+) -> (GlweCiphertextOwned<u64>, u64) {
     let mut accumulator = GlweCiphertext::new(
         0,
         glwe_size,
@@ -369,11 +580,10 @@ pub fn generate_accumulator<F>(
     // Tracking the max value of the function to define the degree later
     let mut max_value = 0;
 
-    for i in 0..modulus_sup {
+    for (i, value) in values.take(modulus_sup).enumerate() {
         let index = i * box_size;
-        let f_eval = f(i as u64);
-        max_value = max_value.max(f_eval);
-        accumulator_u64[index..index + box_size].fill(f_eval * delta);
+        max_value = max_value.max(value);
+        accumulator_u64[index..index + box_size].fill(value * delta);
     }
 
     let half_box_size = box_size / 2;
@@ -389,3 +599,30 @@ pub fn generate_accumulator<F>(
     (accumulator, max_value)
 }
 
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+
+    #[test]
+    fn accumulator_from_row_matches_closure() {
+        let params: ShortintParameterSet = PARAM_MESSAGE_2_CARRY_2_KS_PBS.into();
+        let glwe_size = params.glwe_dimension().to_glwe_size();
+
+        // A full row of the 16 block values and a shorter one whose missing entries map to 0
+        let full = (0..16).map(|y| (5 * y + 3) % 4).collect_vec();
+        for row in [&full[..], &full[..7]] {
+            let from_row = generate_accumulator_from_row(glwe_size, params, row);
+            let from_closure = generate_accumulator(
+                glwe_size,
+                params.polynomial_size(),
+                params.message_modulus(),
+                params.ciphertext_modulus(),
+                params.carry_modulus(),
+                |x| row.get(x as usize).copied().unwrap_or(0),
+            );
+            assert_eq!(from_row, from_closure, "row of {} values", row.len());
+        }
+    }
+}
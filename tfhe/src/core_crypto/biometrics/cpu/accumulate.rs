@@ -0,0 +1,124 @@
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use crate::shortint;
+use crate::shortint::Ciphertext;
+use crate::shortint::ciphertext::NoiseLevel;
+
+/// Sums aligned blocks of several radix numbers.
+///
+/// `columns[i]` holds the i-th block of every number to add. Blocks of a column are added
+/// directly as long as the degree and noise of the accumulator allow it, a column is only
+/// bootstrapped (message and carry extraction) when it cannot be reduced to a single block.
+/// Blocks of degree zero, e.g. trivial padding, are skipped.
+///
+/// The returned blocks may still hold carries, they have to be propagated before using the
+/// result as a radix number.
+pub fn accumulate_blocks(server_key: &shortint::ServerKey, mut columns: Vec<Vec<Ciphertext>>) -> Vec<Ciphertext> {
+    let num_columns = columns.len();
+    let message_max = server_key.message_modulus.0 - 1;
+
+    loop {
+        // Pack every column into as few accumulators as the degree and noise allow
+        let packed = columns.into_par_iter().map(|column| {
+            let mut accumulators: Vec<Ciphertext> = Vec::new();
+            for block in column {
+                if block.degree.get() == 0 {
+                    continue;
+                }
+                match accumulators.last_mut() {
+                    Some(acc) if server_key.is_add_possible(acc.noise_degree(), block.noise_degree()).is_ok() => {
+                        server_key.unchecked_add_assign(acc, &block);
+                    }
+                    _ => accumulators.push(block),
+                }
+            }
+            accumulators
+        }).collect::<Vec<_>>();
+
+        if packed.iter().all(|accumulators| accumulators.len() <= 1) {
+            return packed.into_iter().map(|mut accumulators| {
+                accumulators.pop().unwrap_or_else(|| server_key.create_trivial(0))
+            }).collect();
+        }
+
+        // Split the accumulators of the columns that could not be reduced into message and carry
+        let split = packed.into_par_iter().enumerate().map(|(column_index, accumulators)| {
+            if accumulators.len() <= 1 {
+                return (accumulators, Vec::new());
+            }
+            let (messages, carries): (Vec<_>, Vec<_>) = accumulators.into_par_iter().map(|acc| {
+                if acc.degree.get() <= message_max && acc.noise_level() == NoiseLevel::NOMINAL {
+                    // Nothing to extract, the block is already clean
+                    (acc, None)
+                } else if column_index < num_columns - 1 {
+                    let (message, carry) = rayon::join(
+                        || server_key.message_extract(&acc),
+                        || server_key.carry_extract(&acc),
+                    );
+                    (message, Some(carry))
+                } else {
+                    (server_key.message_extract(&acc), None)
+                }
+            }).unzip();
+            (messages, carries.into_iter().flatten().collect())
+        }).collect::<Vec<_>>();
+
+        columns = vec![Vec::new(); num_columns];
+        for (column_index, (messages, carries)) in split.into_iter().enumerate() {
+            columns[column_index].extend(messages);
+            if column_index + 1 < num_columns {
+                columns[column_index + 1].extend(carries);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core_crypto::biometrics::common;
+    use crate::core_crypto::biometrics::cpu::all_in_one_classic;
+    use crate::shortint;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::{ClientKey, MessageModulus};
+
+    const NUM_FEATURES: u64 = 6;
+    const NUM_SUM_BLOCKS: usize = 4;
+
+    // Entries up to 15 over two blocks, six low blocks of 3 already overflow a block
+    fn score_row(feature: u64, probe_y: u64) -> u64 {
+        (5 * probe_y + 3 * feature + 7) % 16
+    }
+
+    #[test]
+    fn accumulate_matches_radix_sum() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+
+        let values: Vec<Vec<Vec<u64>>> = (0..NUM_FEATURES).map(|feature| {
+            (0..2).map(|block| {
+                (0..16).map(|y| (score_row(feature, y) >> (2 * block)) & 0b11).collect()
+            }).collect()
+        }).collect();
+        let rows: Vec<Vec<&[u64]>> = values.iter().map(|blocks| blocks.iter().map(Vec::as_slice).collect()).collect();
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine);
+
+        // All low blocks at 3 for the first probe, the sums of both columns carry
+        for probe in [[0, 1, 2, 3, 4, 5], [2, 7, 1, 14, 10, 4]] {
+            let score: u64 = (0..NUM_FEATURES).map(|feature| score_row(feature, probe[feature as usize])).sum();
+            let encrypted_probe: Vec<Vec<_>> = probe.iter().map(|&p| {
+                (0..2).map(|_| client_key.encrypt_with_message_modulus(p, MessageModulus(16))).collect()
+            }).collect();
+
+            for threshold in [score, score + 1] {
+                let (accumulated, _) = all_in_one_classic::authenticate_accumulate(server_key.clone(), encrypted_probe.clone(), luts.clone(), threshold as usize, NUM_SUM_BLOCKS);
+                let (summed, _) = all_in_one_classic::authenticate(server_key.clone(), encrypted_probe.clone(), luts.clone(), threshold as usize, NUM_SUM_BLOCKS);
+
+                let accumulated = common::decrypt_boolean_block_client_key(&accumulated, &client_key);
+                let summed = common::decrypt_boolean_block_client_key(&summed, &client_key);
+                assert_eq!(accumulated, summed, "probe {probe:?} with score {score} against {threshold}");
+                assert_eq!(accumulated, threshold == score, "probe {probe:?} with score {score} against {threshold}");
+            }
+        }
+    }
+}
@@ -7,6 +7,8 @@ use crate::integer::{BooleanBlock, IntegerCiphertext, RadixCiphertext};
 use crate::{integer, shortint};
 use crate::shortint::{Ciphertext};
 use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::accumulate::accumulate_blocks;
+use crate::core_crypto::biometrics::cpu::validation::{validate_inputs, AuthenticationError};
 
 pub fn authenticate(
     server_key: shortint::ServerKey,
@@ -16,15 +18,60 @@ pub fn authenticate(
     num_sum_blocks: usize
 ) -> (BooleanBlock, Duration) {
     let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
-    let big_server_key_insde = big_server_key.clone();
     let start = Instant::now();
 
-    let (tx, rx) = mpsc::sync_channel(probe.len());
-    thread::spawn(move || {
+    let lut_values = evaluate_in_worker(server_key, &big_server_key, probe, luts, num_sum_blocks).expect("LUT evaluation failed");
+    let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+
+    let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
+
+    let elapsed = start.elapsed();
+    (res, elapsed)
+}
+
+/// Fallible [`authenticate`], the inputs are checked with [`validate_inputs`] and a panic of the
+/// worker thread is reported instead of deciding on the features it evaluated so far.
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    validate_inputs(&server_key, &probe, &luts, threshold as u64, num_sum_blocks)?;
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let lut_values = evaluate_in_worker(server_key, &big_server_key, probe, luts, num_sum_blocks)?;
+    let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+
+    let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
+
+    let elapsed = start.elapsed();
+    Ok((res, elapsed))
+}
+
+/// Applies the LUTs of every feature on a separate thread and pads the outputs to `num_sum_blocks`.
+pub(super) fn evaluate_in_worker(
+    server_key: shortint::ServerKey,
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    num_sum_blocks: usize
+) -> Result<Vec<RadixCiphertext>, AuthenticationError> {
+    let big_server_key_insde = big_server_key.clone();
+    let num_features = probe.len();
+
+    let (tx, rx) = mpsc::sync_channel(num_features);
+    let worker = thread::spawn(move || {
         probe.into_par_iter().zip(luts).for_each(|(mut probes, luts)| {
             let cur_len = probes.len();
             probes.par_iter_mut().zip(luts).for_each(|(probe, lut)| {
                 server_key.apply_lookup_table_assign(probe, &lut);
+                // The probe is encrypted over the whole block space but the LUT outputs a
+                // regular block, the comparison packs blocks according to these moduli
+                probe.message_modulus = server_key.message_modulus;
+                probe.carry_modulus = server_key.carry_modulus;
             });
             let mut as_radix = RadixCiphertext::from_blocks(probes);
             big_server_key_insde.extend_radix_with_trivial_zero_blocks_msb_assign(&mut as_radix, num_sum_blocks - cur_len);
@@ -33,7 +80,84 @@ pub fn authenticate(
     });
 
     let lut_values = rx.iter().collect_vec();
+    //The channel also closes when the worker dies, only the join tells both apart
+    if worker.join().is_err() || lut_values.len() != num_features {
+        return Err(AuthenticationError::WorkerPanicked);
+    }
+    Ok(lut_values)
+}
+
+/// Fallible [`authenticate_with_key`], the inputs are checked with [`validate_inputs`].
+pub fn try_authenticate_with_key(
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<BooleanBlock, AuthenticationError> {
+    validate_inputs(&big_server_key.key, &probe, luts, threshold as u64, num_sum_blocks)?;
+    Ok(authenticate_with_key(big_server_key, probe, luts, threshold, num_sum_blocks))
+}
+
+/// Same computation as [`authenticate`] without the per call key setup, thread and channel, for
+/// callers that keep the radix server key around.
+pub fn authenticate_with_key(
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: usize,
+    num_sum_blocks: usize
+) -> BooleanBlock {
+    assert_eq!(probe.len(), luts.len(), "probe and template have a different number of features");
+    let lut_values = probe.into_par_iter().zip(luts).map(|(mut probes, luts)| {
+        let cur_len = probes.len();
+        probes.par_iter_mut().zip(luts).for_each(|(probe, lut)| {
+            big_server_key.key.apply_lookup_table_assign(probe, lut);
+            probe.message_modulus = big_server_key.key.message_modulus;
+            probe.carry_modulus = big_server_key.key.carry_modulus;
+        });
+        let mut as_radix = RadixCiphertext::from_blocks(probes);
+        big_server_key.extend_radix_with_trivial_zero_blocks_msb_assign(&mut as_radix, num_sum_blocks - cur_len);
+        as_radix
+    }).collect::<Vec<_>>();
+
     let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+    big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64)
+}
+
+/// Same as [`authenticate`] but sums the aligned LUT output blocks directly instead of turning
+/// every feature into a padded radix number, see [`accumulate_blocks`].
+pub fn authenticate_accumulate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> (BooleanBlock, Duration) {
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let lut_values = probe.into_par_iter().zip(luts).map(|(mut probes, luts)| {
+        assert!(probes.len() <= num_sum_blocks, "feature has more blocks than the sum");
+        probes.par_iter_mut().zip(luts).for_each(|(probe, lut)| {
+            server_key.apply_lookup_table_assign(probe, &lut);
+            // The extraction of the carries and their propagation follow these moduli
+            probe.message_modulus = server_key.message_modulus;
+            probe.carry_modulus = server_key.carry_modulus;
+        });
+        probes
+    }).collect::<Vec<_>>();
+
+    // Only non-trivial blocks end up in the columns, the padding is never materialized
+    let mut columns = vec![Vec::with_capacity(lut_values.len()); num_sum_blocks];
+    for blocks in lut_values {
+        for (column, block) in columns.iter_mut().zip(blocks) {
+            column.push(block);
+        }
+    }
+
+    let mut sum = RadixCiphertext::from_blocks(accumulate_blocks(&server_key, columns));
+    big_server_key.full_propagate_parallelized(&mut sum);
 
     let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
 
@@ -41,6 +165,18 @@ pub fn authenticate(
     (res, elapsed)
 }
 
+/// Fallible [`authenticate_accumulate`], the inputs are checked with [`validate_inputs`].
+pub fn try_authenticate_accumulate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    validate_inputs(&server_key, &probe, &luts, threshold as u64, num_sum_blocks)?;
+    Ok(authenticate_accumulate(server_key, probe, luts, threshold, num_sum_blocks))
+}
+
 /*
 pub fn authenticate_debug(
     decrypt: Box<dyn Fn(&Ciphertext) -> u64>,
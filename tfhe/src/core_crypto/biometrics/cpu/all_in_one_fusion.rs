@@ -0,0 +1,136 @@
+use std::time::{Duration, Instant};
+use crate::integer::{BooleanBlock, RadixCiphertext};
+use crate::{integer, shortint};
+use crate::shortint::{Ciphertext};
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic::evaluate_in_worker;
+use crate::core_crypto::biometrics::cpu::validation::{validate_inputs, AuthenticationError};
+
+/// Encrypted probe and LUTs of one modality together with its cleartext fusion weight.
+pub struct ModalityInput {
+    pub probe: Vec<Vec<Ciphertext>>,
+    pub luts: Vec<Vec<LookupTableOwned>>,
+    pub weight: u64,
+}
+
+/// Score-level fusion of several HELR comparators.
+///
+/// The HELR score of every modality is computed in the common radix width `num_sum_blocks`,
+/// multiplied by its weight and all weighted scores are summed before being compared against
+/// the fused `threshold`. All modalities must be encrypted under the same key.
+pub fn authenticate(
+    server_key: shortint::ServerKey,
+    modalities: Vec<ModalityInput>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> (BooleanBlock, Duration) {
+    assert!(!modalities.is_empty(), "at least one modality is required");
+    for modality in &modalities {
+        assert!(modality.luts.iter().all(|luts| luts.len() <= num_sum_blocks), "feature has more blocks than the sum");
+    }
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let sum = weighted_sum(&server_key, &big_server_key, modalities, num_sum_blocks).expect("LUT evaluation failed");
+    let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
+
+    let elapsed = start.elapsed();
+    (res, elapsed)
+}
+
+/// Fallible [`authenticate`], the inputs of every modality are checked with [`validate_inputs`]
+/// and a panic of a worker is reported.
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    modalities: Vec<ModalityInput>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    if modalities.is_empty() {
+        return Err(AuthenticationError::NoModalities);
+    }
+    for modality in &modalities {
+        validate_inputs(&server_key, &modality.probe, &modality.luts, threshold as u64, num_sum_blocks)?;
+    }
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let sum = weighted_sum(&server_key, &big_server_key, modalities, num_sum_blocks)?;
+    let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
+
+    let elapsed = start.elapsed();
+    Ok((res, elapsed))
+}
+
+fn weighted_sum(
+    server_key: &shortint::ServerKey,
+    big_server_key: &integer::ServerKey,
+    modalities: Vec<ModalityInput>,
+    num_sum_blocks: usize
+) -> Result<RadixCiphertext, AuthenticationError> {
+    let weighted_scores = modalities.into_iter().map(|modality| {
+        let lut_values = evaluate_in_worker(server_key.clone(), big_server_key, modality.probe, modality.luts, num_sum_blocks)?;
+        let score = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+        Ok(big_server_key.scalar_mul_parallelized(&score, modality.weight))
+    }).collect::<Result<Vec<_>, _>>()?;
+    Ok(big_server_key.unchecked_sum_ciphertexts_vec_parallelized(weighted_scores).unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use itertools::Itertools;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::{ClientKey, MessageModulus};
+
+    // Two modalities of two features with a single output block each, indexed [template][probe]
+    const TABLES: [[[[u64; 4]; 4]; 2]; 2] = [
+        [
+            [[3, 2, 1, 0], [2, 3, 2, 1], [1, 2, 3, 2], [0, 1, 2, 3]],
+            [[3, 1, 0, 0], [1, 3, 1, 0], [0, 1, 3, 1], [0, 0, 1, 3]],
+        ],
+        [
+            [[2, 0, 1, 0], [0, 3, 0, 1], [1, 0, 2, 0], [0, 1, 0, 3]],
+            [[3, 2, 2, 1], [2, 3, 1, 1], [2, 1, 3, 2], [1, 1, 2, 3]],
+        ],
+    ];
+    const WEIGHTS: [u64; 2] = [1, 3];
+    // Up to 6 + 3 * 6 = 24, the threshold goes one past it
+    const NUM_SUM_BLOCKS: usize = 3;
+
+    #[test]
+    fn weighted_sum_of_modalities() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+
+        let template = [[1usize, 2], [3, 0]];
+        let luts = TABLES.iter().zip(template).map(|(tables, template)| {
+            let rows: Vec<Vec<&[u64]>> = tables.iter().zip(template).map(|(table, x)| vec![&table[x][..]]).collect();
+            common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine)
+        }).collect_vec();
+
+        for probe in [[[1u64, 2], [3, 0]], [[0, 3], [1, 2]]] {
+            let score: u64 = (0..2).map(|modality| {
+                WEIGHTS[modality] * (0..2).map(|feature| {
+                    TABLES[modality][feature][template[modality][feature]][probe[modality][feature] as usize]
+                }).sum::<u64>()
+            }).sum();
+
+            for threshold in [score, score + 1] {
+                let modalities = (0..2).map(|modality| ModalityInput {
+                    probe: probe[modality].iter().map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))]).collect(),
+                    luts: luts[modality].clone(),
+                    weight: WEIGHTS[modality],
+                }).collect();
+                let (decision, _) = try_authenticate(server_key.clone(), modalities, threshold as usize, NUM_SUM_BLOCKS).unwrap();
+                let decrypted = common::decrypt_boolean_block_client_key(&decision, &client_key);
+                assert_eq!(decrypted, threshold == score, "probe {probe:?} with score {score} against {threshold}");
+            }
+        }
+
+        assert_eq!(try_authenticate(server_key, Vec::new(), 1, NUM_SUM_BLOCKS).err(), Some(AuthenticationError::NoModalities));
+    }
+}
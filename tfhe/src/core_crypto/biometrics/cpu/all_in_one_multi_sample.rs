@@ -0,0 +1,207 @@
+use std::time::{Duration, Instant};
+use itertools::Itertools;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use crate::integer::{BooleanBlock, RadixCiphertext};
+use crate::{integer, shortint};
+use crate::shortint::{Ciphertext};
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic::evaluate_in_worker;
+use crate::core_crypto::biometrics::cpu::validation::{validate_inputs, AuthenticationError};
+
+/// How the encrypted scores of the enrolled samples of one subject are combined into a single
+/// encrypted decision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreAggregation {
+    /// Compare the best score of all samples against the threshold
+    Max,
+    /// Compare the sum of all scores against `threshold * num_samples`
+    Mean,
+    /// Accept if at least `k` of the per-sample decisions are positive
+    Vote(usize),
+}
+
+/// Number of radix blocks needed to hold values up to `count` (inclusive).
+fn blocks_for_count(count: usize, message_modulus: usize) -> usize {
+    let mut blocks = 1;
+    let mut capacity = message_modulus;
+    while capacity <= count {
+        capacity *= message_modulus;
+        blocks += 1;
+    }
+    blocks
+}
+
+/// Authenticates one probe against several enrolled templates of the same subject.
+///
+/// `luts` holds one set of encrypted LUTs per enrolled sample, the probe is evaluated against
+/// each of them and the resulting scores are aggregated according to `aggregation`.
+pub fn authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<Vec<LookupTableOwned>>>,
+    threshold: usize,
+    num_sum_blocks: usize,
+    aggregation: ScoreAggregation,
+) -> (BooleanBlock, Duration) {
+    let num_samples = luts.len();
+    assert!(num_samples > 0, "at least one enrolled sample is required");
+    if let ScoreAggregation::Vote(k) = aggregation {
+        assert!(k > 0 && k <= num_samples, "vote count must be in 1..={num_samples}, got {k}");
+    }
+    assert!(luts.iter().flatten().all(|luts| luts.len() <= num_sum_blocks), "feature has more blocks than the sum");
+
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let scores = sample_scores(&server_key, &big_server_key, &probe, luts, num_sum_blocks).expect("LUT evaluation failed");
+    let res = aggregate(&big_server_key, scores, threshold, aggregation);
+
+    let elapsed = start.elapsed();
+    (res, elapsed)
+}
+
+/// Fallible [`authenticate`], the probe is checked against every sample with [`validate_inputs`]
+/// and a panic of a worker is reported.
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<Vec<LookupTableOwned>>>,
+    threshold: usize,
+    num_sum_blocks: usize,
+    aggregation: ScoreAggregation,
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    let num_samples = luts.len();
+    if num_samples == 0 {
+        return Err(AuthenticationError::NoSamples);
+    }
+    if let ScoreAggregation::Vote(k) = aggregation {
+        if k == 0 || k > num_samples {
+            return Err(AuthenticationError::VoteCountOutOfRange { k, num_samples });
+        }
+    }
+    for sample_luts in &luts {
+        validate_inputs(&server_key, &probe, sample_luts, threshold as u64, num_sum_blocks)?;
+    }
+
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let scores = sample_scores(&server_key, &big_server_key, &probe, luts, num_sum_blocks)?;
+    let res = aggregate(&big_server_key, scores, threshold, aggregation);
+
+    let elapsed = start.elapsed();
+    Ok((res, elapsed))
+}
+
+/// Score of the probe against every sample, in `num_sum_blocks` blocks.
+fn sample_scores(
+    server_key: &shortint::ServerKey,
+    big_server_key: &integer::ServerKey,
+    probe: &[Vec<Ciphertext>],
+    luts: Vec<Vec<Vec<LookupTableOwned>>>,
+    num_sum_blocks: usize,
+) -> Result<Vec<RadixCiphertext>, AuthenticationError> {
+    luts.into_iter().map(|sample_luts| {
+        let lut_values = evaluate_in_worker(server_key.clone(), big_server_key, probe.to_vec(), sample_luts, num_sum_blocks)?;
+        Ok(big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap())
+    }).collect()
+}
+
+fn aggregate(
+    big_server_key: &integer::ServerKey,
+    scores: Vec<RadixCiphertext>,
+    threshold: usize,
+    aggregation: ScoreAggregation,
+) -> BooleanBlock {
+    let num_samples = scores.len();
+    let message_modulus = big_server_key.message_modulus().0;
+
+    match aggregation {
+        ScoreAggregation::Max => {
+            let best = scores.into_par_iter()
+                .reduce_with(|a, b| big_server_key.max_parallelized(&a, &b))
+                .unwrap();
+            big_server_key.unchecked_scalar_ge_parallelized(&best, threshold as u64)
+        }
+        ScoreAggregation::Mean => {
+            // The sum of all scores needs room for num_samples times the largest score
+            let extra_blocks = if num_samples > 1 { blocks_for_count(num_samples - 1, message_modulus) } else { 0 };
+            let scores = scores.into_iter().map(|mut score| {
+                big_server_key.extend_radix_with_trivial_zero_blocks_msb_assign(&mut score, extra_blocks);
+                score
+            }).collect_vec();
+            let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(scores).unwrap();
+            big_server_key.unchecked_scalar_ge_parallelized(&sum, (threshold * num_samples) as u64)
+        }
+        ScoreAggregation::Vote(k) => {
+            let vote_blocks = blocks_for_count(num_samples, message_modulus);
+            let votes = scores.into_par_iter().map(|score| {
+                big_server_key
+                    .unchecked_scalar_ge_parallelized(&score, threshold as u64)
+                    .into_radix::<RadixCiphertext>(vote_blocks, big_server_key)
+            }).collect::<Vec<_>>();
+            let count = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(votes).unwrap();
+            big_server_key.unchecked_scalar_ge_parallelized(&count, k as u64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::{ClientKey, MessageModulus};
+
+    // Two features with a single output block each, indexed [template][probe]
+    const TABLES: [[[u64; 4]; 4]; 2] = [
+        [[3, 2, 1, 0], [2, 3, 2, 1], [1, 2, 3, 2], [0, 1, 2, 3]],
+        [[3, 1, 0, 0], [1, 3, 1, 0], [0, 1, 3, 1], [0, 0, 1, 3]],
+    ];
+    // The probe [1, 2] scores 6, 5 and 2 against these samples
+    const SAMPLES: [[usize; 2]; 3] = [[1, 2], [0, 2], [3, 3]];
+    const PROBE: [u64; 2] = [1, 2];
+    const NUM_SUM_BLOCKS: usize = 2;
+
+    #[test]
+    fn aggregations_with_known_scores() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+
+        let luts = SAMPLES.iter().map(|template| {
+            let rows: Vec<Vec<&[u64]>> = TABLES.iter().zip(template).map(|(table, &x)| vec![&table[x][..]]).collect();
+            common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine)
+        }).collect_vec();
+        let probe = PROBE.iter().map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))]).collect_vec();
+
+        let cases = [
+            // Best score 6
+            (ScoreAggregation::Max, 6, true),
+            (ScoreAggregation::Max, 7, false),
+            // Sum 13 against 3 times the threshold
+            (ScoreAggregation::Mean, 4, true),
+            (ScoreAggregation::Mean, 5, false),
+            // 2 scores reach 5, 1 reaches 6
+            (ScoreAggregation::Vote(2), 5, true),
+            (ScoreAggregation::Vote(2), 6, false),
+            (ScoreAggregation::Vote(3), 2, true),
+            (ScoreAggregation::Vote(3), 3, false),
+        ];
+        for (aggregation, threshold, expected) in cases {
+            let (decision, _) = try_authenticate(server_key.clone(), probe.clone(), luts.clone(), threshold, NUM_SUM_BLOCKS, aggregation).unwrap();
+            let decrypted = common::decrypt_boolean_block_client_key(&decision, &client_key);
+            assert_eq!(decrypted, expected, "{aggregation:?} against {threshold}");
+        }
+
+        assert_eq!(
+            try_authenticate(server_key.clone(), probe.clone(), luts, 5, NUM_SUM_BLOCKS, ScoreAggregation::Vote(4)).err(),
+            Some(AuthenticationError::VoteCountOutOfRange { k: 4, num_samples: 3 })
+        );
+        assert_eq!(
+            try_authenticate(server_key, probe, Vec::new(), 5, NUM_SUM_BLOCKS, ScoreAggregation::Max).err(),
+            Some(AuthenticationError::NoSamples)
+        );
+    }
+}
@@ -2,11 +2,19 @@ use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
 use itertools::Itertools;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use crate::core_crypto::prelude::ThreadCount;
 use crate::integer::{BooleanBlock, IntegerCiphertext, RadixCiphertext};
 use crate::{integer, shortint};
-use crate::shortint::{Ciphertext};
-use crate::shortint::server_key::LookupTableOwned;
+use crate::shortint::{Ciphertext, MessageModulus};
+use crate::shortint::engine::ShortintEngine;
+use crate::shortint::server_key::{LookupTableOwned, ShortintBootstrappingKey};
+use crate::core_crypto::biometrics::common;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic::evaluate_in_worker;
+use crate::core_crypto::biometrics::cpu::validation::{validate_inputs, AuthenticationError};
+use crate::core_crypto::biometrics::planner::PipelineShape;
 
 pub fn authenticate(
     server_key: shortint::ServerKey,
@@ -16,22 +24,9 @@ pub fn authenticate(
     num_sum_blocks: usize
 ) -> (BooleanBlock, Duration) {
     let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
-    let big_server_key_insde = big_server_key.clone();
     let start = Instant::now();
     //tfhe_functions::do_pbs(&keys, &buffer, &mut encrypted_probes, &encrypted_luts, &pbs_indices);
-    let (tx, rx) = mpsc::sync_channel(probe.len());
-    thread::spawn(move || {
-        probe.into_par_iter().zip(luts).for_each(|(mut probes, luts)| {
-            let cur_len = probes.len();
-            probes.par_iter_mut().zip(luts).for_each(|(probe, lut)| {
-                server_key.apply_lookup_table_assign(probe, &lut);
-            });
-            let mut as_radix = RadixCiphertext::from_blocks(probes);
-            big_server_key_insde.extend_radix_with_trivial_zero_blocks_msb_assign(&mut as_radix, num_sum_blocks - cur_len);
-            tx.send(as_radix).unwrap();
-        });
-    });
-    let lut_values = rx.iter().collect_vec();
+    let lut_values = evaluate_in_worker(server_key, &big_server_key, probe, luts, num_sum_blocks).expect("LUT evaluation failed");
     let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
 
     let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
@@ -40,6 +35,28 @@ pub fn authenticate(
     (res, elapsed)
 }
 
+/// Fallible [`authenticate`], the inputs are checked with [`validate_inputs`] and a panic of the
+/// worker thread is reported as an error.
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    validate_inputs(&server_key, &probe, &luts, threshold as u64, num_sum_blocks)?;
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key.clone());
+    let start = Instant::now();
+
+    let lut_values = evaluate_in_worker(server_key, &big_server_key, probe, luts, num_sum_blocks)?;
+    let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+
+    let res = big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64);
+
+    let elapsed = start.elapsed();
+    Ok((res, elapsed))
+}
+
 pub fn authenticate_debug(
     decrypt_radix: Box<dyn Fn(&RadixCiphertext) -> u64>,
     server_key: shortint::ServerKey,
@@ -74,4 +91,179 @@ pub fn authenticate_debug(
 
     let elapsed = start.elapsed();
     (res, elapsed)
-}
\ No newline at end of file
+}
+
+/// Split of a thread budget between the features evaluated in parallel and the threads of every
+/// multi-bit PBS, a full authentication uses up to `feature_threads * pbs_threads` threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThreadBudget {
+    pub feature_threads: usize,
+    pub pbs_threads: usize,
+}
+
+impl ThreadBudget {
+    pub fn total_threads(&self) -> usize {
+        self.feature_threads * self.pbs_threads
+    }
+
+    /// Every split of `total_threads` with a distinct number of PBS threads, from one PBS thread
+    /// per feature to all threads on a single PBS at a time.
+    pub fn splits(total_threads: usize) -> Vec<Self> {
+        assert!(total_threads > 0, "the thread budget must not be empty");
+        (1..=total_threads).map(|pbs_threads| Self {
+            feature_threads: total_threads / pbs_threads,
+            pbs_threads,
+        }).collect()
+    }
+}
+
+/// How the multi-bit authenticator runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultiBitExecution {
+    pub budget: ThreadBudget,
+    /// Bit-exact outputs for the same inputs, at the cost of some PBS throughput
+    pub deterministic_execution: bool,
+}
+
+/// Sets the PBS threads and the execution mode of a multi-bit server key, e.g. to run a key of
+/// [`make_keys_multibit`](super::tfhe_functions_multibit::make_keys_multibit) with another
+/// [`MultiBitExecution`] than it was built for.
+///
+/// # Panics
+///
+/// Panics if `server_key` is not a multi-bit key.
+pub fn configure_server_key(server_key: &mut shortint::ServerKey, execution: &MultiBitExecution) {
+    match &mut server_key.bootstrapping_key {
+        ShortintBootstrappingKey::MultiBit { thread_count, deterministic_execution, .. } => {
+            *thread_count = ThreadCount(execution.budget.pbs_threads);
+            *deterministic_execution = execution.deterministic_execution;
+        }
+        ShortintBootstrappingKey::Classic(_) => panic!("not a multi-bit server key"),
+    }
+}
+
+/// Multi-bit authenticator bound to a [`MultiBitExecution`], the features are evaluated on a pool of
+/// `feature_threads` threads instead of the global rayon pool.
+pub struct MultiBitAuthenticator {
+    big_server_key: integer::ServerKey,
+    pool: ThreadPool,
+    execution: MultiBitExecution,
+}
+
+impl MultiBitAuthenticator {
+    pub fn new(mut server_key: shortint::ServerKey, execution: MultiBitExecution) -> Result<Self, ThreadPoolBuildError> {
+        configure_server_key(&mut server_key, &execution);
+        let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+        let pool = ThreadPoolBuilder::new().num_threads(execution.budget.feature_threads).build()?;
+        Ok(Self { big_server_key, pool, execution })
+    }
+
+    pub fn execution(&self) -> MultiBitExecution {
+        self.execution
+    }
+
+    /// Same computation as [`authenticate`] within the thread budget.
+    pub fn authenticate(
+        &self,
+        probe: Vec<Vec<Ciphertext>>,
+        luts: &[Vec<LookupTableOwned>],
+        threshold: usize,
+        num_sum_blocks: usize
+    ) -> (BooleanBlock, Duration) {
+        let start = Instant::now();
+        let res = self.pool.install(|| {
+            all_in_one_classic::authenticate_with_key(&self.big_server_key, probe, luts, threshold, num_sum_blocks)
+        });
+        (res, start.elapsed())
+    }
+}
+
+/// Mean authentication time of every split measured by [`tune`].
+#[derive(Clone, Debug)]
+pub struct TuningReport {
+    pub best: MultiBitExecution,
+    pub measurements: Vec<(ThreadBudget, Duration)>,
+}
+
+/// Measures every split of `total_threads` on `runs` authentications of `shape` and returns the
+/// fastest one for this machine.
+///
+/// The probes and LUTs are encrypted zeros of the shape of the pipeline, the run time does not
+/// depend on the values.
+pub fn tune(
+    client_key: &shortint::ClientKey,
+    server_key: &shortint::ServerKey,
+    shape: &PipelineShape,
+    total_threads: usize,
+    deterministic_execution: bool,
+    runs: usize,
+    engine: &mut ShortintEngine
+) -> Result<TuningReport, ThreadPoolBuildError> {
+    assert!(runs > 0, "at least one run per split is needed");
+    let probe_modulus = 1 << (2 * shape.block_length);
+    let zeros = vec![0; probe_modulus];
+    let rows = vec![vec![zeros.as_slice(); shape.num_blocks]; shape.num_tables];
+    let luts = common::generate_lookup_tables_individual_from_rows(&rows, client_key, client_key.parameters, engine);
+    let probe = rows.iter().map(|blocks| {
+        blocks.iter().map(|_| client_key.encrypt_with_message_modulus(0, MessageModulus(probe_modulus))).collect_vec()
+    }).collect_vec();
+
+    let mut measurements = Vec::new();
+    for budget in ThreadBudget::splits(total_threads) {
+        let execution = MultiBitExecution { budget, deterministic_execution };
+        let authenticator = MultiBitAuthenticator::new(server_key.clone(), execution)?;
+        let total: Duration = (0..runs).map(|_| {
+            //A threshold of 0 would turn the comparison into a trivial one
+            authenticator.authenticate(probe.clone(), &luts, 1, shape.num_blocks_sum).1
+        }).sum();
+        measurements.push((budget, total / runs as u32));
+    }
+
+    let (budget, _) = *measurements.iter().min_by_key(|(_, duration)| *duration).unwrap();
+    Ok(TuningReport {
+        best: MultiBitExecution { budget, deterministic_execution },
+        measurements,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core_crypto::biometrics::cpu::tfhe_functions_multibit::make_keys_multibit;
+    use crate::shortint::parameters::PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS;
+
+    #[test]
+    fn thread_budget_splits() {
+        let splits = ThreadBudget::splits(4);
+        assert_eq!(splits.iter().map(|b| (b.feature_threads, b.pbs_threads)).collect_vec(), [(4, 1), (2, 2), (1, 3), (1, 4)]);
+        assert!(splits.iter().all(|b| b.total_threads() <= 4));
+    }
+
+    #[test]
+    fn deterministic_execution_is_reproducible() {
+        let mut engine = ShortintEngine::new();
+        let params = PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS;
+        let execution = MultiBitExecution {
+            budget: ThreadBudget { feature_threads: 2, pbs_threads: 2 },
+            deterministic_execution: true,
+        };
+        let (client_key, server_key) = make_keys_multibit(params, execution.budget.pbs_threads, execution.deterministic_execution, &mut engine);
+        assert!(matches!(
+            server_key.bootstrapping_key,
+            ShortintBootstrappingKey::MultiBit { thread_count: ThreadCount(2), deterministic_execution: true, .. }
+        ));
+
+        let row = [3u64, 1, 0, 2];
+        let rows = vec![vec![&row[..]], vec![&row[..]]];
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine);
+        let probe = [0u64, 3].iter().map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))]).collect_vec();
+
+        let authenticator = MultiBitAuthenticator::new(server_key, execution).unwrap();
+        let (first, _) = authenticator.authenticate(probe.clone(), &luts, 5, 2);
+        let (second, _) = authenticator.authenticate(probe, &luts, 5, 2);
+
+        // 3 + 2 >= 5
+        assert!(common::decrypt_boolean_block_client_key(&first, &client_key));
+        assert_eq!(first.as_ref(), second.as_ref());
+    }
+}
@@ -0,0 +1,139 @@
+use std::time::{Duration, Instant};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use crate::integer::{BooleanBlock, IntegerCiphertext, SignedRadixCiphertext};
+use crate::{integer, shortint};
+use crate::shortint::Ciphertext;
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::validation::{validate_signed_inputs, AuthenticationError};
+
+/// Signed counterpart of [`all_in_one_classic::authenticate`](super::all_in_one_classic::authenticate)
+/// for LUTs of two's complement table values, e.g. from `bio_aux::SignedHelrTableSet`.
+///
+/// Every feature is sign extended to `num_sum_blocks` blocks, summed as a
+/// [`SignedRadixCiphertext`] and compared to the signed `threshold`.
+pub fn authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: i64,
+    num_sum_blocks: usize
+) -> (BooleanBlock, Duration) {
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+    let start = Instant::now();
+
+    let res = authenticate_with_key(&big_server_key, probe, &luts, threshold, num_sum_blocks);
+
+    let elapsed = start.elapsed();
+    (res, elapsed)
+}
+
+/// Fallible [`authenticate`], the inputs are checked with [`validate_signed_inputs`].
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: i64,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    validate_signed_inputs(&server_key, &probe, &luts, threshold, num_sum_blocks)?;
+    Ok(authenticate(server_key, probe, luts, threshold, num_sum_blocks))
+}
+
+/// Fallible [`authenticate_with_key`], the inputs are checked with [`validate_signed_inputs`].
+pub fn try_authenticate_with_key(
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: i64,
+    num_sum_blocks: usize
+) -> Result<BooleanBlock, AuthenticationError> {
+    validate_signed_inputs(&big_server_key.key, &probe, luts, threshold, num_sum_blocks)?;
+    Ok(authenticate_with_key(big_server_key, probe, luts, threshold, num_sum_blocks))
+}
+
+/// Same as [`authenticate`] with a radix server key kept by the caller.
+pub fn authenticate_with_key(
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: i64,
+    num_sum_blocks: usize
+) -> BooleanBlock {
+    assert_eq!(probe.len(), luts.len(), "probe and template have a different number of features");
+    let lut_values = probe.into_par_iter().zip(luts).map(|(mut probes, luts)| {
+        let cur_len = probes.len();
+        assert!(cur_len <= num_sum_blocks, "feature has more blocks than the sum");
+        probes.par_iter_mut().zip(luts).for_each(|(probe, lut)| {
+            big_server_key.key.apply_lookup_table_assign(probe, lut);
+            // The probe is encrypted over the whole block space but the LUT outputs a regular
+            // block, the signed comparison packs blocks and reads the sign from these moduli
+            probe.message_modulus = big_server_key.key.message_modulus;
+            probe.carry_modulus = big_server_key.key.carry_modulus;
+        });
+        let mut as_radix = SignedRadixCiphertext::from_blocks(probes);
+        //The top block of the feature holds its sign, extending costs one PBS per feature
+        big_server_key.extend_radix_with_sign_msb_assign(&mut as_radix, num_sum_blocks - cur_len);
+        as_radix
+    }).collect::<Vec<_>>();
+
+    let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+    big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::{ClientKey, MessageModulus};
+
+    // The minimum of the first table is in the middle, the one of the second table in the first
+    // row, none of them at the end of the first row where the offsetting looks for it
+    const TABLES: [[[i32; 4]; 4]; 2] = [
+        [[5, 1, -2, 0], [1, 6, -7, -3], [-2, -3, 4, 1], [0, -9, 1, 3]],
+        [[-8, 2, 1, 7], [2, 5, 0, -1], [1, 0, 6, 2], [-6, -1, 2, 4]],
+    ];
+
+    #[test]
+    fn signed_scores_without_offset() {
+        let config = config::Config {
+            data_set_name: "signed",
+            num_blocks: 0,
+            num_blocks_sum: 0,
+            block_length: 2,
+            num_tables: TABLES.len(),
+            threshold: 3,
+            probe_encoding: config::ProbeEncoding::Block,
+        };
+        let tables = TABLES.iter().map(|table| table.iter().map(|row| row.to_vec()).collect()).collect();
+        let table_set = bio_aux::SignedHelrTableSet::from_tables(tables, &config);
+
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+        let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+        let modulus = MessageModulus(1 << (2 * config.block_length));
+
+        let template = [1u8, 0];
+        let rows = table_set.lut_rows(&template);
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine);
+
+        for probe_x in 0..4u8 {
+            for probe_y in 0..4u8 {
+                let probe = [probe_x, probe_y];
+                let score: i64 = (0..TABLES.len())
+                    .map(|t| TABLES[t][template[t] as usize][probe[t] as usize] as i64)
+                    .sum();
+
+                let encrypted_probe = rows.iter().zip(probe.iter()).map(|(blocks, &p)| {
+                    blocks.iter().map(|_| client_key.encrypt_with_message_modulus(p as u64, modulus)).collect()
+                }).collect();
+                let decision = try_authenticate_with_key(&big_server_key, encrypted_probe, &luts, table_set.threshold(), table_set.num_blocks_sum()).unwrap();
+
+                let decrypted = common::decrypt_boolean_block_client_key(&decision, &client_key);
+                assert_eq!(decrypted, score >= config.threshold, "probe {probe:?} with score {score}");
+            }
+        }
+    }
+}
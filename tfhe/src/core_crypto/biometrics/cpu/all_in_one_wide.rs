@@ -0,0 +1,159 @@
+//! Authentication for quantization alphabets larger than the message and carry space of a block.
+//!
+//! A probe value is encrypted as a low digit of [`common::wide_low_digit_count`] values and a high
+//! digit, and the HELR lookup is evaluated as a two level tree: for every high digit `h` a clear
+//! PBS on the high digit selects `h` by moving the low digit into the upper half of the block
+//! space, then the encrypted LUTs of `h` from [`common::generate_wide_lookup_tables_from_rows`]
+//! are applied. Only the LUTs of the actual high digit output something, every other selection
+//! adds 0 to the score.
+use std::time::{Duration, Instant};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use crate::core_crypto::biometrics::common;
+use crate::integer::{BooleanBlock, IntegerCiphertext, RadixCiphertext};
+use crate::{integer, shortint};
+use crate::shortint::{Ciphertext, MessageModulus};
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::validation::{validate_wide_inputs, AuthenticationError};
+
+/// Encrypted digits of a feature, the low digit first.
+pub type WideProbe = Vec<(Ciphertext, Ciphertext)>;
+
+/// Encrypts every feature of `probe` as its low and high digit.
+///
+/// # Panics
+///
+/// Panics if a value does not fit the two digits.
+pub fn encrypt_probe(probe: &[u64], client_key: &shortint::ClientKey) -> WideProbe {
+    let block_space = client_key.parameters.message_modulus().0 * client_key.parameters.carry_modulus().0;
+    let low_count = common::wide_low_digit_count(client_key.parameters.message_modulus(), client_key.parameters.carry_modulus());
+    probe.iter().map(|&value| {
+        assert!((value as usize) < low_count * block_space, "probe value {value} does not fit two blocks");
+        let low = client_key.encrypt_with_message_modulus(value % low_count as u64, MessageModulus(low_count));
+        let high = client_key.encrypt_with_message_modulus(value / low_count as u64, MessageModulus(block_space));
+        (low, high)
+    }).collect()
+}
+
+/// Counterpart of [`all_in_one_classic::authenticate`](super::all_in_one_classic::authenticate)
+/// for the LUTs of [`common::generate_wide_lookup_tables_from_rows`].
+pub fn authenticate(
+    server_key: shortint::ServerKey,
+    probe: WideProbe,
+    luts: Vec<Vec<Vec<LookupTableOwned>>>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> (BooleanBlock, Duration) {
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+    let start = Instant::now();
+
+    let res = authenticate_with_key(&big_server_key, probe, &luts, threshold, num_sum_blocks);
+
+    let elapsed = start.elapsed();
+    (res, elapsed)
+}
+
+/// Fallible [`authenticate`], the inputs are checked with [`validate_wide_inputs`].
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    probe: WideProbe,
+    luts: Vec<Vec<Vec<LookupTableOwned>>>,
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, Duration), AuthenticationError> {
+    validate_wide_inputs(&server_key, &probe, &luts, threshold as u64, num_sum_blocks)?;
+    Ok(authenticate(server_key, probe, luts, threshold, num_sum_blocks))
+}
+
+/// Fallible [`authenticate_with_key`], the inputs are checked with [`validate_wide_inputs`].
+pub fn try_authenticate_with_key(
+    big_server_key: &integer::ServerKey,
+    probe: WideProbe,
+    luts: &[Vec<Vec<LookupTableOwned>>],
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<BooleanBlock, AuthenticationError> {
+    validate_wide_inputs(&big_server_key.key, &probe, luts, threshold as u64, num_sum_blocks)?;
+    Ok(authenticate_with_key(big_server_key, probe, luts, threshold, num_sum_blocks))
+}
+
+/// Same as [`authenticate`] with a radix server key kept by the caller.
+pub fn authenticate_with_key(
+    big_server_key: &integer::ServerKey,
+    probe: WideProbe,
+    luts: &[Vec<Vec<LookupTableOwned>>],
+    threshold: usize,
+    num_sum_blocks: usize
+) -> BooleanBlock {
+    assert_eq!(probe.len(), luts.len(), "probe and template have a different number of features");
+    let server_key = &big_server_key.key;
+    let low_count = common::wide_low_digit_count(server_key.message_modulus, server_key.carry_modulus) as u64;
+
+    //One radix number per feature and high digit, the sum takes care of the mostly zero terms
+    let lut_values = probe.into_par_iter().zip(luts).flat_map(|((low, high), feature_luts)| {
+        feature_luts.par_iter().enumerate().map(|(high_digit, block_luts)| {
+            assert!(block_luts.len() <= num_sum_blocks, "feature has more blocks than the sum");
+            let select = server_key.generate_lookup_table(|x| if x == high_digit as u64 { low_count } else { 0 });
+            let mut selected = server_key.apply_lookup_table(&high, &select);
+            server_key.unchecked_add_assign(&mut selected, &low);
+
+            let blocks = block_luts.par_iter().map(|lut| {
+                let mut block = server_key.apply_lookup_table(&selected, lut);
+                // The digits are encrypted over the whole block space but the LUT outputs a
+                // regular block, the comparison packs blocks according to these moduli
+                block.message_modulus = server_key.message_modulus;
+                block.carry_modulus = server_key.carry_modulus;
+                block
+            }).collect::<Vec<_>>();
+            let mut as_radix = RadixCiphertext::from_blocks(blocks);
+            big_server_key.extend_radix_with_trivial_zero_blocks_msb_assign(&mut as_radix, num_sum_blocks - block_luts.len());
+            as_radix
+        }).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+
+    let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+    big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::ClientKey;
+
+    const BINS: u64 = 64;
+    const BLOCK_LENGTH: usize = 2;
+    const NUM_SUM_BLOCKS: usize = 4;
+
+    // Row of the template value of each feature, two output blocks per entry
+    fn score_row(feature: u64, probe_y: u64) -> u64 {
+        (probe_y * (feature + 3) + 7 * feature) % 16
+    }
+
+    #[test]
+    fn sixty_four_bins_with_2_2_parameters() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+        let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+
+        let values: Vec<Vec<Vec<u64>>> = (0..2u64).map(|feature| {
+            (0..2).map(|block| {
+                (0..BINS).map(|y| (score_row(feature, y) >> (BLOCK_LENGTH * block)) & 0b11).collect()
+            }).collect()
+        }).collect();
+        let rows: Vec<Vec<&[u64]>> = values.iter().map(|blocks| blocks.iter().map(Vec::as_slice).collect()).collect();
+        let luts = common::generate_wide_lookup_tables_from_rows(&rows, &client_key, client_key.parameters, &mut engine);
+
+        for probe in [[0, 63], [9, 40], [31, 32], [57, 8]] {
+            let score = score_row(0, probe[0]) + score_row(1, probe[1]);
+            let encrypted_probe = encrypt_probe(&probe, &client_key);
+
+            for threshold in [score, score + 1] {
+                let decision = try_authenticate_with_key(&big_server_key, encrypted_probe.clone(), &luts, threshold as usize, NUM_SUM_BLOCKS).unwrap();
+                let decrypted = common::decrypt_boolean_block_client_key(&decision, &client_key);
+                assert_eq!(decrypted, threshold == score, "probe {probe:?} with score {score} against {threshold}");
+            }
+        }
+    }
+}
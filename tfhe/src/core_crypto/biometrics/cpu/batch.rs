@@ -0,0 +1,220 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use crate::integer::BooleanBlock;
+use crate::{integer, shortint};
+use crate::shortint::Ciphertext;
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic;
+use crate::core_crypto::biometrics::cpu::validation::AuthenticationError;
+use crate::core_crypto::biometrics::common::SeededLookupTables;
+
+/// Encrypted LUTs of an enrolled template, shared by all requests against that template.
+pub struct Enrollment {
+    pub luts: Vec<Vec<LookupTableOwned>>,
+    pub threshold: usize,
+    pub num_sum_blocks: usize,
+}
+
+impl Enrollment {
+    /// Decompresses a template stored as [`SeededLookupTables`], done once when it is loaded.
+    pub fn from_seeded(luts: SeededLookupTables, threshold: usize, num_sum_blocks: usize) -> Self {
+        Self {
+            luts: luts.decompress(),
+            threshold,
+            num_sum_blocks,
+        }
+    }
+}
+
+pub struct BatchRequest {
+    pub enrollment: Arc<Enrollment>,
+    pub probe: Vec<Vec<Ciphertext>>,
+}
+
+pub struct BatchReport {
+    /// Decisions in the order of the requests, a malformed request gets its error instead
+    pub decisions: Vec<Result<BooleanBlock, AuthenticationError>>,
+    /// Time from the submission of the batch until each decision was ready, in request order
+    pub latencies: Vec<Duration>,
+    /// Wall clock time of the whole batch
+    pub elapsed: Duration,
+}
+
+impl BatchReport {
+    /// Authentications per second.
+    pub fn throughput(&self) -> f64 {
+        self.decisions.len() as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Nearest-rank latency percentile, `p` in `[0, 100]`.
+    pub fn latency_percentile(&self, p: f64) -> Duration {
+        assert!((0.0..=100.0).contains(&p), "percentile must be in [0, 100]");
+        let mut sorted = self.latencies.clone();
+        sorted.sort_unstable();
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+    }
+}
+
+/// Long lived authentication server, holds the keys once and evaluates batches of requests on
+/// a bounded thread pool instead of a fresh thread per request like [`all_in_one_classic::authenticate`].
+pub struct AuthenticationServer {
+    big_server_key: integer::ServerKey,
+    pool: ThreadPool,
+}
+
+impl AuthenticationServer {
+    /// `num_threads` bounds the threads used by all requests together, 0 uses rayon's default.
+    pub fn new(server_key: shortint::ServerKey, num_threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+        let pool = ThreadPoolBuilder::new().num_threads(num_threads).build()?;
+        Ok(Self { big_server_key, pool })
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Same computation as [`all_in_one_classic::authenticate`] with the keys of the server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the probe does not match the enrollment or the parameters of the server key.
+    pub fn authenticate(&self, enrollment: &Enrollment, probe: Vec<Vec<Ciphertext>>) -> BooleanBlock {
+        self.try_authenticate(enrollment, probe)
+            .unwrap_or_else(|error| panic!("invalid authentication request: {error}"))
+    }
+
+    /// Fallible [`Self::authenticate`], the request is checked with
+    /// [`validate_inputs`](crate::core_crypto::biometrics::cpu::validation::validate_inputs).
+    pub fn try_authenticate(&self, enrollment: &Enrollment, probe: Vec<Vec<Ciphertext>>) -> Result<BooleanBlock, AuthenticationError> {
+        self.pool.install(|| self.evaluate(enrollment, probe))
+    }
+
+    /// Evaluates a queue of requests, requests and their LUT evaluations share the thread pool.
+    ///
+    /// Every request is validated on its own, a malformed one gets its error in the report
+    /// without affecting the others.
+    pub fn try_process_batch(&self, requests: Vec<BatchRequest>) -> BatchReport {
+        let start = Instant::now();
+        let (decisions, latencies): (Vec<_>, Vec<_>) = self.pool.install(|| {
+            requests.into_par_iter().map(|request| {
+                let decision = self.evaluate(&request.enrollment, request.probe);
+                (decision, start.elapsed())
+            }).unzip()
+        });
+
+        BatchReport {
+            decisions,
+            latencies,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    fn evaluate(&self, enrollment: &Enrollment, probe: Vec<Vec<Ciphertext>>) -> Result<BooleanBlock, AuthenticationError> {
+        all_in_one_classic::try_authenticate_with_key(&self.big_server_key, probe, &enrollment.luts, enrollment.threshold, enrollment.num_sum_blocks)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use itertools::Itertools;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::{ClientKey, MessageModulus};
+
+    // Two features with a single output block each, indexed [template][probe]
+    const TABLES: [[[u64; 4]; 4]; 2] = [
+        [[3, 2, 1, 0], [2, 3, 2, 1], [1, 2, 3, 2], [0, 1, 2, 3]],
+        [[3, 1, 0, 0], [1, 3, 1, 0], [0, 1, 3, 1], [0, 0, 1, 3]],
+    ];
+    const THRESHOLD: usize = 5;
+    const NUM_SUM_BLOCKS: usize = 2;
+
+    fn enroll(client_key: &ClientKey, template: [usize; 2], engine: &mut ShortintEngine) -> Arc<Enrollment> {
+        let rows: Vec<Vec<&[u64]>> = TABLES.iter().zip(template).map(|(table, x)| vec![&table[x][..]]).collect();
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, client_key, client_key.parameters, engine);
+        Arc::new(Enrollment { luts, threshold: THRESHOLD, num_sum_blocks: NUM_SUM_BLOCKS })
+    }
+
+    fn encrypt_probe(client_key: &ClientKey, probe: &[u64]) -> Vec<Vec<Ciphertext>> {
+        probe.iter().map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))]).collect()
+    }
+
+    #[test]
+    fn batch_decisions_in_request_order() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server = AuthenticationServer::new(shortint::ServerKey::new(&client_key), 2).unwrap();
+
+        let first = enroll(&client_key, [1, 2], &mut engine);
+        let second = enroll(&client_key, [3, 0], &mut engine);
+        let cases = [
+            // 3 + 3
+            (&first, [1, 2], true),
+            // 1 + 0
+            (&first, [3, 0], false),
+            // 3 + 3
+            (&second, [3, 0], true),
+            // 1 + 0
+            (&second, [1, 2], false),
+        ];
+        let requests = cases.iter().map(|(enrollment, probe, _)| BatchRequest {
+            enrollment: Arc::clone(enrollment),
+            probe: encrypt_probe(&client_key, probe),
+        }).collect_vec();
+
+        let report = server.try_process_batch(requests);
+
+        assert_eq!(report.latencies.len(), cases.len());
+        let decisions = report.decisions.iter().map(|decision| {
+            common::decrypt_boolean_block_client_key(decision.as_ref().unwrap(), &client_key)
+        }).collect_vec();
+        assert_eq!(decisions, cases.map(|(_, _, expected)| expected));
+    }
+
+    #[test]
+    fn malformed_request_does_not_fail_the_batch() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server = AuthenticationServer::new(shortint::ServerKey::new(&client_key), 2).unwrap();
+        let enrollment = enroll(&client_key, [1, 2], &mut engine);
+
+        let requests = vec![
+            BatchRequest { enrollment: Arc::clone(&enrollment), probe: encrypt_probe(&client_key, &[1]) },
+            BatchRequest { enrollment: Arc::clone(&enrollment), probe: encrypt_probe(&client_key, &[1, 2]) },
+        ];
+        let report = server.try_process_batch(requests);
+
+        assert_eq!(report.latencies.len(), 2);
+        assert_eq!(report.decisions[0].as_ref().err(), Some(&AuthenticationError::FeatureCountMismatch { probe: 1, template: 2 }));
+        assert!(common::decrypt_boolean_block_client_key(report.decisions[1].as_ref().unwrap(), &client_key));
+
+        // Two blocks for the first feature while its LUTs only have one
+        let mut probe = encrypt_probe(&client_key, &[1, 2]);
+        let extra_block = probe[0][0].clone();
+        probe[0].push(extra_block);
+        assert_eq!(
+            server.try_authenticate(&enrollment, probe).err(),
+            Some(AuthenticationError::BlockCountMismatch { feature: 0, probe: 2, template: 1 })
+        );
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        let report = BatchReport {
+            decisions: Vec::new(),
+            latencies: [40, 10, 30, 20, 50].into_iter().map(Duration::from_millis).collect(),
+            elapsed: Duration::from_millis(50),
+        };
+
+        assert_eq!(report.latency_percentile(0.0), Duration::from_millis(10));
+        assert_eq!(report.latency_percentile(50.0), Duration::from_millis(30));
+        assert_eq!(report.latency_percentile(90.0), Duration::from_millis(50));
+        assert_eq!(report.latency_percentile(100.0), Duration::from_millis(50));
+    }
+}
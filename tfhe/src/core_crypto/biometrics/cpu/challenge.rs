@@ -0,0 +1,107 @@
+use crate::integer::BooleanBlock;
+use crate::shortint;
+use crate::shortint::Ciphertext;
+
+/// Number of nonce bits a decision is bound to, one block per bit.
+pub const NONCE_BITS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChallengeError {
+    /// The decision is bound to another nonce, e.g. it is replayed from an earlier request
+    Stale,
+    /// The blocks do not carry the same decision or have an unexpected count
+    Malformed,
+}
+
+/// Encrypts the low [`NONCE_BITS`] bits of `nonce`, one bit per block.
+///
+/// The nonce should be drawn at random by the client for every request and kept to verify the
+/// answer with [`verify_decision`].
+pub fn encrypt_nonce(nonce: u64, client_key: &shortint::ClientKey) -> Vec<Ciphertext> {
+    (0..NONCE_BITS)
+        .map(|i| client_key.encrypt((nonce >> i) & 1))
+        .collect()
+}
+
+/// Binds an encrypted decision to an encrypted nonce, block `i` encrypts
+/// `decision + 2 * nonce_bit_i`.
+///
+/// This only detects the replay of an answer to another request. It does not authenticate the
+/// answer: anyone who sees the encrypted nonce of a request can answer it with
+/// `trivial(1) + 2 * nonce_bit` without any key, so the transport has to authenticate the bound
+/// decision, e.g. with a MAC over the nonce and the decision.
+pub fn bind_decision(
+    server_key: &shortint::ServerKey,
+    decision: &BooleanBlock,
+    encrypted_nonce: &[Ciphertext],
+) -> Vec<Ciphertext> {
+    assert!(
+        server_key.message_modulus.0 >= 4,
+        "binding a decision requires at least 2 bits of message"
+    );
+    encrypted_nonce
+        .iter()
+        .map(|nonce_bit| {
+            let shifted = server_key.unchecked_scalar_mul(nonce_bit, 2);
+            server_key.unchecked_add(decision.as_ref(), &shifted)
+        })
+        .collect()
+}
+
+/// Decrypts a bound decision and checks that it was computed for `nonce`.
+pub fn verify_decision(
+    bound: &[Ciphertext],
+    nonce: u64,
+    client_key: &shortint::ClientKey,
+) -> Result<bool, ChallengeError> {
+    if bound.len() != NONCE_BITS {
+        return Err(ChallengeError::Malformed);
+    }
+
+    let message_modulus = client_key.parameters.message_modulus().0 as u64;
+    let mut decision = None;
+    let mut stale = false;
+    for (i, block) in bound.iter().enumerate() {
+        let value = client_key.decrypt_message_and_carry(block) % message_modulus;
+        let block_decision = value & 1 == 1;
+        if *decision.get_or_insert(block_decision) != block_decision {
+            return Err(ChallengeError::Malformed);
+        }
+        if (value >> 1) != (nonce >> i) & 1 {
+            stale = true;
+        }
+    }
+
+    if stale {
+        Err(ChallengeError::Stale)
+    } else {
+        Ok(decision.unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shortint::gen_keys;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+
+    #[test]
+    fn bound_decision_rejects_replay() {
+        let (client_key, server_key) = gen_keys(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let decision = BooleanBlock::new_unchecked(client_key.encrypt(1));
+
+        let nonce = 0b1010_0110_0011_1001;
+        let bound = bind_decision(&server_key, &decision, &encrypt_nonce(nonce, &client_key));
+        assert_eq!(verify_decision(&bound, nonce, &client_key), Ok(true));
+
+        let other_nonce = nonce ^ 0b100;
+        assert_eq!(
+            verify_decision(&bound, other_nonce, &client_key),
+            Err(ChallengeError::Stale)
+        );
+        assert_eq!(
+            verify_decision(&bound[1..], nonce, &client_key),
+            Err(ChallengeError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Release of the decision to an access controller, e.g. a door, instead of the client.
+//!
+//! The controller owns a shortint key of its own. A [`DecisionReleaseKey`] lets the server key
+//! switch the decision block from the client key to the controller key, every other ciphertext of
+//! the pipeline (probes, LUTs, partial scores and sums) stays under the client key, which the
+//! controller cannot decrypt.
+//!
+//! # Trust
+//!
+//! The release key is generated from both secret keys, so it is created during provisioning by a
+//! party trusted by the client and the controller. The key switch works on any ciphertext of the
+//! client key: the controller only learns the decision as long as the server does not switch and
+//! forward anything else, i.e. as long as the server and the controller do not collude.
+use std::time::{Duration, Instant};
+use crate::integer::BooleanBlock;
+use crate::shortint;
+use crate::shortint::{Ciphertext, KeySwitchingKey};
+use crate::shortint::parameters::ShortintKeySwitchingParameters;
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic;
+
+/// Key switching key from the client key to a controller key, held by the server.
+#[derive(Clone)]
+pub struct DecisionReleaseKey {
+    key_switching_key: KeySwitchingKey,
+}
+
+impl DecisionReleaseKey {
+    /// Key switches with the decomposition of the controller's own key switching key, see the
+    /// [module documentation](self) for who should call this.
+    pub fn new(
+        client_keys: (&shortint::ClientKey, &shortint::ServerKey),
+        controller_keys: (&shortint::ClientKey, &shortint::ServerKey)
+    ) -> Self {
+        let controller_params = controller_keys.0.parameters;
+        let params = ShortintKeySwitchingParameters::new(controller_params.ks_base_log(), controller_params.ks_level());
+        Self {
+            key_switching_key: KeySwitchingKey::new(client_keys, controller_keys, params),
+        }
+    }
+
+    /// Moves the decision block to the controller key.
+    pub fn release(&self, decision: &BooleanBlock) -> Ciphertext {
+        self.key_switching_key.cast(decision.as_ref())
+    }
+}
+
+/// Decrypts a decision of [`DecisionReleaseKey::release`] with the controller key.
+pub fn decrypt_released_decision(released: &Ciphertext, controller_key: &shortint::ClientKey) -> bool {
+    controller_key.decrypt(released) == 1
+}
+
+/// [`all_in_one_classic::authenticate`] with the decision released to the controller of
+/// `release_key`, the client is not able to decrypt it anymore.
+pub fn authenticate_for_controller(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize,
+    release_key: &DecisionReleaseKey
+) -> (Ciphertext, Duration) {
+    let (decision, elapsed) = all_in_one_classic::authenticate(server_key, probe, luts, threshold, num_sum_blocks);
+    let start = Instant::now();
+    let released = release_key.release(&decision);
+    (released, elapsed + start.elapsed())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::{PARAM_MESSAGE_1_CARRY_1_KS_PBS, PARAM_MESSAGE_2_CARRY_2_KS_PBS};
+    use crate::shortint::{ClientKey, MessageModulus, ServerKey};
+
+    const ROWS: [[u64; 4]; 2] = [[3, 1, 0, 2], [0, 2, 3, 1]];
+    const THRESHOLD: usize = 5;
+    const NUM_SUM_BLOCKS: usize = 2;
+
+    #[test]
+    fn released_decisions_decrypt_with_the_controller_key() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = ServerKey::new(&client_key);
+
+        let rows: Vec<Vec<&[u64]>> = ROWS.iter().map(|row| vec![&row[..]]).collect();
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine);
+
+        // Same parameters as the client and a smaller message space
+        for controller_params in [PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_1_CARRY_1_KS_PBS] {
+            let controller_key = ClientKey::new(controller_params);
+            let controller_server_key = ServerKey::new(&controller_key);
+            let release_key = DecisionReleaseKey::new((&client_key, &server_key), (&controller_key, &controller_server_key));
+
+            // 3 + 3 >= 5, 1 + 0 < 5
+            for (probe, accepted) in [([0u64, 2], true), ([1, 0], false)] {
+                let encrypted_probe = probe.iter().map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))]).collect();
+                let (released, _) = authenticate_for_controller(server_key.clone(), encrypted_probe, luts.clone(), THRESHOLD, NUM_SUM_BLOCKS, &release_key);
+
+                assert_eq!(released.ct.lwe_size(), controller_key.large_lwe_secret_key().lwe_dimension().to_lwe_size());
+                assert_eq!(decrypt_released_decision(&released, &controller_key), accepted, "probe {probe:?} for {controller_params:?}");
+            }
+        }
+    }
+}
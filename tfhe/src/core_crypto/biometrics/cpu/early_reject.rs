@@ -0,0 +1,264 @@
+//! Two phase authentication rejecting most non-mated probes before all features are evaluated.
+//!
+//! The first phase only applies the LUTs of [`EarlyReject::features`] and compares their partial
+//! sum against [`EarlyReject::lower_bound`]. The key holder decrypts this intermediate decision in
+//! an interactive round and only asks for the second phase, which evaluates the remaining features
+//! on top of the kept partial sum, if the bound was reached.
+//!
+//! # Leakage
+//!
+//! Unlike the single phase authenticators, the server learns one bit about every probe: whether
+//! its partial score reached the lower bound, from the presence of the second round as well as
+//! from the run time. With the bound of [`lossless_lower_bound`] an early rejection implies a
+//! rejection, so the server learns the final decision of every early rejected probe. Higher
+//! bounds save more PBS but also reject probes that would have been accepted, and the key holder
+//! learns the partial decision of every probe it handles.
+use std::time::{Duration, Instant};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use crate::integer::{BooleanBlock, IntegerCiphertext, RadixCiphertext};
+use crate::{integer, shortint};
+use crate::shortint::{Ciphertext, MessageModulus};
+use crate::shortint::server_key::LookupTableOwned;
+use crate::core_crypto::biometrics::cpu::validation::{validate_inputs, validate_template, AuthenticationError};
+
+/// Features of the first phase and the bound their partial sum has to reach.
+#[derive(Clone, Debug)]
+pub struct EarlyReject {
+    pub features: Vec<usize>,
+    pub lower_bound: usize,
+}
+
+/// State the server keeps between the two phases.
+pub struct PendingAuthentication {
+    partial_sum: RadixCiphertext,
+    // Probe blocks of the features not evaluated yet, with their index
+    remaining: Vec<(usize, Vec<Ciphertext>)>,
+}
+
+impl PendingAuthentication {
+    /// Number of LUT evaluations of the second phase, i.e. the PBS an early rejection saves.
+    pub fn remaining_lut_count(&self) -> usize {
+        self.remaining.iter().map(|(_, probes)| probes.len()).sum()
+    }
+}
+
+/// Largest score of every feature of `rows`, whose blocks hold `message_modulus` values each.
+pub fn max_scores(rows: &[Vec<&[u64]>], message_modulus: MessageModulus) -> Vec<u64> {
+    rows.iter().map(|blocks| {
+        (0..blocks[0].len()).map(|y| {
+            blocks.iter().rev().fold(0, |score, row| score * message_modulus.0 as u64 + row[y])
+        }).max().unwrap()
+    }).collect()
+}
+
+/// Lowest bound for `features` that never rejects a probe reaching `threshold`, as the features of
+/// the second phase add at most their maximum score.
+pub fn lossless_lower_bound(max_scores: &[u64], features: &[usize], threshold: usize) -> usize {
+    let second_phase_max: u64 = (0..max_scores.len()).filter(|feature| !features.contains(feature)).map(|feature| max_scores[feature]).sum();
+    threshold.saturating_sub(second_phase_max as usize)
+}
+
+fn evaluate_features(
+    big_server_key: &integer::ServerKey,
+    features: Vec<(usize, Vec<Ciphertext>)>,
+    luts: &[Vec<LookupTableOwned>],
+    num_sum_blocks: usize
+) -> Vec<RadixCiphertext> {
+    features.into_par_iter().map(|(feature, mut probes)| {
+        let cur_len = probes.len();
+        assert!(cur_len <= num_sum_blocks, "feature has more blocks than the sum");
+        probes.par_iter_mut().zip(&luts[feature]).for_each(|(probe, lut)| {
+            big_server_key.key.apply_lookup_table_assign(probe, lut);
+            // The probe is encrypted over the whole block space but the LUT outputs a regular
+            // block, the comparison of the partial sum packs blocks according to these moduli
+            probe.message_modulus = big_server_key.key.message_modulus;
+            probe.carry_modulus = big_server_key.key.carry_modulus;
+        });
+        let mut as_radix = RadixCiphertext::from_blocks(probes);
+        big_server_key.extend_radix_with_trivial_zero_blocks_msb_assign(&mut as_radix, num_sum_blocks - cur_len);
+        as_radix
+    }).collect()
+}
+
+/// Evaluates the features of `early_reject` and compares their sum against its lower bound.
+///
+/// The returned decision has to be decrypted by the key holder, the pending state is passed to
+/// [`second_phase`] if it is true and dropped otherwise.
+pub fn first_phase(
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: &[Vec<LookupTableOwned>],
+    early_reject: &EarlyReject,
+    num_sum_blocks: usize
+) -> (BooleanBlock, PendingAuthentication) {
+    assert_eq!(probe.len(), luts.len(), "probe and template have a different number of features");
+    assert!(early_reject.features.iter().all(|&feature| feature < probe.len()), "first phase feature out of range");
+
+    let (first, remaining): (Vec<_>, Vec<_>) = probe.into_iter().enumerate()
+        .partition(|(feature, _)| early_reject.features.contains(feature));
+
+    let lut_values = evaluate_features(big_server_key, first, luts, num_sum_blocks);
+    let partial_sum = match big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values) {
+        Some(sum) => sum,
+        //No first phase feature, every probe goes on to the second phase
+        None => big_server_key.create_trivial_zero_radix(num_sum_blocks),
+    };
+    let decision = big_server_key.unchecked_scalar_ge_parallelized(&partial_sum, early_reject.lower_bound as u64);
+
+    (decision, PendingAuthentication { partial_sum, remaining })
+}
+
+/// Fallible [`first_phase`], the inputs are checked with [`validate_inputs`] against the lower
+/// bound and the features of `early_reject` against the probe.
+pub fn try_first_phase(
+    big_server_key: &integer::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: &[Vec<LookupTableOwned>],
+    early_reject: &EarlyReject,
+    num_sum_blocks: usize
+) -> Result<(BooleanBlock, PendingAuthentication), AuthenticationError> {
+    validate_early_reject(&big_server_key.key, &probe, luts, early_reject, num_sum_blocks)?;
+    Ok(first_phase(big_server_key, probe, luts, early_reject, num_sum_blocks))
+}
+
+fn validate_early_reject(
+    server_key: &shortint::ServerKey,
+    probe: &[Vec<Ciphertext>],
+    luts: &[Vec<LookupTableOwned>],
+    early_reject: &EarlyReject,
+    num_sum_blocks: usize
+) -> Result<(), AuthenticationError> {
+    validate_inputs(server_key, probe, luts, early_reject.lower_bound as u64, num_sum_blocks)?;
+    match early_reject.features.iter().find(|&&feature| feature >= probe.len()) {
+        Some(&feature) => Err(AuthenticationError::FirstPhaseFeatureOutOfRange { feature, num_features: probe.len() }),
+        None => Ok(()),
+    }
+}
+
+/// Adds the remaining features to the partial sum of [`first_phase`] and compares the total
+/// against `threshold`, the decision is the one of the single phase authenticators.
+pub fn second_phase(
+    big_server_key: &integer::ServerKey,
+    pending: PendingAuthentication,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: usize,
+    num_sum_blocks: usize
+) -> BooleanBlock {
+    let mut lut_values = evaluate_features(big_server_key, pending.remaining, luts, num_sum_blocks);
+    lut_values.push(pending.partial_sum);
+
+    let sum = big_server_key.unchecked_sum_ciphertexts_vec_parallelized(lut_values).unwrap();
+    big_server_key.unchecked_scalar_ge_parallelized(&sum, threshold as u64)
+}
+
+/// Fallible [`second_phase`], `threshold` and the LUTs are checked with [`validate_template`].
+pub fn try_second_phase(
+    big_server_key: &integer::ServerKey,
+    pending: PendingAuthentication,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: usize,
+    num_sum_blocks: usize
+) -> Result<BooleanBlock, AuthenticationError> {
+    validate_template(&big_server_key.key, luts, threshold as u64, num_sum_blocks)?;
+    Ok(second_phase(big_server_key, pending, luts, threshold, num_sum_blocks))
+}
+
+/// Both phases with the interactive round played by `key_holder`, which gets the intermediate
+/// decision and returns whether to continue. Returns `None` for an early rejection.
+///
+/// Works with classic and multi-bit server keys alike.
+pub fn authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize,
+    early_reject: &EarlyReject,
+    key_holder: impl FnOnce(&BooleanBlock) -> bool
+) -> (Option<BooleanBlock>, Duration) {
+    let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+    let start = Instant::now();
+
+    let (partial_decision, pending) = first_phase(&big_server_key, probe, &luts, early_reject, num_sum_blocks);
+    let res = key_holder(&partial_decision).then(|| {
+        second_phase(&big_server_key, pending, &luts, threshold, num_sum_blocks)
+    });
+
+    let elapsed = start.elapsed();
+    (res, elapsed)
+}
+
+/// Fallible [`authenticate`], the inputs are checked against both the lower bound and
+/// `threshold` before the first phase.
+pub fn try_authenticate(
+    server_key: shortint::ServerKey,
+    probe: Vec<Vec<Ciphertext>>,
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize,
+    early_reject: &EarlyReject,
+    key_holder: impl FnOnce(&BooleanBlock) -> bool
+) -> Result<(Option<BooleanBlock>, Duration), AuthenticationError> {
+    validate_early_reject(&server_key, &probe, &luts, early_reject, num_sum_blocks)?;
+    validate_template(&server_key, &luts, threshold as u64, num_sum_blocks)?;
+    Ok(authenticate(server_key, probe, luts, threshold, num_sum_blocks, early_reject, key_holder))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+    use crate::shortint::ClientKey;
+
+    const NUM_SUM_BLOCKS: usize = 3;
+    const THRESHOLD: usize = 20;
+
+    // Score of a probe value for the template value of each feature, two blocks per score
+    const ROWS: [[u64; 4]; 3] = [[9, 2, 0, 1], [1, 8, 3, 0], [0, 2, 7, 1]];
+
+    #[test]
+    fn early_reject_with_lossless_bound() {
+        let mut engine = ShortintEngine::new();
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+        let big_server_key = integer::ServerKey::new_radix_server_key_from_shortint(server_key);
+        let message_modulus = client_key.parameters.message_modulus();
+
+        let blocks: Vec<Vec<Vec<u64>>> = ROWS.iter().map(|row| {
+            (0..2).map(|block| row.iter().map(|score| (score >> (2 * block)) & 0b11).collect()).collect()
+        }).collect();
+        let rows: Vec<Vec<&[u64]>> = blocks.iter().map(|row| row.iter().map(Vec::as_slice).collect()).collect();
+        assert_eq!(max_scores(&rows, message_modulus), [9, 8, 7]);
+
+        let early_reject = EarlyReject { features: vec![0, 1], lower_bound: lossless_lower_bound(&[9, 8, 7], &[0, 1], THRESHOLD) };
+        assert_eq!(early_reject.lower_bound, 13);
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, client_key.parameters, &mut engine);
+
+        // 9 + 8 continues and 9 + 8 + 7 is accepted, 9 + 3 is rejected early, 9 + 8 + 2 after both phases
+        for (probe, continues, accepted) in [([0, 1, 2], true, true), ([0, 2, 2], false, false), ([0, 1, 1], true, false)] {
+            let encrypted_probe = probe.iter().zip(&rows).map(|(&p, blocks)| {
+                blocks.iter().map(|_| client_key.encrypt_with_message_modulus(p, MessageModulus(16))).collect()
+            }).collect();
+
+            let (partial_decision, pending) = try_first_phase(&big_server_key, encrypted_probe, &luts, &early_reject, NUM_SUM_BLOCKS).unwrap();
+            assert_eq!(common::decrypt_boolean_block_client_key(&partial_decision, &client_key), continues, "probe {probe:?}");
+            assert_eq!(pending.remaining_lut_count(), 2);
+
+            if continues {
+                let decision = try_second_phase(&big_server_key, pending, &luts, THRESHOLD, NUM_SUM_BLOCKS).unwrap();
+                assert_eq!(common::decrypt_boolean_block_client_key(&decision, &client_key), accepted, "probe {probe:?}");
+            }
+        }
+
+        let encrypted_probe = rows.iter().map(|blocks| {
+            blocks.iter().map(|_| client_key.encrypt_with_message_modulus(0, MessageModulus(16))).collect()
+        }).collect();
+        let out_of_range = EarlyReject { features: vec![0, 3], lower_bound: 0 };
+        assert_eq!(
+            try_first_phase(&big_server_key, encrypted_probe, &luts, &out_of_range, NUM_SUM_BLOCKS).err(),
+            Some(AuthenticationError::FirstPhaseFeatureOutOfRange { feature: 3, num_features: 3 })
+        );
+    }
+}
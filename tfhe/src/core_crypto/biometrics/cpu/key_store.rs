@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use crate::core_crypto::entities::{LweBootstrapKeyOwned, LweKeyswitchKeyOwned, LweMultiBitBootstrapKeyOwned};
+use crate::core_crypto::biometrics::cpu::tfhe_functions_classic::{make_standard_keys_classic, server_key_from_standard_classic};
+use crate::core_crypto::biometrics::cpu::tfhe_functions_multibit::{make_standard_keys_multibit, server_key_from_standard_multibit};
+use crate::keycache::{FileStorage, ImplKeyCache};
+use crate::shortint;
+use crate::shortint::{ClassicPBSParameters, MultiBitPBSParameters};
+use crate::shortint::engine::ShortintEngine;
+
+/// Where [`BiometricKeyStore::default`] keeps the keys, next to the other key caches.
+pub const DEFAULT_KEY_STORE_PATH: &str = "../keys/biometrics";
+
+/// Persisted key material of a parameter set, the bootstrapping key is stored before the Fourier
+/// conversion which is redone on every load.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StandardKeys<BootstrapKey> {
+    pub client_key: shortint::ClientKey,
+    pub bootstrap_key: BootstrapKey,
+    pub key_switching_key: LweKeyswitchKeyOwned<u64>,
+}
+
+/// Keys of [`make_keys_classic`](super::tfhe_functions_classic::make_keys_classic) and
+/// [`make_keys_multibit`](super::tfhe_functions_multibit::make_keys_multibit) generated once per
+/// parameter set and reloaded from disk afterwards.
+///
+/// Parameter sets are told apart by their name, so only named parameter sets can be stored.
+pub struct BiometricKeyStore {
+    classic: ImplKeyCache<ClassicPBSParameters, StandardKeys<LweBootstrapKeyOwned<u64>>, FileStorage>,
+    multibit: ImplKeyCache<MultiBitPBSParameters, StandardKeys<LweMultiBitBootstrapKeyOwned<u64>>, FileStorage>,
+}
+
+impl Default for BiometricKeyStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_KEY_STORE_PATH)
+    }
+}
+
+impl BiometricKeyStore {
+    /// Stores one file per parameter set in the `path` directory.
+    pub fn new(path: &str) -> Self {
+        Self {
+            classic: ImplKeyCache::new(FileStorage::new(path.to_string())),
+            multibit: ImplKeyCache::new(FileStorage::new(path.to_string())),
+        }
+    }
+
+    /// Same keys as [`make_keys_classic`](super::tfhe_functions_classic::make_keys_classic),
+    /// `engine` is only used if the keys of `params` are not stored yet.
+    pub fn classic_keys(&self, params: ClassicPBSParameters, engine: &mut ShortintEngine) -> (shortint::ClientKey, shortint::ServerKey) {
+        let keys = self.classic.get_with_closure(params, &mut |params| {
+            let (client_key, bootstrap_key, key_switching_key) = make_standard_keys_classic(params, engine);
+            StandardKeys { client_key, bootstrap_key, key_switching_key }
+        });
+        let server_key = server_key_from_standard_classic(&keys.client_key, &keys.bootstrap_key, keys.key_switching_key.clone());
+        (keys.client_key.clone(), server_key)
+    }
+
+    /// Same keys as [`make_keys_multibit`](super::tfhe_functions_multibit::make_keys_multibit),
+    /// the thread count and the execution mode are not part of the stored keys.
+    pub fn multibit_keys(&self, params: MultiBitPBSParameters, thread_count_bs: usize, deterministic_execution: bool, engine: &mut ShortintEngine) -> (shortint::ClientKey, shortint::ServerKey) {
+        let keys = self.multibit.get_with_closure(params, &mut |params| {
+            let (client_key, bootstrap_key, key_switching_key) = make_standard_keys_multibit(params, engine);
+            StandardKeys { client_key, bootstrap_key, key_switching_key }
+        });
+        let server_key = server_key_from_standard_multibit(&keys.client_key, &keys.bootstrap_key, keys.key_switching_key.clone(), thread_count_bs, deterministic_execution);
+        (keys.client_key.clone(), server_key)
+    }
+
+    /// Drops the keys kept in memory, the next request reloads them from disk.
+    pub fn clear_in_memory_cache(&self) {
+        self.classic.clear_in_memory_cache();
+        self.multibit.clear_in_memory_cache();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shortint::parameters::{PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS};
+
+    // Encrypts with `encryption_key`, bootstraps with `server_key` and decrypts with `client_key`
+    fn check_pbs(encryption_key: &shortint::ClientKey, client_key: &shortint::ClientKey, server_key: &shortint::ServerKey) {
+        let lut = server_key.generate_lookup_table(|x| (x + 1) % 4);
+        for msg in 0..4 {
+            let ct = server_key.apply_lookup_table(&encryption_key.encrypt(msg), &lut);
+            assert_eq!(client_key.decrypt(&ct), (msg + 1) % 4);
+        }
+    }
+
+    #[test]
+    fn keys_reload_from_disk() {
+        let dir = std::env::temp_dir().join(format!("biometric_key_store_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = BiometricKeyStore::new(dir.to_str().unwrap());
+        let mut engine = ShortintEngine::new();
+
+        let classic = PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+        let multibit = PARAM_MULTI_BIT_MESSAGE_2_CARRY_2_GROUP_3_KS_PBS;
+        let (classic_client_key, classic_server_key) = store.classic_keys(classic, &mut engine);
+        check_pbs(&classic_client_key, &classic_client_key, &classic_server_key);
+        let (multibit_client_key, multibit_server_key) = store.multibit_keys(multibit, 1, false, &mut engine);
+        check_pbs(&multibit_client_key, &multibit_client_key, &multibit_server_key);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        // Ciphertexts of the generated keys go through the reloaded ones
+        store.clear_in_memory_cache();
+        let (client_key, server_key) = store.classic_keys(classic, &mut engine);
+        check_pbs(&classic_client_key, &client_key, &server_key);
+        let (client_key, server_key) = store.multibit_keys(multibit, 2, true, &mut engine);
+        check_pbs(&multibit_client_key, &client_key, &server_key);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
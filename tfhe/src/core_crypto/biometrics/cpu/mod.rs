@@ -1,4 +1,18 @@
 pub mod all_in_one_multibit;
 pub mod tfhe_functions_multibit;
 pub mod all_in_one_classic;
-pub mod tfhe_functions_classic;
\ No newline at end of file
+pub mod all_in_one_signed;
+pub mod all_in_one_wide;
+pub mod tfhe_functions_classic;
+pub mod all_in_one_multi_sample;
+pub mod all_in_one_fusion;
+pub mod accumulate;
+pub mod challenge;
+pub mod batch;
+#[cfg(feature = "internal-keycache")]
+pub mod key_store;
+pub mod validation;
+pub mod early_reject;
+pub mod controller;
+#[cfg(feature = "experimental")]
+pub mod threshold;
\ No newline at end of file
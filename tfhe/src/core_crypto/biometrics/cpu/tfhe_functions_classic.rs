@@ -1,5 +1,5 @@
 use crate::core_crypto::algorithms::{allocate_and_generate_new_binary_glwe_secret_key, allocate_and_generate_new_binary_lwe_secret_key, allocate_and_generate_new_lwe_keyswitch_key};
-use crate::core_crypto::prelude::{FourierLweBootstrapKey, LweBootstrapKeyOwned, par_allocate_and_generate_new_lwe_bootstrap_key, par_convert_standard_lwe_bootstrap_key_to_fourier};
+use crate::core_crypto::prelude::{FourierLweBootstrapKey, LweBootstrapKeyOwned, LweKeyswitchKeyOwned, par_allocate_and_generate_new_lwe_bootstrap_key, par_convert_standard_lwe_bootstrap_key_to_fourier};
 use crate::integer::encryption::KnowsMessageModulus;
 use crate::shortint;
 use crate::shortint::{ClassicPBSParameters};
@@ -9,6 +9,16 @@ use crate::shortint::server_key::ShortintBootstrappingKey;
 
 
 pub fn make_keys_classic(params: ClassicPBSParameters, engine: &mut ShortintEngine) -> (shortint::ClientKey, shortint::ServerKey) {
+    let (client_key, bootstrap_key, key_switching_key) = make_standard_keys_classic(params, engine);
+    let server_key = server_key_from_standard_classic(&client_key, &bootstrap_key, key_switching_key);
+    (
+        client_key, server_key
+    )
+}
+
+/// Client key, bootstrapping key before the Fourier conversion and key switching key, the parts
+/// of [`make_keys_classic`] worth persisting.
+pub fn make_standard_keys_classic(params: ClassicPBSParameters, engine: &mut ShortintEngine) -> (shortint::ClientKey, LweBootstrapKeyOwned<u64>, LweKeyswitchKeyOwned<u64>) {
     //Generate secret keys
     //Small key
     let lwe_secret_key = allocate_and_generate_new_binary_lwe_secret_key(
@@ -36,21 +46,6 @@ pub fn make_keys_classic(params: ClassicPBSParameters, engine: &mut ShortintEngi
             &mut engine.encryption_generator,
         );
 
-    // Creation of the bootstrapping key in the Fourier domain
-    let mut fourier_bsk = FourierLweBootstrapKey::new(
-        bootstrap_key.input_lwe_dimension(),
-        bootstrap_key.glwe_size(),
-        bootstrap_key.polynomial_size(),
-        bootstrap_key.decomposition_base_log(),
-        bootstrap_key.decomposition_level_count(),
-    );
-
-    // Conversion to fourier domain
-    par_convert_standard_lwe_bootstrap_key_to_fourier(
-        &bootstrap_key,
-        &mut fourier_bsk,
-    );
-
     // Create key switching key
     //Create CPU key
 
@@ -72,11 +67,34 @@ pub fn make_keys_classic(params: ClassicPBSParameters, engine: &mut ShortintEngi
         parameters: params.into(),
     };
 
+    (
+        client_key, bootstrap_key, h_key_switching_key
+    )
+}
+
+/// Builds the server key of [`make_keys_classic`] from its standard parts, converting the
+/// bootstrapping key to the Fourier domain.
+pub fn server_key_from_standard_classic(client_key: &shortint::ClientKey, bootstrap_key: &LweBootstrapKeyOwned<u64>, key_switching_key: LweKeyswitchKeyOwned<u64>) -> shortint::ServerKey {
+    // Creation of the bootstrapping key in the Fourier domain
+    let mut fourier_bsk = FourierLweBootstrapKey::new(
+        bootstrap_key.input_lwe_dimension(),
+        bootstrap_key.glwe_size(),
+        bootstrap_key.polynomial_size(),
+        bootstrap_key.decomposition_base_log(),
+        bootstrap_key.decomposition_level_count(),
+    );
+
+    // Conversion to fourier domain
+    par_convert_standard_lwe_bootstrap_key_to_fourier(
+        bootstrap_key,
+        &mut fourier_bsk,
+    );
+
     let max_value = client_key.parameters.message_modulus().0 * client_key.parameters.carry_modulus().0 - 1;
     let max = MaxDegree::new(max_value);
 
-    let server_key = shortint::ServerKey {
-        key_switching_key: h_key_switching_key,
+    shortint::ServerKey {
+        key_switching_key,
         bootstrapping_key: ShortintBootstrappingKey::Classic(fourier_bsk),
         message_modulus: client_key.message_modulus(),
         carry_modulus: client_key.parameters.carry_modulus(),
@@ -84,9 +102,5 @@ pub fn make_keys_classic(params: ClassicPBSParameters, engine: &mut ShortintEngi
         max_noise_level: client_key.parameters.max_noise_level(),
         ciphertext_modulus: client_key.parameters.ciphertext_modulus(),
         pbs_order: client_key.parameters.encryption_key_choice().into(),
-    };
-
-    (
-        client_key, server_key
-    )
+    }
 }
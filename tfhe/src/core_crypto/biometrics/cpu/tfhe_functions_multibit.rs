@@ -1,5 +1,5 @@
 use crate::core_crypto::algorithms::{allocate_and_generate_new_binary_glwe_secret_key, allocate_and_generate_new_binary_lwe_secret_key, allocate_and_generate_new_lwe_keyswitch_key, par_convert_standard_lwe_multi_bit_bootstrap_key_to_fourier};
-use crate::core_crypto::entities::{FourierLweMultiBitBootstrapKey, LweMultiBitBootstrapKeyOwned};
+use crate::core_crypto::entities::{FourierLweMultiBitBootstrapKey, LweKeyswitchKeyOwned, LweMultiBitBootstrapKeyOwned};
 use crate::core_crypto::prelude::{par_allocate_and_generate_new_lwe_multi_bit_bootstrap_key, ThreadCount};
 use crate::integer::encryption::KnowsMessageModulus;
 use crate::shortint;
@@ -9,7 +9,19 @@ use crate::shortint::engine::ShortintEngine;
 use crate::shortint::server_key::ShortintBootstrappingKey;
 
 
-pub fn make_keys_multibit(params: MultiBitPBSParameters, thread_count_bs: usize, engine: &mut ShortintEngine) -> (shortint::ClientKey, shortint::ServerKey) {
+/// Multi-bit keys running every PBS on `thread_count_bs` threads, `deterministic_execution` trades
+/// some PBS throughput for bit-exact outputs.
+pub fn make_keys_multibit(params: MultiBitPBSParameters, thread_count_bs: usize, deterministic_execution: bool, engine: &mut ShortintEngine) -> (shortint::ClientKey, shortint::ServerKey) {
+    let (client_key, bootstrap_key, key_switching_key) = make_standard_keys_multibit(params, engine);
+    let server_key = server_key_from_standard_multibit(&client_key, &bootstrap_key, key_switching_key, thread_count_bs, deterministic_execution);
+    (
+        client_key, server_key
+    )
+}
+
+/// Client key, bootstrapping key before the Fourier conversion and key switching key, the parts
+/// of [`make_keys_multibit`] worth persisting.
+pub fn make_standard_keys_multibit(params: MultiBitPBSParameters, engine: &mut ShortintEngine) -> (shortint::ClientKey, LweMultiBitBootstrapKeyOwned<u64>, LweKeyswitchKeyOwned<u64>) {
     //Generate secret keys
     //Small key
     let lwe_secret_key = allocate_and_generate_new_binary_lwe_secret_key(
@@ -38,22 +50,6 @@ pub fn make_keys_multibit(params: MultiBitPBSParameters, thread_count_bs: usize,
             &mut engine.encryption_generator,
         );
 
-    // Creation of the bootstrapping key in the Fourier domain
-    let mut fourier_bsk = FourierLweMultiBitBootstrapKey::new(
-        bootstrap_key.input_lwe_dimension(),
-        bootstrap_key.glwe_size(),
-        bootstrap_key.polynomial_size(),
-        bootstrap_key.decomposition_base_log(),
-        bootstrap_key.decomposition_level_count(),
-        bootstrap_key.grouping_factor(),
-    );
-
-    // Conversion to fourier domain
-    par_convert_standard_lwe_multi_bit_bootstrap_key_to_fourier(
-        &bootstrap_key,
-        &mut fourier_bsk,
-    );
-
     // Create key switching key
     //Create CPU key
 
@@ -75,15 +71,39 @@ pub fn make_keys_multibit(params: MultiBitPBSParameters, thread_count_bs: usize,
         parameters: params.into(),
     };
 
+    (
+        client_key, bootstrap_key, h_key_switching_key
+    )
+}
+
+/// Builds the server key of [`make_keys_multibit`] from its standard parts, converting the
+/// bootstrapping key to the Fourier domain.
+pub fn server_key_from_standard_multibit(client_key: &shortint::ClientKey, bootstrap_key: &LweMultiBitBootstrapKeyOwned<u64>, key_switching_key: LweKeyswitchKeyOwned<u64>, thread_count_bs: usize, deterministic_execution: bool) -> shortint::ServerKey {
+    // Creation of the bootstrapping key in the Fourier domain
+    let mut fourier_bsk = FourierLweMultiBitBootstrapKey::new(
+        bootstrap_key.input_lwe_dimension(),
+        bootstrap_key.glwe_size(),
+        bootstrap_key.polynomial_size(),
+        bootstrap_key.decomposition_base_log(),
+        bootstrap_key.decomposition_level_count(),
+        bootstrap_key.grouping_factor(),
+    );
+
+    // Conversion to fourier domain
+    par_convert_standard_lwe_multi_bit_bootstrap_key_to_fourier(
+        bootstrap_key,
+        &mut fourier_bsk,
+    );
+
     let max_value = client_key.parameters.message_modulus().0 * client_key.parameters.carry_modulus().0 - 1;
     let max = MaxDegree::new(max_value);
 
-    let server_key = shortint::ServerKey {
-        key_switching_key: h_key_switching_key,
+    shortint::ServerKey {
+        key_switching_key,
         bootstrapping_key: ShortintBootstrappingKey::MultiBit {
             fourier_bsk,
             thread_count: ThreadCount(thread_count_bs),
-            deterministic_execution: false,
+            deterministic_execution,
         },
         message_modulus: client_key.message_modulus(),
         carry_modulus: client_key.parameters.carry_modulus(),
@@ -91,9 +111,5 @@ pub fn make_keys_multibit(params: MultiBitPBSParameters, thread_count_bs: usize,
         max_noise_level: client_key.parameters.max_noise_level(),
         ciphertext_modulus: client_key.parameters.ciphertext_modulus(),
         pbs_order: client_key.parameters.encryption_key_choice().into(),
-    };
-
-    (
-        client_key, server_key
-    )
+    }
 }
@@ -0,0 +1,365 @@
+use crate::core_crypto::commons::math::decomposition::{DecompositionLevel, DecompositionTerm};
+use crate::core_crypto::commons::math::random::CompressionSeed;
+use crate::core_crypto::commons::numeric::UnsignedInteger;
+use crate::core_crypto::experimental::prelude::*;
+use crate::core_crypto::prelude::*;
+use crate::integer::BooleanBlock;
+use crate::shortint;
+use crate::shortint::parameters::{EncryptionKeyChoice, ShortintParameterSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThresholdError {
+    /// The smudging noise hiding the noise of the decision, added up over all the parties, does
+    /// not fit in the decoding margin of the parameters
+    NoRoomForSmudging { required_bits: u32, available_bits: u32 },
+}
+
+impl std::fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRoomForSmudging {
+                required_bits,
+                available_bits,
+            } => write!(
+                f,
+                "the smudged decryption needs a margin of {required_bits} bits, \
+                the parameters only leave {available_bits} bits"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ThresholdError {}
+
+/// Dimension of the key the parties generate together to decrypt the decisions, with the noise
+/// of the compact public keys of the shortint parameters
+pub const DECISION_KEY_LWE_DIMENSION: LweDimension = LweDimension(2048);
+const DECISION_KEY_NOISE_BOUND_LOG2: u32 = 14;
+// The switching noise adds up to about 2^30, it has to fit in the bound given to `smudging_noise`
+// together with the noise of the decision
+const SWITCH_BASE_LOG: DecompositionBaseLog = DecompositionBaseLog(5);
+const SWITCH_LEVEL: DecompositionLevelCount = DecompositionLevelCount(8);
+
+/// Contribution of one of `party_count` parties to the distributed generation of the decision
+/// key, any `threshold` of them are needed to decrypt a decision.
+///
+/// The party draws its own part of the key and returns its shares, `shares[party]` to be sent to
+/// `party` who sums all the shares it receives with [`sum_lwe_secret_key_shares`], and its part
+/// of the public key, whose mask is derived from the `public_key_seed` all the parties agree on.
+/// The key, the sum of the parts of all the parties, is never known to anyone.
+pub fn generate_decision_key_contribution(
+    threshold: usize,
+    party_count: usize,
+    public_key_seed: CompressionSeed,
+) -> (Vec<LweSecretKeyShare<u64>>, SeededLweCompactPublicKeyOwned<u64>) {
+    let mut seeder = new_seeder();
+    let seeder = seeder.as_mut();
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator =
+        EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    let key_part = allocate_and_generate_new_binary_lwe_secret_key(
+        DECISION_KEY_LWE_DIMENSION,
+        &mut secret_generator,
+    );
+    let shares = allocate_and_deal_lwe_secret_key(
+        &key_part,
+        threshold,
+        party_count,
+        &mut encryption_generator,
+    );
+
+    let mut public_key_part = SeededLweCompactPublicKeyOwned::new(
+        0u64,
+        DECISION_KEY_LWE_DIMENSION,
+        public_key_seed,
+        CiphertextModulus::new_native(),
+    );
+    generate_seeded_lwe_compact_public_key(
+        &key_part,
+        &mut public_key_part,
+        TUniform::<u64>::new(DECISION_KEY_NOISE_BOUND_LOG2),
+        seeder,
+    );
+
+    (shares, public_key_part)
+}
+
+/// Public key of the decision key, the sum of the public key parts of all the parties.
+///
+/// # Panics
+///
+/// Panics if `public_key_parts` is empty or if the parts were not generated from the same seed.
+pub fn combine_decision_public_key(
+    public_key_parts: &[SeededLweCompactPublicKeyOwned<u64>],
+) -> LweCompactPublicKeyOwned<u64> {
+    assert!(!public_key_parts.is_empty(), "At least one public key part is needed");
+
+    let mut public_key = public_key_parts[0].clone();
+    for part in &public_key_parts[1..] {
+        assert_eq!(
+            part.compression_seed(),
+            public_key.compression_seed(),
+            "Public key parts generated from different seeds"
+        );
+        for (coef, &part_coef) in public_key.get_mut_body().as_mut().iter_mut().zip(part.get_body().as_ref()) {
+            *coef = coef.wrapping_add(part_coef);
+        }
+    }
+    public_key.decompress_into_lwe_compact_public_key()
+}
+
+/// Key switching the decisions encrypted under `client_key` to the decision key of the parties,
+/// generated by the client key owner from the public key of the parties.
+pub fn generate_decision_switching_key(
+    client_key: &shortint::ClientKey,
+    decision_public_key: &LweCompactPublicKeyOwned<u64>,
+) -> LweKeyswitchKeyOwned<u64> {
+    let lwe_secret_key = match client_key.parameters.encryption_key_choice() {
+        EncryptionKeyChoice::Big => client_key.large_lwe_secret_key(),
+        EncryptionKeyChoice::Small => client_key.small_lwe_secret_key(),
+    };
+
+    // Same plaintexts as `generate_lwe_keyswitch_key`, encrypted with the public key
+    let plaintexts: Vec<u64> = lwe_secret_key
+        .as_ref()
+        .iter()
+        .flat_map(|&key_element| {
+            (1..=SWITCH_LEVEL.0).rev().map(move |level| {
+                DecompositionTerm::new(DecompositionLevel(level), SWITCH_BASE_LOG, key_element)
+                    .to_recomposition_summand()
+            })
+        })
+        .collect();
+
+    let mut seeder = new_seeder();
+    let seeder = seeder.as_mut();
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator =
+        EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    let mut switching_key_list = LweCompactCiphertextList::new(
+        0u64,
+        decision_public_key.lwe_dimension().to_lwe_size(),
+        LweCiphertextCount(plaintexts.len()),
+        decision_public_key.ciphertext_modulus(),
+    );
+    encrypt_lwe_compact_ciphertext_list_with_compact_public_key(
+        decision_public_key,
+        &mut switching_key_list,
+        &PlaintextList::from_container(plaintexts),
+        TUniform::<u64>::new(DECISION_KEY_NOISE_BOUND_LOG2),
+        TUniform::<u64>::new(DECISION_KEY_NOISE_BOUND_LOG2),
+        &mut secret_generator,
+        &mut encryption_generator,
+    );
+
+    LweKeyswitchKey::from_container(
+        switching_key_list.expand_into_lwe_ciphertext_list().into_container(),
+        SWITCH_BASE_LOG,
+        SWITCH_LEVEL,
+        decision_public_key.lwe_dimension().to_lwe_size(),
+        decision_public_key.ciphertext_modulus(),
+    )
+}
+
+/// Switches `decision` to the decision key of the parties, for [`partially_decrypt_decision`] and
+/// [`combine_decision`].
+pub fn switch_decision(
+    switching_key: &LweKeyswitchKeyOwned<u64>,
+    decision: &BooleanBlock,
+) -> LweCiphertextOwned<u64> {
+    let mut switched = LweCiphertext::new(
+        0u64,
+        switching_key.output_lwe_size(),
+        switching_key.ciphertext_modulus(),
+    );
+    keyswitch_lwe_ciphertext(switching_key, &decision.as_ref().ct, &mut switched);
+    switched
+}
+
+/// Smudging noise of a decryption share for decisions whose noise is bounded by
+/// `2^ciphertext_noise_bound_log2` (on the 64 bits torus).
+///
+/// Each share gets `statistical_security_bits` more bits of noise than the decision, so that the
+/// shares do not leak the noise of the decision, and through it the key. The smudging noises of
+/// the `party_count` shares and the noise of the decision added together must stay below half of
+/// the encoding step of `parameters`, otherwise an error is returned.
+///
+/// The bound has to cover the noise of the decision as it is sent to the parties: the output noise
+/// of the last PBS of the authentication plus the noise of [`switch_decision`]. The single level
+/// PBS of the default shortint parameters outputs about 2^50 of noise, which leaves no room, the
+/// PBS needs a finer decomposition (e.g. base 2^7 with 6 levels for the 2_2 parameters, which keeps
+/// the switched decision below 2^35 and leaves room for 16 bits of statistical security).
+pub fn smudging_noise(
+    parameters: ShortintParameterSet,
+    ciphertext_noise_bound_log2: u32,
+    statistical_security_bits: u32,
+    party_count: usize,
+) -> Result<TUniform<u64>, ThresholdError> {
+    let delta_log2 = 63 - (parameters.message_modulus().0 * parameters.carry_modulus().0).ilog2();
+    let smudging_bound_log2 = ciphertext_noise_bound_log2 + statistical_security_bits;
+
+    // The shares add up to at most 2^(smudging + ceil(log2(n))), the noise of the decision adds
+    // at most as much again, the sum has to stay strictly below delta / 2
+    let required_bits = smudging_bound_log2 + (party_count as u64).ceil_ilog2() + 1;
+    let available_bits = delta_log2 - 2;
+    if required_bits > available_bits {
+        return Err(ThresholdError::NoRoomForSmudging {
+            required_bits,
+            available_bits,
+        });
+    }
+
+    Ok(TUniform::new(smudging_bound_log2))
+}
+
+/// Decryption share of the switched `decision` by the party holding `share`, for the decryption by
+/// `participants`, masked with noise from `smudging_noise_distribution` (see [`smudging_noise`]).
+pub fn partially_decrypt_decision(
+    share: &LweSecretKeyShare<u64>,
+    participants: &[usize],
+    decision: &LweCiphertextOwned<u64>,
+    smudging_noise_distribution: TUniform<u64>,
+) -> LweDecryptionShare<u64> {
+    let mut seeder = new_seeder();
+    let seeder = seeder.as_mut();
+    let mut generator =
+        EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+    partially_decrypt_lwe_ciphertext(
+        share,
+        participants,
+        decision,
+        smudging_noise_distribution,
+        &mut generator,
+    )
+}
+
+/// Combines the decryption shares of all participants into the clear switched decision.
+pub fn combine_decision(
+    decision: &LweCiphertextOwned<u64>,
+    decryption_shares: &[LweDecryptionShare<u64>],
+    parameters: ShortintParameterSet,
+) -> bool {
+    let decrypted = combine_lwe_decryption_shares(decision, decryption_shares).0;
+
+    // Same decoding as `shortint::ClientKey::decrypt_message_and_carry`
+    let delta =
+        (1_u64 << 63) / (parameters.message_modulus().0 * parameters.carry_modulus().0) as u64;
+    let rounding = (decrypted & (delta >> 1)) << 1;
+    let decoded = decrypted.wrapping_add(rounding) / delta;
+
+    decoded % parameters.message_modulus().0 as u64 != 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use itertools::Itertools;
+    use crate::core_crypto::biometrics::common;
+    use crate::core_crypto::biometrics::cpu::all_in_one_classic;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::{ClassicPBSParameters, PARAM_MESSAGE_2_CARRY_2_KS_PBS};
+    use crate::shortint::MessageModulus;
+
+    // Finer PBS decomposition than the default, whose output noise leaves no room for smudging
+    const PARAMETERS: ClassicPBSParameters = ClassicPBSParameters {
+        pbs_base_log: DecompositionBaseLog(7),
+        pbs_level: DecompositionLevelCount(6),
+        ..PARAM_MESSAGE_2_CARRY_2_KS_PBS
+    };
+    // Noise of the switched decision with `PARAMETERS`, checked below
+    const DECISION_NOISE_BOUND_LOG2: u32 = 35;
+    const STATISTICAL_SECURITY_BITS: u32 = 16;
+
+    // Two features with a single output block each, indexed [template][probe]
+    const TABLES: [[[u64; 4]; 4]; 2] = [
+        [[3, 2, 1, 0], [2, 3, 2, 1], [1, 2, 3, 2], [0, 1, 2, 3]],
+        [[3, 1, 0, 0], [1, 3, 1, 0], [0, 1, 3, 1], [0, 0, 1, 3]],
+    ];
+
+    #[test]
+    fn threshold_decision() {
+        let mut engine = ShortintEngine::new();
+        let client_key = shortint::ClientKey::new(PARAMETERS);
+        let server_key = shortint::ServerKey::new(&client_key);
+        let parameters = client_key.parameters;
+        let smudging_noise_distribution = smudging_noise(
+            parameters,
+            DECISION_NOISE_BOUND_LOG2,
+            STATISTICAL_SECURITY_BITS,
+            3,
+        )
+        .unwrap();
+
+        // 3 parties generate the decision key, any 2 of them decrypt
+        let public_key_seed = CompressionSeed::from(new_seeder().seed());
+        let (dealt, public_key_parts): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| generate_decision_key_contribution(2, 3, public_key_seed))
+            .unzip();
+        let shares = (0..3).map(|party| {
+            let received = dealt.iter().map(|shares| shares[party].clone()).collect_vec();
+            sum_lwe_secret_key_shares(&received)
+        }).collect_vec();
+        let switching_key = generate_decision_switching_key(
+            &client_key,
+            &combine_decision_public_key(&public_key_parts),
+        );
+
+        let template = [1, 2];
+        let rows: Vec<Vec<&[u64]>> = TABLES.iter().zip(template).map(|(table, x)| vec![&table[x][..]]).collect();
+        let luts = common::generate_lookup_tables_individual_from_rows(&rows, &client_key, parameters, &mut engine);
+
+        // 3 + 3 and 1 + 0 against a threshold of 5
+        for (probe, expected) in [([1, 2], true), ([3, 0], false)] {
+            let probe = probe.iter().map(|&p| vec![client_key.encrypt_with_message_modulus(p, MessageModulus(16))]).collect();
+            let (decision, _) = all_in_one_classic::authenticate(server_key.clone(), probe, luts.clone(), 5, 2);
+            let switched = switch_decision(&switching_key, &decision);
+
+            // Unsmudged decryption by everyone, to check the bound the smudging is sized for
+            let decryption_shares = (0..3).map(|party| {
+                partially_decrypt_decision(&shares[party], &[0, 1, 2], &switched, TUniform::new(0))
+            }).collect_vec();
+            let delta = (1_u64 << 63) / 16;
+            let noise = combine_lwe_decryption_shares(&switched, &decryption_shares).0
+                .wrapping_sub(u64::from(expected) * delta) as i64;
+            assert!(noise.unsigned_abs() < 1 << DECISION_NOISE_BOUND_LOG2, "noise {noise}");
+
+            for participants in [[0, 1], [0, 2], [1, 2]] {
+                let decryption_shares = participants.iter().map(|&party| {
+                    partially_decrypt_decision(
+                        &shares[party],
+                        &participants,
+                        &switched,
+                        smudging_noise_distribution,
+                    )
+                }).collect_vec();
+                assert_eq!(
+                    combine_decision(&switched, &decryption_shares, parameters),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn smudging_noise_fits_parameters() {
+        let parameters = ShortintParameterSet::from(PARAMETERS);
+
+        // delta = 2^59, 35 + 16 + 2 + 1 = 54 bits fit in the 57 available ones
+        assert_eq!(
+            smudging_noise(parameters, DECISION_NOISE_BOUND_LOG2, STATISTICAL_SECURITY_BITS, 3),
+            Ok(TUniform::new(51))
+        );
+
+        // The noise after a PBS with the default parameters leaves no room for 40 bits of
+        // statistical security
+        assert_eq!(
+            smudging_noise(parameters, 50, 40, 3),
+            Err(ThresholdError::NoRoomForSmudging {
+                required_bits: 93,
+                available_bits: 57,
+            })
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use crate::conformance::ParameterSetConformant;
+use crate::core_crypto::biometrics::common;
+use crate::shortint;
+use crate::shortint::parameters::{CiphertextConformanceParams, Degree};
+use crate::shortint::server_key::LookupTableOwned;
+use crate::shortint::{CarryModulus, Ciphertext, MessageModulus};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthenticationError {
+    /// The probe and the template do not have the same number of features
+    FeatureCountMismatch { probe: usize, template: usize },
+    /// A feature of the probe is not encrypted once per LUT of the template feature
+    BlockCountMismatch {
+        feature: usize,
+        probe: usize,
+        template: usize,
+    },
+    /// A feature has more blocks than the sum, so it cannot be padded to the sum
+    FeatureWiderThanSum {
+        feature: usize,
+        blocks: usize,
+        num_sum_blocks: usize,
+    },
+    /// The threshold does not fit `num_sum_blocks` blocks
+    ThresholdOutOfRange {
+        threshold: u64,
+        num_sum_blocks: usize,
+    },
+    /// A probe block was not encrypted under the parameters of the server key
+    NonConformantProbe { feature: usize, block: usize },
+    /// A LUT was not encrypted under the parameters of the server key, or its outputs do not fit
+    /// a block of the sum
+    NonConformantLut { feature: usize, block: usize },
+    /// A worker evaluating the LUTs panicked
+    WorkerPanicked,
+    /// A subject has no enrolled sample
+    NoSamples,
+    /// The number of positive per-sample decisions required by a vote is not in `1..=num_samples`
+    VoteCountOutOfRange { k: usize, num_samples: usize },
+    /// A fusion has no modality
+    NoModalities,
+    /// The signed threshold does not fit `num_sum_blocks` blocks in two's complement
+    SignedThresholdOutOfRange {
+        threshold: i64,
+        num_sum_blocks: usize,
+    },
+    /// A feature of the first phase of an early rejection is not a feature of the probe
+    FirstPhaseFeatureOutOfRange { feature: usize, num_features: usize },
+}
+
+impl std::fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FeatureCountMismatch { probe, template } => {
+                write!(f, "the probe has {probe} features, the template {template}")
+            }
+            Self::BlockCountMismatch {
+                feature,
+                probe,
+                template,
+            } => write!(
+                f,
+                "feature {feature} has {probe} probe blocks for {template} LUTs"
+            ),
+            Self::FeatureWiderThanSum {
+                feature,
+                blocks,
+                num_sum_blocks,
+            } => write!(
+                f,
+                "feature {feature} has {blocks} blocks, the sum only {num_sum_blocks}"
+            ),
+            Self::ThresholdOutOfRange {
+                threshold,
+                num_sum_blocks,
+            } => write!(
+                f,
+                "threshold {threshold} does not fit {num_sum_blocks} blocks"
+            ),
+            Self::NonConformantProbe { feature, block } => write!(
+                f,
+                "block {block} of probe feature {feature} does not match the server key"
+            ),
+            Self::NonConformantLut { feature, block } => write!(
+                f,
+                "LUT {block} of template feature {feature} does not match the server key"
+            ),
+            Self::WorkerPanicked => write!(f, "a worker evaluating the LUTs panicked"),
+            Self::NoSamples => write!(f, "no enrolled sample"),
+            Self::VoteCountOutOfRange { k, num_samples } => write!(
+                f,
+                "vote count must be in 1..={num_samples}, got {k}"
+            ),
+            Self::NoModalities => write!(f, "no modality to fuse"),
+            Self::SignedThresholdOutOfRange {
+                threshold,
+                num_sum_blocks,
+            } => write!(
+                f,
+                "signed threshold {threshold} does not fit {num_sum_blocks} blocks"
+            ),
+            Self::FirstPhaseFeatureOutOfRange {
+                feature,
+                num_features,
+            } => write!(
+                f,
+                "first phase feature {feature} is not one of the {num_features} features"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AuthenticationError {}
+
+/// Parameters of fresh probe blocks, which are encrypted over the whole message and carry space
+/// of the server key.
+pub fn probe_conformance_params(server_key: &shortint::ServerKey) -> CiphertextConformanceParams {
+    let total_modulus = server_key.message_modulus.0 * server_key.carry_modulus.0;
+    digit_conformance_params(server_key, MessageModulus(total_modulus))
+}
+
+/// Parameters of fresh blocks encrypted with `encrypt_with_message_modulus` and `modulus`, the
+/// rest of the block space is left to the carries.
+pub fn digit_conformance_params(
+    server_key: &shortint::ServerKey,
+    modulus: MessageModulus,
+) -> CiphertextConformanceParams {
+    let total_modulus = server_key.message_modulus.0 * server_key.carry_modulus.0;
+    CiphertextConformanceParams {
+        message_modulus: modulus,
+        carry_modulus: CarryModulus(total_modulus / modulus.0),
+        degree: Degree::new(modulus.0 - 1),
+        ..server_key.conformance_params()
+    }
+}
+
+fn lut_is_conformant(server_key: &shortint::ServerKey, lut: &LookupTableOwned) -> bool {
+    lut.acc.glwe_size() == server_key.bootstrapping_key.glwe_size()
+        && lut.acc.polynomial_size() == server_key.bootstrapping_key.polynomial_size()
+        && lut.acc.ciphertext_modulus() == server_key.ciphertext_modulus
+        && lut.degree.get() < server_key.message_modulus.0
+}
+
+/// Checks the template part of [`validate_inputs`]: that every feature fits the sum, that the
+/// threshold fits the sum and that the LUTs were encrypted for `server_key`.
+///
+/// Servers storing templates run it on enrollment, so that a stored template can only fail the
+/// checks of the probe later on.
+pub fn validate_template(
+    server_key: &shortint::ServerKey,
+    luts: &[Vec<LookupTableOwned>],
+    threshold: u64,
+    num_sum_blocks: usize,
+) -> Result<(), AuthenticationError> {
+    let sum_bits = server_key.message_modulus.0.ilog2() as usize * num_sum_blocks;
+    if sum_bits < u64::BITS as usize && threshold >> sum_bits != 0 {
+        return Err(AuthenticationError::ThresholdOutOfRange {
+            threshold,
+            num_sum_blocks,
+        });
+    }
+
+    for (feature, feature_luts) in luts.iter().enumerate() {
+        if feature_luts.len() > num_sum_blocks {
+            return Err(AuthenticationError::FeatureWiderThanSum {
+                feature,
+                blocks: feature_luts.len(),
+                num_sum_blocks,
+            });
+        }
+        if let Some(block) = feature_luts
+            .iter()
+            .position(|lut| !lut_is_conformant(server_key, lut))
+        {
+            return Err(AuthenticationError::NonConformantLut { feature, block });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks everything the authenticators otherwise assume: the shapes of the probe and the
+/// template, that every feature fits the sum, that the threshold fits the sum and that the probe
+/// and the LUTs were encrypted for `server_key`.
+pub fn validate_inputs(
+    server_key: &shortint::ServerKey,
+    probe: &[Vec<Ciphertext>],
+    luts: &[Vec<LookupTableOwned>],
+    threshold: u64,
+    num_sum_blocks: usize,
+) -> Result<(), AuthenticationError> {
+    if probe.len() != luts.len() {
+        return Err(AuthenticationError::FeatureCountMismatch {
+            probe: probe.len(),
+            template: luts.len(),
+        });
+    }
+
+    validate_template(server_key, luts, threshold, num_sum_blocks)?;
+
+    let probe_params = probe_conformance_params(server_key);
+    for (feature, (probes, feature_luts)) in probe.iter().zip(luts).enumerate() {
+        if probes.len() != feature_luts.len() {
+            return Err(AuthenticationError::BlockCountMismatch {
+                feature,
+                probe: probes.len(),
+                template: feature_luts.len(),
+            });
+        }
+        if let Some(block) = probes
+            .iter()
+            .position(|ct| !ct.is_conformant(&probe_params))
+        {
+            return Err(AuthenticationError::NonConformantProbe { feature, block });
+        }
+    }
+
+    Ok(())
+}
+
+/// [`validate_inputs`] for the two's complement sums of
+/// [`all_in_one_signed`](super::all_in_one_signed), whose `threshold` has to fit the signed range
+/// of `num_sum_blocks` blocks.
+pub fn validate_signed_inputs(
+    server_key: &shortint::ServerKey,
+    probe: &[Vec<Ciphertext>],
+    luts: &[Vec<LookupTableOwned>],
+    threshold: i64,
+    num_sum_blocks: usize,
+) -> Result<(), AuthenticationError> {
+    validate_inputs(server_key, probe, luts, 0, num_sum_blocks)?;
+
+    let sum_bits = server_key.message_modulus.0.ilog2() as usize * num_sum_blocks;
+    if sum_bits == 0
+        || (sum_bits < i64::BITS as usize && (threshold >> (sum_bits - 1)) != threshold >> 63)
+    {
+        return Err(AuthenticationError::SignedThresholdOutOfRange {
+            threshold,
+            num_sum_blocks,
+        });
+    }
+    Ok(())
+}
+
+/// [`validate_inputs`] for the digit encoded probes of [`all_in_one_wide`](super::all_in_one_wide).
+///
+/// Every feature has one set of LUTs per high digit, which is checked as a template of its own.
+pub fn validate_wide_inputs(
+    server_key: &shortint::ServerKey,
+    probe: &[(Ciphertext, Ciphertext)],
+    luts: &[Vec<Vec<LookupTableOwned>>],
+    threshold: u64,
+    num_sum_blocks: usize,
+) -> Result<(), AuthenticationError> {
+    if probe.len() != luts.len() {
+        return Err(AuthenticationError::FeatureCountMismatch {
+            probe: probe.len(),
+            template: luts.len(),
+        });
+    }
+
+    let total_modulus = server_key.message_modulus.0 * server_key.carry_modulus.0;
+    let low_count =
+        common::wide_low_digit_count(server_key.message_modulus, server_key.carry_modulus);
+    let low_params = digit_conformance_params(server_key, MessageModulus(low_count));
+    let high_params = probe_conformance_params(server_key);
+    for (feature, ((low, high), feature_luts)) in probe.iter().zip(luts).enumerate() {
+        // The high digit selects one set of LUTs, there cannot be more sets than its values
+        if feature_luts.len() > total_modulus {
+            return Err(AuthenticationError::BlockCountMismatch {
+                feature,
+                probe: total_modulus,
+                template: feature_luts.len(),
+            });
+        }
+        validate_template(server_key, feature_luts, threshold, num_sum_blocks).map_err(
+            |err| match err {
+                AuthenticationError::FeatureWiderThanSum {
+                    blocks,
+                    num_sum_blocks,
+                    ..
+                } => AuthenticationError::FeatureWiderThanSum {
+                    feature,
+                    blocks,
+                    num_sum_blocks,
+                },
+                AuthenticationError::NonConformantLut { block, .. } => {
+                    AuthenticationError::NonConformantLut { feature, block }
+                }
+                err => err,
+            },
+        )?;
+        if !low.is_conformant(&low_params) {
+            return Err(AuthenticationError::NonConformantProbe { feature, block: 0 });
+        }
+        if !high.is_conformant(&high_params) {
+            return Err(AuthenticationError::NonConformantProbe { feature, block: 1 });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core_crypto::biometrics::common;
+    use crate::shortint::engine::ShortintEngine;
+    use crate::shortint::parameters::{
+        PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS,
+    };
+    use crate::shortint::ClientKey;
+
+    const ROW: [u64; 4] = [1, 2, 3, 0];
+
+    fn inputs(client_key: &ClientKey) -> (Vec<Vec<Ciphertext>>, Vec<Vec<LookupTableOwned>>) {
+        let mut engine = ShortintEngine::new();
+        let rows = vec![vec![&ROW[..], &ROW[..]], vec![&ROW[..]]];
+        let luts = common::generate_lookup_tables_individual_from_rows(
+            &rows,
+            client_key,
+            client_key.parameters,
+            &mut engine,
+        );
+        let total_modulus = MessageModulus(
+            client_key.parameters.message_modulus().0 * client_key.parameters.carry_modulus().0,
+        );
+        let probe = rows
+            .iter()
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .map(|_| client_key.encrypt_with_message_modulus(1, total_modulus))
+                    .collect()
+            })
+            .collect();
+        (probe, luts)
+    }
+
+    #[test]
+    fn validate_shapes_and_parameters() {
+        let client_key = ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let server_key = shortint::ServerKey::new(&client_key);
+        let (probe, luts) = inputs(&client_key);
+
+        assert_eq!(validate_inputs(&server_key, &probe, &luts, 10, 2), Ok(()));
+        assert_eq!(
+            validate_inputs(&server_key, &probe[..1], &luts, 10, 2),
+            Err(AuthenticationError::FeatureCountMismatch {
+                probe: 1,
+                template: 2
+            })
+        );
+        assert_eq!(
+            validate_inputs(&server_key, &probe, &luts, 10, 1),
+            Err(AuthenticationError::ThresholdOutOfRange {
+                threshold: 10,
+                num_sum_blocks: 1
+            })
+        );
+        assert_eq!(
+            validate_inputs(&server_key, &probe, &luts, 3, 1),
+            Err(AuthenticationError::FeatureWiderThanSum {
+                feature: 0,
+                blocks: 2,
+                num_sum_blocks: 1
+            })
+        );
+
+        // Two blocks of 2 bits hold -8..=7 in two's complement
+        assert_eq!(validate_signed_inputs(&server_key, &probe, &luts, -8, 2), Ok(()));
+        assert_eq!(validate_signed_inputs(&server_key, &probe, &luts, 7, 2), Ok(()));
+        for threshold in [-9, 8] {
+            assert_eq!(
+                validate_signed_inputs(&server_key, &probe, &luts, threshold, 2),
+                Err(AuthenticationError::SignedThresholdOutOfRange {
+                    threshold,
+                    num_sum_blocks: 2
+                })
+            );
+        }
+
+        let mut short_probe = probe.clone();
+        short_probe[1].clear();
+        assert_eq!(
+            validate_inputs(&server_key, &short_probe, &luts, 10, 2),
+            Err(AuthenticationError::BlockCountMismatch {
+                feature: 1,
+                probe: 0,
+                template: 1
+            })
+        );
+
+        let mut other_probe = probe;
+        other_probe[1][0] = client_key.encrypt(1);
+        assert_eq!(
+            validate_inputs(&server_key, &other_probe, &luts, 10, 2),
+            Err(AuthenticationError::NonConformantProbe {
+                feature: 1,
+                block: 0
+            })
+        );
+
+        // LUTs of another parameter set
+        let other_client_key = ClientKey::new(PARAM_MESSAGE_3_CARRY_3_KS_PBS);
+        let (probe, _) = inputs(&client_key);
+        let (_, other_luts) = inputs(&other_client_key);
+        assert_eq!(
+            validate_inputs(&server_key, &probe, &other_luts, 10, 2),
+            Err(AuthenticationError::NonConformantLut {
+                feature: 0,
+                block: 0
+            })
+        );
+    }
+}
@@ -1,8 +1,11 @@
 
+#[cfg(feature = "gpu")]
 pub mod gpu;
 pub mod common;
+#[cfg(feature = "gpu")]
 pub mod cpu_gpu;
 pub mod cpu;
+pub mod planner;
 
 pub fn this_name_is_very_expressive(){
     /*
@@ -0,0 +1,233 @@
+use crate::shortint::PBSParameters;
+
+/// Shape of a HELR comparison, mirrors the fields of `config::Config` used by the pipeline.
+#[derive(Clone, Copy, Debug)]
+pub struct PipelineShape {
+    pub num_tables: usize,
+    pub num_blocks: usize,
+    pub block_length: usize,
+    pub num_blocks_sum: usize,
+    /// Largest sum the (offset) HELR tables can produce, if known. Without it the planner assumes
+    /// every table can output `2^(block_length * num_blocks) - 1`.
+    pub max_sum: Option<u64>,
+}
+
+impl PipelineShape {
+    pub fn worst_case_sum(&self) -> u64 {
+        self.max_sum.unwrap_or_else(|| {
+            let max_table_value = (1u64 << (self.block_length * self.num_blocks)) - 1;
+            max_table_value * self.num_tables as u64
+        })
+    }
+}
+
+/// Cost and correctness estimate of running a [`PipelineShape`] with a given parameter set.
+#[derive(Clone, Copy, Debug)]
+pub struct Plan {
+    pub worst_case_sum: u64,
+    /// Number of radix blocks needed to hold `worst_case_sum`
+    pub required_sum_blocks: usize,
+    /// Number of ciphertexts added together before the sum has to be bootstrapped
+    pub additions_between_bootstraps: usize,
+    pub lut_pbs_count: usize,
+    pub sum_pbs_count: usize,
+    pub comparison_pbs_count: usize,
+    /// Union bound of the failure probability of all PBS of one authentication
+    pub log2_p_fail: f64,
+}
+
+impl Plan {
+    pub fn total_pbs_count(&self) -> usize {
+        self.lut_pbs_count + self.sum_pbs_count + self.comparison_pbs_count
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlanError {
+    /// The LUT outputs are decomposed in base `2^block_length` which has to match the message
+    /// modulus of the radix sum
+    BlockLengthMismatch { block_length: usize, message_modulus: usize },
+    /// Probes are encrypted over `2 * block_length` bits which must fit in message and carry
+    ProbeDoesNotFit { probe_modulus: usize, total_modulus: usize },
+    /// `num_blocks_sum` is too small to hold the worst case sum
+    SumTooNarrow { required_blocks: usize, num_blocks_sum: usize },
+    /// The unchecked sum adds more ciphertexts than the noise of the parameter set allows
+    NoiseLevelExceeded { additions: usize, max_noise_level: usize },
+    FailureProbabilityTooHigh { log2_p_fail: f64, max_log2_p_fail: f64 },
+}
+
+/// Counts the PBS of `unchecked_partial_sum_ciphertexts_vec_parallelized` for `columns[i]`
+/// non-trivial blocks in column `i`, following the same chunking.
+fn partial_sum_pbs_count(mut columns: Vec<usize>, chunk_size: usize) -> usize {
+    let num_columns = columns.len();
+    let mut pbs_count = 0;
+    while columns.iter().any(|&c| c > chunk_size) {
+        let mut next = vec![0; num_columns];
+        for (i, &len) in columns.iter().enumerate() {
+            if len < chunk_size {
+                next[i] += len;
+                continue;
+            }
+            let chunks = len / chunk_size;
+            next[i] += len % chunk_size + chunks;
+            if i < num_columns - 1 {
+                next[i + 1] += chunks;
+                pbs_count += 2 * chunks;
+            } else {
+                pbs_count += chunks;
+            }
+        }
+        columns = next;
+    }
+    pbs_count
+}
+
+/// Checks that `params` can evaluate `shape` and estimates its cost.
+///
+/// PBS counts of the sum and comparison are estimates: the carry propagation and the scalar
+/// comparison are counted as two PBS per block of the sum.
+pub fn plan<P: Into<PBSParameters>>(
+    shape: &PipelineShape,
+    params: P,
+    max_log2_p_fail: f64,
+) -> Result<Plan, PlanError> {
+    let params: PBSParameters = params.into();
+    let message_modulus = params.message_modulus().0;
+    let total_modulus = message_modulus * params.carry_modulus().0;
+
+    if message_modulus != 1 << shape.block_length {
+        return Err(PlanError::BlockLengthMismatch {
+            block_length: shape.block_length,
+            message_modulus,
+        });
+    }
+
+    let probe_modulus = 1 << (2 * shape.block_length);
+    if probe_modulus > total_modulus {
+        return Err(PlanError::ProbeDoesNotFit {
+            probe_modulus,
+            total_modulus,
+        });
+    }
+
+    let worst_case_sum = shape.worst_case_sum();
+    let mut required_sum_blocks = 1;
+    while worst_case_sum >> (shape.block_length * required_sum_blocks) > 0 {
+        required_sum_blocks += 1;
+    }
+    if required_sum_blocks > shape.num_blocks_sum {
+        return Err(PlanError::SumTooNarrow {
+            required_blocks: required_sum_blocks,
+            num_blocks_sum: shape.num_blocks_sum,
+        });
+    }
+
+    // Same bound as the integer sum: fill the carry space with messages of maximal degree
+    let additions_between_bootstraps = (total_modulus - 1) / (message_modulus - 1);
+    let max_noise_level = params.max_noise_level().get();
+    if additions_between_bootstraps > max_noise_level {
+        return Err(PlanError::NoiseLevelExceeded {
+            additions: additions_between_bootstraps,
+            max_noise_level,
+        });
+    }
+
+    let lut_pbs_count = shape.num_tables * shape.num_blocks;
+    let mut columns = vec![0; shape.num_blocks_sum];
+    columns[..shape.num_blocks.min(shape.num_blocks_sum)].fill(shape.num_tables);
+    let sum_pbs_count =
+        partial_sum_pbs_count(columns, additions_between_bootstraps) + 2 * shape.num_blocks_sum;
+    let comparison_pbs_count = 2 * shape.num_blocks_sum;
+
+    let params_log2_p_fail = match params {
+        PBSParameters::PBS(p) => p.log2_p_fail,
+        PBSParameters::MultiBitPBS(p) => p.log2_p_fail,
+    };
+    let total_pbs_count = lut_pbs_count + sum_pbs_count + comparison_pbs_count;
+    let log2_p_fail = params_log2_p_fail + (total_pbs_count as f64).log2();
+    if log2_p_fail > max_log2_p_fail {
+        return Err(PlanError::FailureProbabilityTooHigh {
+            log2_p_fail,
+            max_log2_p_fail,
+        });
+    }
+
+    Ok(Plan {
+        worst_case_sum,
+        required_sum_blocks,
+        additions_between_bootstraps,
+        lut_pbs_count,
+        sum_pbs_count,
+        comparison_pbs_count,
+        log2_p_fail,
+    })
+}
+
+/// Returns the first of `candidates` able to evaluate `shape`, candidates should therefore be
+/// ordered from cheapest to most expensive. If none fits, the rejection reason of every
+/// candidate is returned.
+pub fn select<P: Into<PBSParameters> + Copy>(
+    shape: &PipelineShape,
+    candidates: &[P],
+    max_log2_p_fail: f64,
+) -> Result<(P, Plan), Vec<PlanError>> {
+    let mut errors = Vec::with_capacity(candidates.len());
+    for &candidate in candidates {
+        match plan(shape, candidate, max_log2_p_fail) {
+            Ok(plan) => return Ok((candidate, plan)),
+            Err(err) => errors.push(err),
+        }
+    }
+    Err(errors)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shortint::parameters::{PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS};
+
+    const FRGC_SHAPE: PipelineShape = PipelineShape {
+        num_tables: 94,
+        num_blocks: 4,
+        block_length: 3,
+        num_blocks_sum: 4,
+        max_sum: Some(3000),
+    };
+
+    #[test]
+    fn select_matches_block_length() {
+        let (params, plan) = select(
+            &FRGC_SHAPE,
+            &[PARAM_MESSAGE_2_CARRY_2_KS_PBS, PARAM_MESSAGE_3_CARRY_3_KS_PBS],
+            -20.0,
+        )
+        .unwrap();
+        assert_eq!(params, PARAM_MESSAGE_3_CARRY_3_KS_PBS);
+        assert_eq!(plan.required_sum_blocks, 4);
+        assert_eq!(plan.lut_pbs_count, 94 * 4);
+        assert!(plan.log2_p_fail > PARAM_MESSAGE_3_CARRY_3_KS_PBS.log2_p_fail);
+    }
+
+    #[test]
+    fn reject_narrow_sum() {
+        let shape = PipelineShape {
+            max_sum: None,
+            ..FRGC_SHAPE
+        };
+        assert_eq!(
+            plan(&shape, PARAM_MESSAGE_3_CARRY_3_KS_PBS, -20.0).unwrap_err(),
+            PlanError::SumTooNarrow {
+                required_blocks: 7,
+                num_blocks_sum: 4
+            }
+        );
+    }
+
+    #[test]
+    fn partial_sum_count() {
+        // 10 blocks in a single column with chunks of 5: two chunks, no carry column
+        assert_eq!(partial_sum_pbs_count(vec![10], 5), 2);
+        // Already small enough, nothing to bootstrap
+        assert_eq!(partial_sum_pbs_count(vec![5, 5], 5), 0);
+    }
+}
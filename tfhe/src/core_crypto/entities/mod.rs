@@ -53,6 +53,12 @@ pub use crate::core_crypto::fft_impl::fft64::crypto::ggsw::{
     FourierGgswCiphertext, FourierGgswCiphertextList, FourierGgswLevelMatrix, FourierGgswLevelRow,
 };
 pub use crate::core_crypto::fft_impl::fft64::math::polynomial::FourierPolynomial;
+#[cfg(feature = "experimental-ntt")]
+pub use crate::core_crypto::ntt_impl::ntt64::crypto::bootstrap::{
+    NttLweBootstrapKey, NttLweBootstrapKeyOwned,
+};
+#[cfg(feature = "experimental-ntt")]
+pub use crate::core_crypto::ntt_impl::ntt64::crypto::ggsw::NttGgswCiphertext;
 #[cfg(feature = "zk-pok-experimental")]
 pub use crate::zk::*;
 pub use cleartext::*;
@@ -0,0 +1,323 @@
+//! Module containing primitives pertaining to the threshold decryption of
+//! [`LWE ciphertexts`](`LweCiphertext`).
+//!
+//! Unlike the shared key generations of this module, which share coefficients between two keys of
+//! the same owner, the key is split here between several parties so that at least `threshold` of
+//! them have to cooperate to decrypt.
+//!
+//! The shares are either handed out by a trusted dealer who knows the whole key
+//! ([`allocate_and_deal_lwe_secret_key`]), or generated without dealer: every party deals its own
+//! contribution to the key and sums the shares it receives ([`sum_lwe_secret_key_shares`]), the
+//! key being the sum of the contributions nobody sees.
+
+use crate::core_crypto::algorithms::slice_algorithms::slice_wrapping_dot_product;
+use crate::core_crypto::commons::generators::EncryptionRandomGenerator;
+use crate::core_crypto::commons::math::random::{
+    ByteRandomGenerator, Distribution, RandomGenerable, Uniform,
+};
+use crate::core_crypto::commons::numeric::UnsignedInteger;
+use crate::core_crypto::commons::traits::Container;
+use crate::core_crypto::entities::{LweCiphertext, LweSecretKey, Plaintext};
+use crate::core_crypto::experimental::entities::{LweDecryptionShare, LweSecretKeyShare};
+
+/// Return the sets of `threshold - 1` parties among `party_count` as bit masks, in increasing
+/// order.
+fn excluded_sets(threshold: usize, party_count: usize) -> Vec<u64> {
+    let size = threshold - 1;
+    if size == 0 {
+        return vec![0];
+    }
+
+    // Enumerate the masks with `size` bits set (Gosper's hack)
+    let mut sets = Vec::new();
+    let mut set: u64 = (1 << size) - 1;
+    while set < 1 << party_count {
+        sets.push(set);
+        let lowest = set & set.wrapping_neg();
+        let ripple = set + lowest;
+        set = (((ripple ^ set) >> 2) / lowest) | ripple;
+    }
+    sets
+}
+
+fn participants_mask(participants: &[usize], party_count: usize) -> u64 {
+    participants.iter().fold(0, |mask, &party| {
+        assert!(
+            party < party_count,
+            "Party {party} is out of range for {party_count} parties"
+        );
+        mask | (1 << party)
+    })
+}
+
+/// Deal the shares of an [`LWE secret key`](`LweSecretKey`) to `party_count` parties, any
+/// `threshold` of them are needed to decrypt with [`partially_decrypt_lwe_ciphertext`] and
+/// [`combine_lwe_decryption_shares`].
+///
+/// The caller acts as a trusted dealer: it sees the whole key and is expected to drop it once the
+/// shares are distributed.
+///
+/// # Example
+///
+/// ```rust
+/// use tfhe::core_crypto::experimental::prelude::*;
+/// use tfhe::core_crypto::prelude::*;
+///
+/// let lwe_dimension = LweDimension(742);
+/// let lwe_noise_distribution =
+///     Gaussian::from_dispersion_parameter(StandardDev(0.000007069849454709433), 0.0);
+/// let ciphertext_modulus = CiphertextModulus::new_native();
+///
+/// let mut seeder = new_seeder();
+/// let seeder = seeder.as_mut();
+/// let mut encryption_generator =
+///     EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+/// let mut secret_generator =
+///     SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+///
+/// let lwe_secret_key =
+///     allocate_and_generate_new_binary_lwe_secret_key(lwe_dimension, &mut secret_generator);
+///
+/// // 2 out of 3 parties are needed to decrypt
+/// let shares = allocate_and_deal_lwe_secret_key(&lwe_secret_key, 2, 3, &mut encryption_generator);
+///
+/// let msg = 3u64;
+/// let plaintext = Plaintext(msg << 60);
+/// let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+///     &lwe_secret_key,
+///     plaintext,
+///     lwe_noise_distribution,
+///     ciphertext_modulus,
+///     &mut encryption_generator,
+/// );
+///
+/// let participants = [0, 2];
+/// let decryption_shares: Vec<_> = participants
+///     .iter()
+///     .map(|&party| {
+///         partially_decrypt_lwe_ciphertext(
+///             &shares[party],
+///             &participants,
+///             &lwe,
+///             TUniform::<u64>::new(40),
+///             &mut encryption_generator,
+///         )
+///     })
+///     .collect();
+///
+/// let decrypted = combine_lwe_decryption_shares(&lwe, &decryption_shares);
+///
+/// let decomposer = SignedDecomposer::new(DecompositionBaseLog(4), DecompositionLevelCount(1));
+/// let cleartext = decomposer.closest_representable(decrypted.0) >> 60;
+/// assert_eq!(cleartext, msg);
+/// ```
+pub fn allocate_and_deal_lwe_secret_key<Scalar, KeyCont, Gen>(
+    lwe_secret_key: &LweSecretKey<KeyCont>,
+    threshold: usize,
+    party_count: usize,
+    generator: &mut EncryptionRandomGenerator<Gen>,
+) -> Vec<LweSecretKeyShare<Scalar>>
+where
+    Scalar: UnsignedInteger + RandomGenerable<Uniform>,
+    KeyCont: Container<Element = Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    assert!(
+        party_count < u64::BITS as usize,
+        "At most {} parties are supported, got {party_count}",
+        u64::BITS - 1
+    );
+    assert!(
+        (1..=party_count).contains(&threshold),
+        "The threshold ({threshold}) must be in 1..={party_count}"
+    );
+
+    let lwe_dimension = lwe_secret_key.lwe_dimension();
+    let sets = excluded_sets(threshold, party_count);
+
+    // Uniform parts for all sets but the last one, which completes the sum to the key
+    let mut last_part = lwe_secret_key.as_ref().to_vec();
+    let mut parts = Vec::with_capacity(sets.len());
+    for (index, &set) in sets.iter().enumerate() {
+        let mut part = LweSecretKey::new_empty_key(Scalar::ZERO, lwe_dimension);
+        if index + 1 == sets.len() {
+            part.as_mut().copy_from_slice(&last_part);
+        } else {
+            generator.fill_slice_with_random_uniform_mask(part.as_mut());
+            for (remaining, &coef) in last_part.iter_mut().zip(part.as_ref().iter()) {
+                *remaining = remaining.wrapping_sub(coef);
+            }
+        }
+        parts.push((set, part));
+    }
+
+    (0..party_count)
+        .map(|party| {
+            let held = parts
+                .iter()
+                .filter(|(set, _)| set & (1 << party) == 0)
+                .cloned()
+                .collect();
+            LweSecretKeyShare::from_parts(party, threshold, party_count, held)
+        })
+        .collect()
+}
+
+/// Sum the shares held by one party of several [`LWE secret keys`](`LweSecretKey`) into its
+/// share of the sum of the keys.
+///
+/// This is the last step of a distributed key generation: each party draws its own contribution
+/// to the key and deals it with [`allocate_and_deal_lwe_secret_key`], then every party sums the
+/// shares it received from all the others. Fewer than `threshold` parties learn nothing about the
+/// resulting key as long as one contribution is honestly drawn.
+///
+/// # Panics
+///
+/// Panics if `shares` is empty, or if the shares are not held by the same party or do not split
+/// keys of the same dimension between the same parties.
+pub fn sum_lwe_secret_key_shares<Scalar>(
+    shares: &[LweSecretKeyShare<Scalar>],
+) -> LweSecretKeyShare<Scalar>
+where
+    Scalar: UnsignedInteger,
+{
+    assert!(!shares.is_empty(), "At least one key share is needed");
+
+    let first = &shares[0];
+    let mut parts = first.parts().to_vec();
+    for share in &shares[1..] {
+        assert!(
+            share.party() == first.party()
+                && share.threshold() == first.threshold()
+                && share.party_count() == first.party_count(),
+            "Key shares held by different parties or with different thresholds"
+        );
+        assert!(
+            share.lwe_dimension() == first.lwe_dimension(),
+            "Mismatched LweDimension between key shares. Got {:?} and {:?}.",
+            share.lwe_dimension(),
+            first.lwe_dimension()
+        );
+
+        // Parts are dealt in the same order for every key, see `excluded_sets`
+        for ((set, part), (share_set, share_part)) in parts.iter_mut().zip(share.parts()) {
+            assert_eq!(set, share_set, "Key shares split differently");
+            for (coef, &share_coef) in part.as_mut().iter_mut().zip(share_part.as_ref()) {
+                *coef = coef.wrapping_add(share_coef);
+            }
+        }
+    }
+
+    LweSecretKeyShare::from_parts(first.party(), first.threshold(), first.party_count(), parts)
+}
+
+/// Compute the decryption share of `share` for an
+/// [`LWE ciphertext`](`LweCiphertext`) decrypted by `participants`.
+///
+/// Every part of the key is used by exactly one participant, the one with the smallest index
+/// among the participants holding it. The share is masked with noise drawn from
+/// `smudging_noise_distribution`, which has to be wide enough to statistically hide the noise of
+/// `lwe_ciphertext` while the sum of the smudging noises of all participants stays below the
+/// precision lost at decoding.
+///
+/// # Panics
+///
+/// Panics if there are fewer participants than the threshold of the share or if the party of the
+/// share does not participate.
+pub fn partially_decrypt_lwe_ciphertext<Scalar, NoiseDistribution, InputCont, Gen>(
+    share: &LweSecretKeyShare<Scalar>,
+    participants: &[usize],
+    lwe_ciphertext: &LweCiphertext<InputCont>,
+    smudging_noise_distribution: NoiseDistribution,
+    generator: &mut EncryptionRandomGenerator<Gen>,
+) -> LweDecryptionShare<Scalar>
+where
+    Scalar: UnsignedInteger + RandomGenerable<NoiseDistribution>,
+    NoiseDistribution: Distribution,
+    InputCont: Container<Element = Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    assert!(
+        lwe_ciphertext.ciphertext_modulus().is_native_modulus(),
+        "Threshold decryption only supports the native modulus"
+    );
+    assert!(
+        lwe_ciphertext.lwe_size().to_lwe_dimension() == share.lwe_dimension(),
+        "Mismatch between LweDimension of input ciphertext and key share. \
+        Got {:?} in input, and {:?} in key share.",
+        lwe_ciphertext.lwe_size().to_lwe_dimension(),
+        share.lwe_dimension()
+    );
+
+    let mask = participants_mask(participants, share.party_count());
+    assert!(
+        mask.count_ones() as usize >= share.threshold(),
+        "{} participants cannot decrypt, the threshold is {}",
+        mask.count_ones(),
+        share.threshold()
+    );
+    assert!(
+        mask & (1 << share.party()) != 0,
+        "Party {} is not among the participants",
+        share.party()
+    );
+
+    let lwe_mask = lwe_ciphertext.get_mask();
+    let value = share
+        .parts()
+        .iter()
+        .filter(|(set, _)| (mask & !set).trailing_zeros() as usize == share.party())
+        .fold(Scalar::ZERO, |acc, (_, part)| {
+            acc.wrapping_add(slice_wrapping_dot_product(lwe_mask.as_ref(), part.as_ref()))
+        });
+
+    let smudging_noise: Scalar =
+        generator.random_noise_from_distribution(smudging_noise_distribution);
+
+    LweDecryptionShare::new(share.party(), mask, value.wrapping_add(smudging_noise))
+}
+
+/// Combine the decryption shares of all the participants into the noisy
+/// [`Plaintext`] of `lwe_ciphertext`, which is decoded like the output of
+/// [`decrypt_lwe_ciphertext`](`crate::core_crypto::algorithms::decrypt_lwe_ciphertext`).
+///
+/// # Panics
+///
+/// Panics if the shares were computed for different participants or if a participant is missing.
+pub fn combine_lwe_decryption_shares<Scalar, InputCont>(
+    lwe_ciphertext: &LweCiphertext<InputCont>,
+    decryption_shares: &[LweDecryptionShare<Scalar>],
+) -> Plaintext<Scalar>
+where
+    Scalar: UnsignedInteger,
+    InputCont: Container<Element = Scalar>,
+{
+    assert!(
+        !decryption_shares.is_empty(),
+        "At least one decryption share is needed"
+    );
+
+    let participants = decryption_shares[0].participants();
+    let mut parties = 0u64;
+    for decryption_share in decryption_shares {
+        assert_eq!(
+            decryption_share.participants(),
+            participants,
+            "Decryption shares were computed for different participants"
+        );
+        parties |= 1 << decryption_share.party();
+    }
+    assert!(
+        parties == participants && decryption_shares.len() == participants.count_ones() as usize,
+        "Missing or duplicated decryption shares"
+    );
+
+    let body = *lwe_ciphertext.get_body().data;
+    Plaintext(
+        decryption_shares
+            .iter()
+            .fold(body, |acc, decryption_share| {
+                acc.wrapping_sub(decryption_share.value())
+            }),
+    )
+}
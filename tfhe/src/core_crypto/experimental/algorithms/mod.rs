@@ -2,6 +2,7 @@ pub mod glwe_fast_keyswitch;
 pub mod glwe_partial_sample_extraction;
 pub mod lwe_shrinking_keyswitch;
 pub mod lwe_shrinking_keyswitch_key_generation;
+pub mod lwe_threshold_decryption;
 pub mod partial_glwe_secret_key_generation;
 pub mod pseudo_ggsw_conversion;
 pub mod pseudo_ggsw_encryption;
@@ -12,6 +13,7 @@ pub use glwe_fast_keyswitch::*;
 pub use glwe_partial_sample_extraction::*;
 pub use lwe_shrinking_keyswitch::*;
 pub use lwe_shrinking_keyswitch_key_generation::*;
+pub use lwe_threshold_decryption::*;
 pub use partial_glwe_secret_key_generation::*;
 pub use pseudo_ggsw_conversion::*;
 pub use pseudo_ggsw_encryption::*;
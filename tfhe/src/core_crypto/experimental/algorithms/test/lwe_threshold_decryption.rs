@@ -0,0 +1,181 @@
+use super::*;
+
+const LWE_DIMENSION: LweDimension = LweDimension(742);
+const LWE_NOISE_STD_DEV: StandardDev = StandardDev(0.000007069849454709433);
+const SMUDGING_BOUND_LOG2: u32 = 48;
+
+fn lwe_encrypt_threshold_decrypt(threshold: usize, party_count: usize) {
+    let log_precision = MessageModulusLog(4);
+    let ciphertext_modulus = CiphertextModulus::<u64>::new_native();
+    let msg_modulus = 1u64 << log_precision.0;
+    let delta = get_encoding_with_padding(ciphertext_modulus) / msg_modulus;
+    let noise_distribution = DynamicDistribution::new_gaussian_from_std_dev(LWE_NOISE_STD_DEV);
+
+    let mut rsc = TestResources::new();
+
+    let lwe_secret_key = allocate_and_generate_new_binary_lwe_secret_key(
+        LWE_DIMENSION,
+        &mut rsc.secret_random_generator,
+    );
+    let shares = allocate_and_deal_lwe_secret_key(
+        &lwe_secret_key,
+        threshold,
+        party_count,
+        &mut rsc.encryption_random_generator,
+    );
+
+    // Every qualified set of parties made of consecutive indices, plus everyone
+    let mut participant_sets: Vec<Vec<usize>> = (0..=party_count - threshold)
+        .map(|first| (first..first + threshold).collect())
+        .collect();
+    participant_sets.push((0..party_count).collect());
+
+    for msg in 0..msg_modulus {
+        let ct = allocate_and_encrypt_new_lwe_ciphertext(
+            &lwe_secret_key,
+            Plaintext(msg * delta),
+            noise_distribution,
+            ciphertext_modulus,
+            &mut rsc.encryption_random_generator,
+        );
+
+        for participants in participant_sets.iter() {
+            let decryption_shares: Vec<_> = participants
+                .iter()
+                .map(|&party| {
+                    partially_decrypt_lwe_ciphertext(
+                        &shares[party],
+                        participants,
+                        &ct,
+                        TUniform::<u64>::new(SMUDGING_BOUND_LOG2),
+                        &mut rsc.encryption_random_generator,
+                    )
+                })
+                .collect();
+
+            let decrypted = combine_lwe_decryption_shares(&ct, &decryption_shares);
+            let decoded = round_decode(decrypted.0, delta) % msg_modulus;
+            assert_eq!(decoded, msg, "Err with participants {participants:?}");
+        }
+    }
+}
+
+#[test]
+fn lwe_encrypt_threshold_decrypt_2_of_3() {
+    lwe_encrypt_threshold_decrypt(2, 3)
+}
+
+#[test]
+fn lwe_encrypt_threshold_decrypt_3_of_5() {
+    lwe_encrypt_threshold_decrypt(3, 5)
+}
+
+#[test]
+fn lwe_distributed_key_threshold_decrypt() {
+    let (threshold, party_count) = (2, 3);
+    let log_precision = MessageModulusLog(4);
+    let ciphertext_modulus = CiphertextModulus::<u64>::new_native();
+    let msg_modulus = 1u64 << log_precision.0;
+    let delta = get_encoding_with_padding(ciphertext_modulus) / msg_modulus;
+    let noise_distribution = DynamicDistribution::new_gaussian_from_std_dev(LWE_NOISE_STD_DEV);
+
+    let mut rsc = TestResources::new();
+
+    // Every party deals its own contribution, shares[dealer][party]
+    let contributions: Vec<_> = (0..party_count)
+        .map(|_| {
+            allocate_and_generate_new_binary_lwe_secret_key(
+                LWE_DIMENSION,
+                &mut rsc.secret_random_generator,
+            )
+        })
+        .collect();
+    let dealt: Vec<_> = contributions
+        .iter()
+        .map(|contribution| {
+            allocate_and_deal_lwe_secret_key(
+                contribution,
+                threshold,
+                party_count,
+                &mut rsc.encryption_random_generator,
+            )
+        })
+        .collect();
+    let shares: Vec<_> = (0..party_count)
+        .map(|party| {
+            let received: Vec<_> = dealt.iter().map(|shares| shares[party].clone()).collect();
+            sum_lwe_secret_key_shares(&received)
+        })
+        .collect();
+
+    // Only known to the test, to encrypt under the key the parties generated
+    let mut lwe_secret_key = LweSecretKey::new_empty_key(0u64, LWE_DIMENSION);
+    for contribution in contributions.iter() {
+        for (coef, &contribution_coef) in lwe_secret_key
+            .as_mut()
+            .iter_mut()
+            .zip(contribution.as_ref())
+        {
+            *coef = coef.wrapping_add(contribution_coef);
+        }
+    }
+
+    for msg in 0..msg_modulus {
+        let ct = allocate_and_encrypt_new_lwe_ciphertext(
+            &lwe_secret_key,
+            Plaintext(msg * delta),
+            noise_distribution,
+            ciphertext_modulus,
+            &mut rsc.encryption_random_generator,
+        );
+
+        for participants in [[0, 1], [0, 2], [1, 2]] {
+            let decryption_shares: Vec<_> = participants
+                .iter()
+                .map(|&party| {
+                    partially_decrypt_lwe_ciphertext(
+                        &shares[party],
+                        &participants,
+                        &ct,
+                        TUniform::<u64>::new(SMUDGING_BOUND_LOG2),
+                        &mut rsc.encryption_random_generator,
+                    )
+                })
+                .collect();
+
+            let decrypted = combine_lwe_decryption_shares(&ct, &decryption_shares);
+            let decoded = round_decode(decrypted.0, delta) % msg_modulus;
+            assert_eq!(decoded, msg, "Err with participants {participants:?}");
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot decrypt")]
+fn lwe_threshold_decrypt_below_threshold() {
+    let mut rsc = TestResources::new();
+
+    let lwe_secret_key = allocate_and_generate_new_binary_lwe_secret_key(
+        LWE_DIMENSION,
+        &mut rsc.secret_random_generator,
+    );
+    let shares = allocate_and_deal_lwe_secret_key(
+        &lwe_secret_key,
+        2,
+        3,
+        &mut rsc.encryption_random_generator,
+    );
+    let ct = LweCiphertext::new(
+        0u64,
+        LWE_DIMENSION.to_lwe_size(),
+        CiphertextModulus::new_native(),
+    );
+
+    let _ = partially_decrypt_lwe_ciphertext(
+        &shares[0],
+        &[0],
+        &ct,
+        TUniform::<u64>::new(SMUDGING_BOUND_LOG2),
+        &mut rsc.encryption_random_generator,
+    );
+}
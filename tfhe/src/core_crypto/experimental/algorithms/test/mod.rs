@@ -3,3 +3,4 @@ use crate::core_crypto::experimental::prelude::*;
 
 mod lwe_fast_keyswitch;
 mod lwe_stair_keyswitch;
+mod lwe_threshold_decryption;
@@ -0,0 +1,94 @@
+//! Module containing the definitions of the [`LweSecretKeyShare`] and of the
+//! [`LweDecryptionShare`].
+
+use crate::core_crypto::commons::numeric::UnsignedInteger;
+use crate::core_crypto::commons::parameters::LweDimension;
+use crate::core_crypto::entities::LweSecretKeyOwned;
+use serde::{Deserialize, Serialize};
+
+/// Share of an [`LWE secret key`](`crate::core_crypto::entities::LweSecretKey`) held by one of
+/// `party_count` parties, any `threshold` parties can decrypt together while fewer learn nothing
+/// about the key.
+///
+/// The key is the sum of one additive part per set of `threshold - 1` parties, every part being
+/// held by all the parties outside of its set (replicated secret sharing). The number of parts
+/// grows like the binomial coefficient of `party_count` and `threshold - 1`, which is meant for a
+/// handful of parties.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweSecretKeyShare<Scalar: UnsignedInteger> {
+    party: usize,
+    threshold: usize,
+    party_count: usize,
+    // (parties that do not hold the part as a bit mask, part)
+    parts: Vec<(u64, LweSecretKeyOwned<Scalar>)>,
+}
+
+impl<Scalar: UnsignedInteger> LweSecretKeyShare<Scalar> {
+    pub(crate) fn from_parts(
+        party: usize,
+        threshold: usize,
+        party_count: usize,
+        parts: Vec<(u64, LweSecretKeyOwned<Scalar>)>,
+    ) -> Self {
+        Self {
+            party,
+            threshold,
+            party_count,
+            parts,
+        }
+    }
+
+    /// Return the index of the party holding the share, in `0..party_count`.
+    pub fn party(&self) -> usize {
+        self.party
+    }
+
+    /// Return the number of parties needed to decrypt.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn party_count(&self) -> usize {
+        self.party_count
+    }
+
+    pub fn lwe_dimension(&self) -> LweDimension {
+        self.parts[0].1.lwe_dimension()
+    }
+
+    pub(crate) fn parts(&self) -> &[(u64, LweSecretKeyOwned<Scalar>)] {
+        &self.parts
+    }
+}
+
+/// Partial decryption of an [`LWE ciphertext`](`crate::core_crypto::entities::LweCiphertext`)
+/// by one party, for a given set of participating parties.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweDecryptionShare<Scalar: UnsignedInteger> {
+    party: usize,
+    participants: u64,
+    value: Scalar,
+}
+
+impl<Scalar: UnsignedInteger> LweDecryptionShare<Scalar> {
+    pub(crate) fn new(party: usize, participants: u64, value: Scalar) -> Self {
+        Self {
+            party,
+            participants,
+            value,
+        }
+    }
+
+    pub fn party(&self) -> usize {
+        self.party
+    }
+
+    /// Return the participating parties as a bit mask.
+    pub fn participants(&self) -> u64 {
+        self.participants
+    }
+
+    pub(crate) fn value(&self) -> Scalar {
+        self.value
+    }
+}
@@ -17,6 +17,8 @@ pub mod prelude;
 pub mod seeders;
 
 pub mod fft_impl;
+#[cfg(feature = "experimental-ntt")]
+pub mod ntt_impl;
 
 #[cfg(feature = "gpu")]
 pub mod gpu;
@@ -24,6 +26,7 @@ pub mod gpu;
 pub mod keycache;
 
 // Experimental section
+#[cfg(feature = "integer")]
+pub mod biometrics;
 #[cfg(feature = "experimental")]
 pub mod experimental;
-pub mod biometrics;
@@ -0,0 +1,25 @@
+//! Number theoretic transform backends, used to bootstrap ciphertexts under prime moduli that the
+//! [`fft_impl`](`super::fft_impl`) backends do not support.
+//!
+//! This module is only available with the `experimental-ntt` feature. Building the bootstrap key
+//! and the look-up table of an NTT bootstrap relies on the following algorithms, which accept any
+//! ciphertext modulus, prime moduli included, whether or not the feature is enabled:
+//! - [`encrypt_glwe_ciphertext`](`crate::core_crypto::algorithms::encrypt_glwe_ciphertext`),
+//!   [`encrypt_glwe_ciphertext_assign`](`crate::core_crypto::algorithms::encrypt_glwe_ciphertext_assign`)
+//!   and [`encrypt_glwe_ciphertext_list`](`crate::core_crypto::algorithms::encrypt_glwe_ciphertext_list`);
+//! - [`decrypt_glwe_ciphertext`](`crate::core_crypto::algorithms::decrypt_glwe_ciphertext`) and
+//!   [`decrypt_glwe_ciphertext_list`](`crate::core_crypto::algorithms::decrypt_glwe_ciphertext_list`);
+//! - [`trivially_encrypt_glwe_ciphertext`](`crate::core_crypto::algorithms::trivially_encrypt_glwe_ciphertext`)
+//!   and [`allocate_and_trivially_encrypt_new_glwe_ciphertext`](`crate::core_crypto::algorithms::allocate_and_trivially_encrypt_new_glwe_ciphertext`);
+//! - [`encrypt_constant_ggsw_ciphertext`](`crate::core_crypto::algorithms::encrypt_constant_ggsw_ciphertext`)
+//!   and [`par_encrypt_constant_ggsw_ciphertext`](`crate::core_crypto::algorithms::par_encrypt_constant_ggsw_ciphertext`),
+//!   [`decrypt_constant_ggsw_ciphertext`](`crate::core_crypto::algorithms::decrypt_constant_ggsw_ciphertext`),
+//!   hence [`generate_lwe_bootstrap_key`](`crate::core_crypto::algorithms::generate_lwe_bootstrap_key`)
+//!   and [`par_generate_lwe_bootstrap_key`](`crate::core_crypto::algorithms::par_generate_lwe_bootstrap_key`);
+//! - [`extract_lwe_sample_from_glwe_ciphertext`](`crate::core_crypto::algorithms::extract_lwe_sample_from_glwe_ciphertext`)
+//!   and [`par_extract_lwe_sample_from_glwe_ciphertext`](`crate::core_crypto::algorithms::par_extract_lwe_sample_from_glwe_ciphertext`).
+//!
+//! Their seeded variants and the multi bit bootstrap key generation still only support power of 2
+//! moduli.
+
+pub mod ntt64;
@@ -0,0 +1,365 @@
+use super::super::math::ntt::Ntt64View;
+use super::ggsw::{cmux, cmux_scratch, NttGgswCiphertext};
+use crate::core_crypto::algorithms::extract_lwe_sample_from_glwe_ciphertext;
+use crate::core_crypto::algorithms::polynomial_algorithms::*;
+use crate::core_crypto::commons::parameters::{
+    CiphertextModulus, DecompositionBaseLog, DecompositionLevelCount, GlweSize, LweDimension,
+    MonomialDegree, PolynomialSize,
+};
+use crate::core_crypto::commons::traits::{
+    Container, ContainerMut, ContiguousEntityContainer, ContiguousEntityContainerMut, Split,
+};
+use crate::core_crypto::commons::utils::izip;
+use crate::core_crypto::entities::*;
+use aligned_vec::{avec, ABox, CACHELINE_ALIGN};
+use dyn_stack::{PodStack, ReborrowMut, SizeOverflow, StackReq};
+
+/// An [`LWE bootstrap key`](`LweBootstrapKey`) under a prime ciphertext modulus, in the NTT
+/// domain.
+///
+/// Unlike the Fourier bootstrap keys, the blind rotation with this key is exact modulo the prime:
+/// the output noise only comes from the key and the rounding of the gadget decomposition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NttLweBootstrapKey<C: Container<Element = u64>> {
+    data: C,
+    polynomial_size: PolynomialSize,
+    input_lwe_dimension: LweDimension,
+    glwe_size: GlweSize,
+    decomposition_base_log: DecompositionBaseLog,
+    decomposition_level_count: DecompositionLevelCount,
+    ciphertext_modulus: CiphertextModulus<u64>,
+}
+
+impl<C: Container<Element = u64>> NttLweBootstrapKey<C> {
+    pub fn from_container(
+        data: C,
+        input_lwe_dimension: LweDimension,
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+        ciphertext_modulus: CiphertextModulus<u64>,
+    ) -> Self {
+        let container_len = input_lwe_dimension.0
+            * polynomial_size.0
+            * decomposition_level_count.0
+            * glwe_size.0
+            * glwe_size.0;
+        assert_eq!(data.container_len(), container_len);
+        Self {
+            data,
+            polynomial_size,
+            input_lwe_dimension,
+            glwe_size,
+            decomposition_base_log,
+            decomposition_level_count,
+            ciphertext_modulus,
+        }
+    }
+
+    /// Return an iterator over the GGSW ciphertexts composing the key.
+    pub fn into_ggsw_iter(self) -> impl DoubleEndedIterator<Item = NttGgswCiphertext<C>>
+    where
+        C: Split,
+    {
+        self.data
+            .split_into(self.input_lwe_dimension.0)
+            .map(move |data| {
+                NttGgswCiphertext::from_container(
+                    data,
+                    self.polynomial_size,
+                    self.glwe_size,
+                    self.decomposition_base_log,
+                    self.decomposition_level_count,
+                    self.ciphertext_modulus,
+                )
+            })
+    }
+
+    pub fn input_lwe_dimension(&self) -> LweDimension {
+        self.input_lwe_dimension
+    }
+
+    pub fn polynomial_size(&self) -> PolynomialSize {
+        self.polynomial_size
+    }
+
+    pub fn glwe_size(&self) -> GlweSize {
+        self.glwe_size
+    }
+
+    pub fn decomposition_base_log(&self) -> DecompositionBaseLog {
+        self.decomposition_base_log
+    }
+
+    pub fn decomposition_level_count(&self) -> DecompositionLevelCount {
+        self.decomposition_level_count
+    }
+
+    pub fn ciphertext_modulus(&self) -> CiphertextModulus<u64> {
+        self.ciphertext_modulus
+    }
+
+    pub fn output_lwe_dimension(&self) -> LweDimension {
+        LweDimension((self.glwe_size.0 - 1) * self.polynomial_size().0)
+    }
+
+    pub fn data(self) -> C {
+        self.data
+    }
+
+    pub fn as_view(&self) -> NttLweBootstrapKey<&[C::Element]> {
+        NttLweBootstrapKey {
+            data: self.data.as_ref(),
+            polynomial_size: self.polynomial_size,
+            input_lwe_dimension: self.input_lwe_dimension,
+            glwe_size: self.glwe_size,
+            decomposition_base_log: self.decomposition_base_log,
+            decomposition_level_count: self.decomposition_level_count,
+            ciphertext_modulus: self.ciphertext_modulus,
+        }
+    }
+
+    pub fn as_mut_view(&mut self) -> NttLweBootstrapKey<&mut [C::Element]>
+    where
+        C: AsMut<[C::Element]>,
+    {
+        NttLweBootstrapKey {
+            data: self.data.as_mut(),
+            polynomial_size: self.polynomial_size,
+            input_lwe_dimension: self.input_lwe_dimension,
+            glwe_size: self.glwe_size,
+            decomposition_base_log: self.decomposition_base_log,
+            decomposition_level_count: self.decomposition_level_count,
+            ciphertext_modulus: self.ciphertext_modulus,
+        }
+    }
+}
+
+pub type NttLweBootstrapKeyOwned = NttLweBootstrapKey<ABox<[u64]>>;
+
+impl NttLweBootstrapKey<ABox<[u64]>> {
+    pub fn new(
+        input_lwe_dimension: LweDimension,
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+        ciphertext_modulus: CiphertextModulus<u64>,
+    ) -> Self {
+        let container_len = polynomial_size.0
+            * input_lwe_dimension.0
+            * decomposition_level_count.0
+            * glwe_size.0
+            * glwe_size.0;
+
+        let boxed = avec![0u64; container_len].into_boxed_slice();
+
+        NttLweBootstrapKey::from_container(
+            boxed,
+            input_lwe_dimension,
+            glwe_size,
+            polynomial_size,
+            decomposition_base_log,
+            decomposition_level_count,
+            ciphertext_modulus,
+        )
+    }
+}
+
+impl<Cont> NttLweBootstrapKey<Cont>
+where
+    Cont: ContainerMut<Element = u64>,
+{
+    /// Fill a bootstrapping key with the NTT of a bootstrapping key in the standard domain.
+    pub fn fill_with_forward_ntt<ContBsk>(
+        &mut self,
+        coef_bsk: &LweBootstrapKey<ContBsk>,
+        ntt: Ntt64View<'_>,
+    ) where
+        ContBsk: Container<Element = u64>,
+    {
+        fn implementation(
+            this: NttLweBootstrapKey<&mut [u64]>,
+            coef_bsk: LweBootstrapKey<&[u64]>,
+            ntt: Ntt64View<'_>,
+        ) {
+            for (mut ntt_ggsw, standard_ggsw) in izip!(this.into_ggsw_iter(), coef_bsk.iter()) {
+                ntt_ggsw.fill_with_forward_ntt(&standard_ggsw, ntt);
+            }
+        }
+        implementation(self.as_mut_view(), coef_bsk.as_view(), ntt);
+    }
+}
+
+/// Switch a coefficient modulo the prime `modulus` to the exponent of the monomial it selects in
+/// the blind rotation, i.e. round it to the closest multiple of $q / 2N$.
+pub fn ntt_pbs_modulus_switch(input: u64, modulus: u64, polynomial_size: PolynomialSize) -> usize {
+    let two_n = 2 * polynomial_size.0 as u128;
+    let modulus = modulus as u128;
+    (((input as u128 * two_n + modulus / 2) / modulus) % two_n) as usize
+}
+
+/// Return the required memory for [`NttLweBootstrapKey::blind_rotate_assign`].
+pub fn blind_rotate_scratch(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_level_count: DecompositionLevelCount,
+) -> Result<StackReq, SizeOverflow> {
+    StackReq::try_new_aligned::<u64>(glwe_size.0 * polynomial_size.0, CACHELINE_ALIGN)?.try_and(
+        cmux_scratch(glwe_size, polynomial_size, decomposition_level_count)?,
+    )
+}
+
+/// Return the required memory for [`NttLweBootstrapKey::bootstrap`].
+pub fn bootstrap_scratch(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_level_count: DecompositionLevelCount,
+) -> Result<StackReq, SizeOverflow> {
+    blind_rotate_scratch(glwe_size, polynomial_size, decomposition_level_count)?.try_and(
+        StackReq::try_new_aligned::<u64>(glwe_size.0 * polynomial_size.0, CACHELINE_ALIGN)?,
+    )
+}
+
+impl<Cont> NttLweBootstrapKey<Cont>
+where
+    Cont: Container<Element = u64>,
+{
+    pub fn blind_rotate_assign<ContLut, ContLwe>(
+        &self,
+        lut: &mut GlweCiphertext<ContLut>,
+        lwe: &LweCiphertext<ContLwe>,
+        ntt: Ntt64View<'_>,
+        stack: PodStack<'_>,
+    ) where
+        ContLut: ContainerMut<Element = u64>,
+        ContLwe: Container<Element = u64>,
+    {
+        fn implementation(
+            this: NttLweBootstrapKey<&[u64]>,
+            mut lut: GlweCiphertext<&mut [u64]>,
+            lwe: LweCiphertext<&[u64]>,
+            ntt: Ntt64View<'_>,
+            mut stack: PodStack<'_>,
+        ) {
+            let lwe = lwe.as_ref();
+            let (lwe_body, lwe_mask) = lwe.split_last().unwrap();
+
+            let lut_poly_size = lut.polynomial_size();
+            let modulus = ntt.modulus();
+            let monomial_degree = ntt_pbs_modulus_switch(*lwe_body, modulus, lut_poly_size);
+
+            lut.as_mut_polynomial_list()
+                .iter_mut()
+                .for_each(|mut poly| {
+                    polynomial_wrapping_monic_monomial_div_assign_custom_mod(
+                        &mut poly,
+                        MonomialDegree(monomial_degree),
+                        modulus,
+                    );
+                });
+
+            // We initialize the ct_0 used for the successive cmuxes
+            let mut ct0 = lut;
+
+            for (lwe_mask_element, bootstrap_key_ggsw) in
+                izip!(lwe_mask.iter(), this.into_ggsw_iter())
+            {
+                if *lwe_mask_element != 0 {
+                    let stack = stack.rb_mut();
+                    // We copy ct_0 to ct_1
+                    let (mut ct1, stack) =
+                        stack.collect_aligned(CACHELINE_ALIGN, ct0.as_ref().iter().copied());
+                    let mut ct1 = GlweCiphertextMutView::from_container(
+                        &mut *ct1,
+                        ct0.polynomial_size(),
+                        ct0.ciphertext_modulus(),
+                    );
+
+                    // We rotate ct_1 by performing ct_1 <- ct_1 * X^{a_hat}
+                    for mut poly in ct1.as_mut_polynomial_list().iter_mut() {
+                        polynomial_wrapping_monic_monomial_mul_assign_custom_mod(
+                            &mut poly,
+                            MonomialDegree(ntt_pbs_modulus_switch(
+                                *lwe_mask_element,
+                                modulus,
+                                lut_poly_size,
+                            )),
+                            modulus,
+                        );
+                    }
+
+                    cmux(&mut ct0, &mut ct1, &bootstrap_key_ggsw, ntt, stack);
+                }
+            }
+        }
+
+        assert_eq!(
+            lut.ciphertext_modulus(),
+            self.ciphertext_modulus,
+            "Mismatched moduli between lut ({:?}) and bootstrap key ({:?})",
+            lut.ciphertext_modulus(),
+            self.ciphertext_modulus,
+        );
+        assert_eq!(
+            lwe.ciphertext_modulus(),
+            self.ciphertext_modulus,
+            "Mismatched moduli between lwe ({:?}) and bootstrap key ({:?})",
+            lwe.ciphertext_modulus(),
+            self.ciphertext_modulus,
+        );
+        assert_eq!(
+            ntt.modulus() as u128,
+            self.ciphertext_modulus.get_custom_modulus(),
+            "The NTT is not over the modulus of the bootstrap key",
+        );
+
+        implementation(self.as_view(), lut.as_mut_view(), lwe.as_view(), ntt, stack);
+    }
+
+    pub fn bootstrap<ContLweOut, ContLweIn, ContAcc>(
+        &self,
+        lwe_out: &mut LweCiphertext<ContLweOut>,
+        lwe_in: &LweCiphertext<ContLweIn>,
+        accumulator: &GlweCiphertext<ContAcc>,
+        ntt: Ntt64View<'_>,
+        stack: PodStack<'_>,
+    ) where
+        ContLweOut: ContainerMut<Element = u64>,
+        ContLweIn: Container<Element = u64>,
+        ContAcc: Container<Element = u64>,
+    {
+        fn implementation(
+            this: NttLweBootstrapKey<&[u64]>,
+            mut lwe_out: LweCiphertext<&mut [u64]>,
+            lwe_in: LweCiphertext<&[u64]>,
+            accumulator: GlweCiphertext<&[u64]>,
+            ntt: Ntt64View<'_>,
+            stack: PodStack<'_>,
+        ) {
+            let (mut local_accumulator_data, stack) =
+                stack.collect_aligned(CACHELINE_ALIGN, accumulator.as_ref().iter().copied());
+            let mut local_accumulator = GlweCiphertextMutView::from_container(
+                &mut *local_accumulator_data,
+                accumulator.polynomial_size(),
+                accumulator.ciphertext_modulus(),
+            );
+            this.blind_rotate_assign(&mut local_accumulator.as_mut_view(), &lwe_in, ntt, stack);
+            extract_lwe_sample_from_glwe_ciphertext(
+                &local_accumulator,
+                &mut lwe_out,
+                MonomialDegree(0),
+            );
+        }
+
+        implementation(
+            self.as_view(),
+            lwe_out.as_mut_view(),
+            lwe_in.as_view(),
+            accumulator.as_view(),
+            ntt,
+            stack,
+        );
+    }
+}
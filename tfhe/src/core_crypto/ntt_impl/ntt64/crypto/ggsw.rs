@@ -0,0 +1,264 @@
+use super::super::math::ntt::Ntt64View;
+use crate::core_crypto::algorithms::slice_algorithms::slice_wrapping_sub_assign_custom_mod;
+use crate::core_crypto::commons::math::decomposition::SignedDecomposerNonNative;
+use crate::core_crypto::commons::parameters::{
+    CiphertextModulus, DecompositionBaseLog, DecompositionLevelCount, GlweSize, PolynomialSize,
+};
+use crate::core_crypto::commons::traits::{Container, ContainerMut};
+use crate::core_crypto::commons::utils::izip;
+use crate::core_crypto::entities::*;
+use aligned_vec::CACHELINE_ALIGN;
+use dyn_stack::{PodStack, SizeOverflow, StackReq};
+
+/// A GGSW ciphertext in the NTT domain.
+///
+/// The polynomials are laid out as in the standard [`GgswCiphertext`], each of them replaced by
+/// its [`Ntt64View::forward`] transform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NttGgswCiphertext<C: Container<Element = u64>> {
+    data: C,
+
+    polynomial_size: PolynomialSize,
+    glwe_size: GlweSize,
+    decomposition_base_log: DecompositionBaseLog,
+    decomposition_level_count: DecompositionLevelCount,
+    ciphertext_modulus: CiphertextModulus<u64>,
+}
+
+impl<C: Container<Element = u64>> NttGgswCiphertext<C> {
+    pub fn from_container(
+        data: C,
+        polynomial_size: PolynomialSize,
+        glwe_size: GlweSize,
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+        ciphertext_modulus: CiphertextModulus<u64>,
+    ) -> Self {
+        assert_eq!(
+            data.container_len(),
+            polynomial_size.0 * glwe_size.0 * glwe_size.0 * decomposition_level_count.0
+        );
+
+        Self {
+            data,
+            polynomial_size,
+            glwe_size,
+            decomposition_base_log,
+            decomposition_level_count,
+            ciphertext_modulus,
+        }
+    }
+
+    pub fn polynomial_size(&self) -> PolynomialSize {
+        self.polynomial_size
+    }
+
+    pub fn glwe_size(&self) -> GlweSize {
+        self.glwe_size
+    }
+
+    pub fn decomposition_base_log(&self) -> DecompositionBaseLog {
+        self.decomposition_base_log
+    }
+
+    pub fn decomposition_level_count(&self) -> DecompositionLevelCount {
+        self.decomposition_level_count
+    }
+
+    pub fn ciphertext_modulus(&self) -> CiphertextModulus<u64> {
+        self.ciphertext_modulus
+    }
+
+    pub fn data(self) -> C {
+        self.data
+    }
+
+    pub fn as_view(&self) -> NttGgswCiphertext<&[C::Element]> {
+        NttGgswCiphertext {
+            data: self.data.as_ref(),
+            polynomial_size: self.polynomial_size,
+            glwe_size: self.glwe_size,
+            decomposition_base_log: self.decomposition_base_log,
+            decomposition_level_count: self.decomposition_level_count,
+            ciphertext_modulus: self.ciphertext_modulus,
+        }
+    }
+
+    pub fn as_mut_view(&mut self) -> NttGgswCiphertext<&mut [C::Element]>
+    where
+        C: AsMut<[C::Element]>,
+    {
+        NttGgswCiphertext {
+            data: self.data.as_mut(),
+            polynomial_size: self.polynomial_size,
+            glwe_size: self.glwe_size,
+            decomposition_base_log: self.decomposition_base_log,
+            decomposition_level_count: self.decomposition_level_count,
+            ciphertext_modulus: self.ciphertext_modulus,
+        }
+    }
+}
+
+impl<Cont> NttGgswCiphertext<Cont>
+where
+    Cont: ContainerMut<Element = u64>,
+{
+    /// Fill a GGSW ciphertext with the NTT of a GGSW ciphertext in the standard domain.
+    pub fn fill_with_forward_ntt<ContGgsw>(
+        &mut self,
+        coef_ggsw: &GgswCiphertext<ContGgsw>,
+        ntt: Ntt64View<'_>,
+    ) where
+        ContGgsw: Container<Element = u64>,
+    {
+        fn implementation(
+            this: NttGgswCiphertext<&mut [u64]>,
+            coef_ggsw: GgswCiphertext<&[u64]>,
+            ntt: Ntt64View<'_>,
+        ) {
+            debug_assert_eq!(coef_ggsw.polynomial_size(), this.polynomial_size());
+            debug_assert_eq!(coef_ggsw.ciphertext_modulus(), this.ciphertext_modulus());
+            let poly_size = coef_ggsw.polynomial_size().0;
+
+            for (ntt_poly, coef_poly) in izip!(
+                this.data().chunks_exact_mut(poly_size),
+                coef_ggsw.as_ref().chunks_exact(poly_size)
+            ) {
+                ntt_poly.copy_from_slice(coef_poly);
+                ntt.forward(ntt_poly);
+            }
+        }
+        implementation(self.as_mut_view(), coef_ggsw.as_view(), ntt);
+    }
+}
+
+/// Return the required memory for [`add_external_product_assign`].
+pub fn add_external_product_assign_scratch(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_level_count: DecompositionLevelCount,
+) -> Result<StackReq, SizeOverflow> {
+    let align = CACHELINE_ALIGN;
+    let glwe_len = glwe_size.0 * polynomial_size.0;
+    let decomposition_scratch =
+        StackReq::try_new_aligned::<u64>(glwe_len * decomposition_level_count.0, align)?;
+    let ntt_scratch = StackReq::try_new_aligned::<u64>(glwe_len, align)?;
+    decomposition_scratch.try_and(ntt_scratch)
+}
+
+/// Add the external product of `ggsw` and `glwe` to `out`.
+///
+/// The decomposition of `glwe` is exact modulo the prime of `ntt` and so are the products in the
+/// NTT domain, the only error added to the one of `ggsw` is the rounding of the decomposition.
+pub fn add_external_product_assign<ContOut, ContGgsw, ContGlwe>(
+    out: &mut GlweCiphertext<ContOut>,
+    ggsw: &NttGgswCiphertext<ContGgsw>,
+    glwe: &GlweCiphertext<ContGlwe>,
+    ntt: Ntt64View<'_>,
+    stack: PodStack<'_>,
+) where
+    ContOut: ContainerMut<Element = u64>,
+    ContGgsw: Container<Element = u64>,
+    ContGlwe: Container<Element = u64>,
+{
+    fn implementation(
+        mut out: GlweCiphertext<&mut [u64]>,
+        ggsw: NttGgswCiphertext<&[u64]>,
+        glwe: GlweCiphertext<&[u64]>,
+        ntt: Ntt64View<'_>,
+        stack: PodStack<'_>,
+    ) {
+        // we check that the polynomial sizes match
+        debug_assert_eq!(ggsw.polynomial_size(), glwe.polynomial_size());
+        debug_assert_eq!(ggsw.polynomial_size(), out.polynomial_size());
+        // we check that the glwe sizes match
+        debug_assert_eq!(ggsw.glwe_size(), glwe.glwe_size());
+        debug_assert_eq!(ggsw.glwe_size(), out.glwe_size());
+
+        debug_assert_eq!(glwe.ciphertext_modulus(), out.ciphertext_modulus());
+        debug_assert_eq!(glwe.ciphertext_modulus(), ggsw.ciphertext_modulus());
+
+        let align = CACHELINE_ALIGN;
+        let poly_size = ggsw.polynomial_size().0;
+        let glwe_len = ggsw.glwe_size().0 * poly_size;
+
+        let decomposer = SignedDecomposerNonNative::new(
+            ggsw.decomposition_base_log(),
+            ggsw.decomposition_level_count(),
+            ggsw.ciphertext_modulus(),
+        );
+
+        let (mut decomposition, stack) =
+            stack.make_aligned_raw::<u64>(glwe_len * ggsw.decomposition_level_count().0, align);
+        let (mut output_ntt_buffer, _) = stack.make_aligned_raw::<u64>(glwe_len, align);
+        output_ntt_buffer.fill(0);
+
+        // The terms of level j of every coefficient of the glwe are gathered in the chunk j - 1 of
+        // the decomposition, i.e. the chunks are in the order of the level matrices of the ggsw
+        for (index, &value) in glwe.as_ref().iter().enumerate() {
+            for term in decomposer.decompose(value) {
+                decomposition[(term.level().0 - 1) * glwe_len + index] = term.value();
+            }
+        }
+
+        // For each level we add the product of the decomposed glwe with the level matrix, one
+        // row of the matrix per polynomial of the decomposition
+        for (ggsw_level_matrix, glwe_decomp_term) in izip!(
+            ggsw.data().chunks_exact(ggsw.glwe_size().0 * glwe_len),
+            decomposition.chunks_exact_mut(glwe_len),
+        ) {
+            for (ggsw_row, glwe_poly) in izip!(
+                ggsw_level_matrix.chunks_exact(glwe_len),
+                glwe_decomp_term.chunks_exact_mut(poly_size),
+            ) {
+                ntt.forward(glwe_poly);
+                for (output_poly, ggsw_poly) in izip!(
+                    output_ntt_buffer.chunks_exact_mut(poly_size),
+                    ggsw_row.chunks_exact(poly_size),
+                ) {
+                    ntt.mul_accumulate(output_poly, glwe_poly, ggsw_poly);
+                }
+            }
+        }
+
+        for (out_poly, output_poly) in izip!(
+            out.as_mut().chunks_exact_mut(poly_size),
+            output_ntt_buffer.chunks_exact_mut(poly_size),
+        ) {
+            ntt.add_backward(out_poly, output_poly);
+        }
+    }
+
+    implementation(
+        out.as_mut_view(),
+        ggsw.as_view(),
+        glwe.as_view(),
+        ntt,
+        stack,
+    );
+}
+
+/// Return the required memory for [`cmux`].
+pub fn cmux_scratch(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_level_count: DecompositionLevelCount,
+) -> Result<StackReq, SizeOverflow> {
+    add_external_product_assign_scratch(glwe_size, polynomial_size, decomposition_level_count)
+}
+
+/// This cmux mutates both ct1 and ct0. The result is in ct0 after the method was called.
+pub fn cmux<ContCt0, ContCt1, ContGgsw>(
+    ct0: &mut GlweCiphertext<ContCt0>,
+    ct1: &mut GlweCiphertext<ContCt1>,
+    ggsw: &NttGgswCiphertext<ContGgsw>,
+    ntt: Ntt64View<'_>,
+    stack: PodStack<'_>,
+) where
+    ContCt0: ContainerMut<Element = u64>,
+    ContCt1: ContainerMut<Element = u64>,
+    ContGgsw: Container<Element = u64>,
+{
+    slice_wrapping_sub_assign_custom_mod(ct1.as_mut(), ct0.as_ref(), ntt.modulus());
+    add_external_product_assign(ct0, ggsw, ct1, ntt, stack);
+}
@@ -0,0 +1,286 @@
+use crate::core_crypto::commons::ciphertext_modulus::CiphertextModulus;
+use crate::core_crypto::commons::parameters::PolynomialSize;
+use crate::core_crypto::commons::utils::izip;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Twiddle factors of a negacyclic NTT of a given size over a given prime.
+#[derive(Debug)]
+pub(crate) struct Plan {
+    modulus: u64,
+    // psi^bit_reverse(i) for a primitive 2N-th root of unity psi
+    psi_rev: Box<[u64]>,
+    // psi^-bit_reverse(i)
+    psi_inv_rev: Box<[u64]>,
+    // N^-1
+    n_inv: u64,
+}
+
+/// Negacyclic Number Theoretic Transform over a prime modulus. See [`Ntt64View`] for transform
+/// functions.
+///
+/// A polynomial modulo $X^{N}+1$ is mapped to its evaluations at the primitive $2N$-th roots of
+/// unity of $\mathbb{Z}\_p$, so the products computed in the NTT domain are exact, unlike in the
+/// Fourier domain. This requires $p$ to be a prime such that $2N$ divides $p-1$, e.g. $2^{64} -
+/// 2^{32} + 1$ for every power of two $N$ up to $2^{31}$.
+#[derive(Clone, Debug)]
+pub struct Ntt64 {
+    plan: Arc<Plan>,
+}
+
+/// View type for [`Ntt64`].
+#[derive(Clone, Copy, Debug)]
+pub struct Ntt64View<'a> {
+    pub(crate) plan: &'a Plan,
+}
+
+impl Ntt64 {
+    #[inline]
+    pub fn as_view(&self) -> Ntt64View<'_> {
+        Ntt64View { plan: &self.plan }
+    }
+}
+
+type PlanMap = RwLock<HashMap<(usize, u64), Arc<OnceLock<Arc<Plan>>>>>;
+pub(crate) static PLANS: OnceLock<PlanMap> = OnceLock::new();
+fn plans() -> &'static PlanMap {
+    PLANS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+#[inline(always)]
+fn mul_mod(lhs: u64, rhs: u64, modulus: u64) -> u64 {
+    ((lhs as u128 * rhs as u128) % modulus as u128) as u64
+}
+
+#[inline(always)]
+fn add_mod(lhs: u64, rhs: u64, modulus: u64) -> u64 {
+    let (sum, overflow) = lhs.overflowing_add(rhs);
+    if overflow || sum >= modulus {
+        sum.wrapping_sub(modulus)
+    } else {
+        sum
+    }
+}
+
+#[inline(always)]
+fn sub_mod(lhs: u64, rhs: u64, modulus: u64) -> u64 {
+    if lhs >= rhs {
+        lhs - rhs
+    } else {
+        lhs.wrapping_sub(rhs).wrapping_add(modulus)
+    }
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1 % modulus;
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Deterministic Miller-Rabin test, the bases are enough for every 64 bits integer.
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for base in BASES {
+        if n % base == 0 {
+            return n == base;
+        }
+    }
+
+    let trailing_zeros = (n - 1).trailing_zeros();
+    let odd_part = (n - 1) >> trailing_zeros;
+    'witness: for base in BASES {
+        let mut x = pow_mod(base, odd_part, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..trailing_zeros {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+fn bit_reverse(value: usize, log_n: u32) -> usize {
+    if log_n == 0 {
+        0
+    } else {
+        value.reverse_bits() >> (usize::BITS - log_n)
+    }
+}
+
+impl Plan {
+    fn new(n: usize, modulus: u64) -> Self {
+        let two_n = 2 * n as u64;
+        let exponent = (modulus - 1) / two_n;
+
+        // psi^N = -1 makes psi a primitive 2N-th root of unity as N is a power of two, half of the
+        // elements of Z_p give one
+        let psi = (2..modulus)
+            .map(|generator| pow_mod(generator, exponent, modulus))
+            .find(|&candidate| pow_mod(candidate, n as u64, modulus) == modulus - 1)
+            .unwrap();
+        let psi_inv = pow_mod(psi, modulus - 2, modulus);
+
+        let log_n = n.ilog2();
+        let powers = |root: u64| -> Box<[u64]> {
+            let mut natural = vec![1u64; n];
+            for i in 1..n {
+                natural[i] = mul_mod(natural[i - 1], root, modulus);
+            }
+            (0..n).map(|i| natural[bit_reverse(i, log_n)]).collect()
+        };
+
+        Self {
+            modulus,
+            psi_rev: powers(psi),
+            psi_inv_rev: powers(psi_inv),
+            n_inv: pow_mod(n as u64, modulus - 2, modulus),
+        }
+    }
+}
+
+impl Ntt64 {
+    /// Return whether [`Ntt64::new`] supports the given polynomial size and modulus.
+    pub fn is_supported(size: PolynomialSize, modulus: CiphertextModulus<u64>) -> bool {
+        let n = size.0;
+        if !n.is_power_of_two() || modulus.is_native_modulus() {
+            return false;
+        }
+        let modulus = modulus.get_custom_modulus() as u64;
+        is_prime(modulus) && (modulus - 1) % (2 * n as u64) == 0
+    }
+
+    /// Negacyclic NTT of polynomials of size `size` modulo `modulus`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size and modulus are not [supported](`Ntt64::is_supported`).
+    pub fn new(size: PolynomialSize, modulus: CiphertextModulus<u64>) -> Self {
+        assert!(
+            Self::is_supported(size, modulus),
+            "The NTT requires a power of two polynomial size and a prime modulus p with 2N | p - 1, \
+            got {size:?} and {modulus:?}"
+        );
+
+        let global_plans = plans();
+
+        let key = (size.0, modulus.get_custom_modulus() as u64);
+        let get_plan = || {
+            let plans = global_plans.read().unwrap();
+            let plan = plans.get(&key).cloned();
+            drop(plans);
+
+            plan.map(|p| p.get_or_init(|| Arc::new(Plan::new(key.0, key.1))).clone())
+        };
+
+        // could not find a plan of the given size, we lock the map again and try to insert it
+        let mut plans = global_plans.write().unwrap();
+        if let Entry::Vacant(v) = plans.entry(key) {
+            v.insert(Arc::new(OnceLock::new()));
+        }
+
+        drop(plans);
+
+        Self {
+            plan: get_plan().unwrap(),
+        }
+    }
+}
+
+impl<'a> Ntt64View<'a> {
+    pub fn polynomial_size(self) -> PolynomialSize {
+        PolynomialSize(self.plan.psi_rev.len())
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.plan.modulus
+    }
+
+    /// Perform an in place forward negacyclic NTT, the input coefficients have to be reduced
+    /// modulo [`Self::modulus`]. The output is in bit reversed order.
+    pub fn forward(self, data: &mut [u64]) {
+        let n = data.len();
+        assert_eq!(n, self.polynomial_size().0);
+        let modulus = self.plan.modulus;
+
+        let mut t = n;
+        let mut m = 1;
+        while m < n {
+            t /= 2;
+            for i in 0..m {
+                let twiddle = self.plan.psi_rev[m + i];
+                let (lo, hi) = data[2 * i * t..2 * (i + 1) * t].split_at_mut(t);
+                for (u, v) in izip!(lo.iter_mut(), hi.iter_mut()) {
+                    let product = mul_mod(*v, twiddle, modulus);
+                    *v = sub_mod(*u, product, modulus);
+                    *u = add_mod(*u, product, modulus);
+                }
+            }
+            m *= 2;
+        }
+    }
+
+    /// Perform an in place backward negacyclic NTT, the input is in the order of
+    /// [`Self::forward`]. The output is normalized.
+    pub fn backward(self, data: &mut [u64]) {
+        let n = data.len();
+        assert_eq!(n, self.polynomial_size().0);
+        let modulus = self.plan.modulus;
+
+        let mut t = 1;
+        let mut m = n;
+        while m > 1 {
+            let h = m / 2;
+            for i in 0..h {
+                let twiddle = self.plan.psi_inv_rev[h + i];
+                let (lo, hi) = data[2 * i * t..2 * (i + 1) * t].split_at_mut(t);
+                for (u, v) in izip!(lo.iter_mut(), hi.iter_mut()) {
+                    let (a, b) = (*u, *v);
+                    *u = add_mod(a, b, modulus);
+                    *v = mul_mod(sub_mod(a, b, modulus), twiddle, modulus);
+                }
+            }
+            t *= 2;
+            m = h;
+        }
+
+        for value in data.iter_mut() {
+            *value = mul_mod(*value, self.plan.n_inv, modulus);
+        }
+    }
+
+    /// Perform an in place backward NTT of `data` and add the result to `output`.
+    pub fn add_backward(self, output: &mut [u64], data: &mut [u64]) {
+        self.backward(data);
+        let modulus = self.plan.modulus;
+        for (out, value) in izip!(output.iter_mut(), data.iter()) {
+            *out = add_mod(*out, *value, modulus);
+        }
+    }
+
+    /// Add the pointwise product of `lhs` and `rhs`, in the NTT domain, to `output`.
+    pub fn mul_accumulate(self, output: &mut [u64], lhs: &[u64], rhs: &[u64]) {
+        let modulus = self.plan.modulus;
+        for (out, lhs, rhs) in izip!(output.iter_mut(), lhs.iter(), rhs.iter()) {
+            *out = add_mod(*out, mul_mod(*lhs, *rhs, modulus), modulus);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
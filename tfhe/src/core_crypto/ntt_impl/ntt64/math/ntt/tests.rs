@@ -0,0 +1,95 @@
+use super::*;
+use crate::core_crypto::commons::test_tools::new_random_generator;
+
+const SOLINAS_PRIME: u64 = 0xFFFF_FFFF_0000_0001;
+
+fn random_polynomial(size: usize) -> Vec<u64> {
+    let mut generator = new_random_generator();
+    (0..size)
+        .map(|_| generator.random_uniform::<u64>() % SOLINAS_PRIME)
+        .collect()
+}
+
+fn modulus() -> CiphertextModulus<u64> {
+    CiphertextModulus::try_new(SOLINAS_PRIME as u128).unwrap()
+}
+
+#[test]
+fn test_supported_moduli() {
+    assert!(Ntt64::is_supported(PolynomialSize(2048), modulus()));
+    // 2N does not divide p - 1
+    assert!(!Ntt64::is_supported(PolynomialSize(1 << 32), modulus()));
+    // 2^64 - 59 is prime, but 8 does not divide p - 1
+    assert!(Ntt64::is_supported(
+        PolynomialSize(2),
+        CiphertextModulus::try_new((1 << 64) - 59).unwrap()
+    ));
+    assert!(!Ntt64::is_supported(
+        PolynomialSize(4),
+        CiphertextModulus::try_new((1 << 64) - 59).unwrap()
+    ));
+    // 2^64 - 2^33 + 1 is divisible by 3
+    assert!(!Ntt64::is_supported(
+        PolynomialSize(2),
+        CiphertextModulus::try_new((1 << 64) - (1 << 33) + 1).unwrap()
+    ));
+    assert!(!Ntt64::is_supported(
+        PolynomialSize(2048),
+        CiphertextModulus::new_native()
+    ));
+}
+
+#[test]
+fn test_roundtrip() {
+    for size_log in 1..=14 {
+        let size = 1_usize << size_log;
+        let ntt = Ntt64::new(PolynomialSize(size), modulus());
+        let ntt = ntt.as_view();
+
+        let poly = random_polynomial(size);
+        let mut roundtrip = poly.clone();
+        ntt.forward(&mut roundtrip);
+        ntt.backward(&mut roundtrip);
+
+        assert_eq!(poly, roundtrip);
+    }
+}
+
+#[test]
+fn test_product() {
+    fn convolution_naive(lhs: &[u64], rhs: &[u64]) -> Vec<u64> {
+        let n = lhs.len();
+        let mut out = vec![0u64; n];
+        for i in 0..n {
+            for j in 0..n {
+                let product = mul_mod(lhs[i], rhs[j], SOLINAS_PRIME);
+                let k = (i + j) % n;
+                out[k] = if i + j < n {
+                    add_mod(out[k], product, SOLINAS_PRIME)
+                } else {
+                    sub_mod(out[k], product, SOLINAS_PRIME)
+                };
+            }
+        }
+        out
+    }
+
+    for size_log in 1..=10 {
+        let size = 1_usize << size_log;
+        let ntt = Ntt64::new(PolynomialSize(size), modulus());
+        let ntt = ntt.as_view();
+
+        let lhs = random_polynomial(size);
+        let rhs = random_polynomial(size);
+        let (mut lhs_ntt, mut rhs_ntt) = (lhs.clone(), rhs.clone());
+        ntt.forward(&mut lhs_ntt);
+        ntt.forward(&mut rhs_ntt);
+
+        let mut product_ntt = vec![0u64; size];
+        ntt.mul_accumulate(&mut product_ntt, &lhs_ntt, &rhs_ntt);
+        let mut product = vec![0u64; size];
+        ntt.add_backward(&mut product, &mut product_ntt);
+
+        assert_eq!(product, convolution_naive(&lhs, &rhs));
+    }
+}
@@ -0,0 +1,335 @@
+//! Biometric authentication with the high level API keys.
+//!
+//! The client encrypts the LUTs of a template and the probes with its [`ClientKey`], the server
+//! evaluates them with the key given to [`set_server_key`](crate::set_server_key) and returns an
+//! encrypted [`FheBool`] decision.
+//!
+//! The probe values and the LUT outputs are blocks of the integer parameters of the key, so the
+//! block length of the data set has to match the message modulus of the config, e.g. data sets
+//! with 3 bit blocks need a config built with `PARAM_MESSAGE_3_CARRY_3_KS_PBS`.
+//!
+//! ```rust
+//! use tfhe::biometrics::{authenticate, EncryptedProbe, EncryptedTemplate};
+//! use tfhe::prelude::*;
+//! use tfhe::{generate_keys, set_server_key, ConfigBuilder};
+//!
+//! let (client_key, server_key) = generate_keys(ConfigBuilder::default());
+//!
+//! // One single block feature, its LUT row for the template value is indexed by the probe value
+//! let row = [3u64, 2, 1, 0];
+//! let template = EncryptedTemplate::encrypt(&[vec![&row[..]]], 2, 2, &client_key);
+//! let probe = EncryptedProbe::encrypt(&[1], &template, &client_key);
+//!
+//! set_server_key(server_key);
+//! let decision = authenticate(&template, probe);
+//! assert!(decision.decrypt(&client_key));
+//! ```
+
+use crate::core_crypto::biometrics::common;
+use crate::core_crypto::biometrics::common::SeededLookupTables;
+use crate::core_crypto::biometrics::cpu::all_in_one_classic;
+use crate::high_level_api::global_state::with_cpu_internal_keys;
+use crate::named::Named;
+use crate::shortint::engine::ShortintEngine;
+use crate::shortint::parameters::ShortintParameterSet;
+use crate::shortint::server_key::LookupTableOwned;
+use crate::shortint::{Ciphertext, MessageModulus};
+use crate::{ClientKey, FheBool, PublicKey};
+use serde::{Deserialize, Serialize};
+
+/// Encrypted LUTs of an enrolled template.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EncryptedTemplate {
+    luts: Vec<Vec<LookupTableOwned>>,
+    threshold: usize,
+    num_sum_blocks: usize,
+}
+
+impl Named for EncryptedTemplate {
+    const NAME: &'static str = "high_level_api::biometrics::EncryptedTemplate";
+}
+
+impl EncryptedTemplate {
+    /// Encrypts the LUT rows of a template, one row per block of every feature, as returned by
+    /// `bio_aux::HelrTableSet::lut_rows`.
+    ///
+    /// The authentication succeeds if the sum of the LUT outputs, as a radix number of
+    /// `num_sum_blocks` blocks, is greater or equal to `threshold`.
+    pub fn encrypt(
+        rows: &[Vec<&[u64]>],
+        threshold: usize,
+        num_sum_blocks: usize,
+        client_key: &ClientKey,
+    ) -> Self {
+        assert!(
+            rows.iter().all(|blocks| blocks.len() <= num_sum_blocks),
+            "a feature has more blocks than the sum"
+        );
+        let shortint_key = &client_key.key.key.key;
+        let luts = ShortintEngine::with_thread_local_mut(|engine| {
+            common::generate_lookup_tables_individual_from_rows(
+                rows,
+                shortint_key,
+                shortint_key.parameters,
+                engine,
+            )
+        });
+        Self {
+            luts,
+            threshold,
+            num_sum_blocks,
+        }
+    }
+
+    pub fn num_features(&self) -> usize {
+        self.luts.len()
+    }
+
+    /// Number of blocks every feature is evaluated on.
+    pub fn blocks_per_feature(&self) -> Vec<usize> {
+        self.luts.iter().map(Vec::len).collect()
+    }
+}
+
+/// [`EncryptedTemplate`] with seeded LUT masks, about half the size of the template with the
+/// default parameters, to be stored until the server needs it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompressedEncryptedTemplate {
+    luts: SeededLookupTables,
+    threshold: usize,
+    num_sum_blocks: usize,
+}
+
+impl Named for CompressedEncryptedTemplate {
+    const NAME: &'static str = "high_level_api::biometrics::CompressedEncryptedTemplate";
+}
+
+impl CompressedEncryptedTemplate {
+    /// Same as [`EncryptedTemplate::encrypt`].
+    pub fn encrypt(
+        rows: &[Vec<&[u64]>],
+        threshold: usize,
+        num_sum_blocks: usize,
+        client_key: &ClientKey,
+    ) -> Self {
+        assert!(
+            rows.iter().all(|blocks| blocks.len() <= num_sum_blocks),
+            "a feature has more blocks than the sum"
+        );
+        let shortint_key = &client_key.key.key.key;
+        let luts = ShortintEngine::with_thread_local_mut(|engine| {
+            common::generate_seeded_lookup_tables_individual_from_rows(
+                rows,
+                shortint_key,
+                shortint_key.parameters,
+                engine,
+            )
+        });
+        Self {
+            luts,
+            threshold,
+            num_sum_blocks,
+        }
+    }
+
+    pub fn decompress(&self) -> EncryptedTemplate {
+        EncryptedTemplate {
+            luts: self.luts.clone().decompress(),
+            threshold: self.threshold,
+            num_sum_blocks: self.num_sum_blocks,
+        }
+    }
+}
+
+/// Encrypted probe, every feature value is encrypted once per block of the template feature.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EncryptedProbe {
+    blocks: Vec<Vec<Ciphertext>>,
+}
+
+impl Named for EncryptedProbe {
+    const NAME: &'static str = "high_level_api::biometrics::EncryptedProbe";
+}
+
+impl EncryptedProbe {
+    /// Encrypts `probe` to be compared against `template`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of features differs from the template or if a value does not fit
+    /// the message and carry space of the key.
+    pub fn encrypt(probe: &[u8], template: &EncryptedTemplate, client_key: &ClientKey) -> Self {
+        let shortint_key = &client_key.key.key.key;
+        Self::encrypt_with(
+            probe,
+            template,
+            shortint_key.parameters,
+            |value, modulus| shortint_key.encrypt_with_message_modulus(value, modulus),
+        )
+    }
+
+    /// Encrypts `probe` with a [`PublicKey`], for capture devices that do not hold the
+    /// [`ClientKey`].
+    ///
+    /// # Panics
+    ///
+    /// Same as [`EncryptedProbe::encrypt`].
+    pub fn encrypt_with_public_key(
+        probe: &[u8],
+        template: &EncryptedTemplate,
+        public_key: &PublicKey,
+    ) -> Self {
+        let shortint_key = &public_key.key.key;
+        Self::encrypt_with(
+            probe,
+            template,
+            shortint_key.parameters,
+            |value, modulus| shortint_key.encrypt_with_message_modulus(value, modulus),
+        )
+    }
+
+    fn encrypt_with(
+        probe: &[u8],
+        template: &EncryptedTemplate,
+        params: ShortintParameterSet,
+        encrypt: impl Fn(u64, MessageModulus) -> Ciphertext,
+    ) -> Self {
+        assert_eq!(
+            probe.len(),
+            template.num_features(),
+            "probe and template have a different number of features"
+        );
+        let modulus = MessageModulus(params.message_modulus().0 * params.carry_modulus().0);
+
+        let blocks = probe
+            .iter()
+            .zip(template.luts.iter())
+            .map(|(&value, luts)| {
+                assert!(
+                    (value as usize) < modulus.0,
+                    "probe value {value} does not fit a block"
+                );
+                luts.iter()
+                    .map(|_| encrypt(value as u64, modulus))
+                    .collect()
+            })
+            .collect();
+        Self { blocks }
+    }
+}
+
+/// Compares `probe` against `template` with the server key of the current thread.
+///
+/// # Panics
+///
+/// Panics if no server key is set, if the server key is not a CPU key or if the probe does not
+/// have the shape of the template.
+pub fn authenticate(template: &EncryptedTemplate, probe: EncryptedProbe) -> FheBool {
+    let expected = template.blocks_per_feature();
+    let got: Vec<usize> = probe.blocks.iter().map(Vec::len).collect();
+    assert_eq!(
+        expected, got,
+        "probe does not have the shape of the template"
+    );
+
+    with_cpu_internal_keys(|keys| {
+        let decision = all_in_one_classic::authenticate_with_key(
+            keys.pbs_key(),
+            probe.blocks,
+            &template.luts,
+            template.threshold,
+            template.num_sum_blocks,
+        );
+        FheBool::new(decision)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::safe_deserialization::{safe_deserialize, safe_serialize};
+    use crate::{generate_keys, set_server_key, ConfigBuilder};
+
+    // Two features with a single output block each, the score is the sum of both table entries
+    const ROWS: [[u64; 4]; 2] = [[1, 2, 3, 2], [0, 1, 3, 1]];
+    const THRESHOLD: usize = 5;
+    const NUM_SUM_BLOCKS: usize = 2;
+
+    #[test]
+    fn test_biometrics_authenticate() {
+        let (client_key, server_key) = generate_keys(ConfigBuilder::default());
+        set_server_key(server_key);
+
+        let rows: Vec<Vec<&[u64]>> = ROWS.iter().map(|row| vec![&row[..]]).collect();
+        let template = EncryptedTemplate::encrypt(&rows, THRESHOLD, NUM_SUM_BLOCKS, &client_key);
+
+        // 3 + 3 >= 5
+        let probe = EncryptedProbe::encrypt(&[2, 2], &template, &client_key);
+        assert!(authenticate(&template, probe).decrypt(&client_key));
+
+        // 2 + 1 < 5
+        let probe = EncryptedProbe::encrypt(&[1, 3], &template, &client_key);
+        assert!(!authenticate(&template, probe).decrypt(&client_key));
+    }
+
+    #[test]
+    fn test_biometrics_public_key_probe() {
+        let config = ConfigBuilder::default_with_small_encryption().build();
+        let (client_key, server_key) = generate_keys(config);
+        set_server_key(server_key);
+        let public_key = PublicKey::new(&client_key);
+
+        let rows: Vec<Vec<&[u64]>> = ROWS.iter().map(|row| vec![&row[..]]).collect();
+        let template = EncryptedTemplate::encrypt(&rows, THRESHOLD, NUM_SUM_BLOCKS, &client_key);
+
+        let probe = EncryptedProbe::encrypt_with_public_key(&[2, 2], &template, &public_key);
+        assert!(authenticate(&template, probe).decrypt(&client_key));
+
+        let probe = EncryptedProbe::encrypt_with_public_key(&[1, 3], &template, &public_key);
+        assert!(!authenticate(&template, probe).decrypt(&client_key));
+    }
+
+    #[test]
+    fn test_biometrics_template_serialization() {
+        let (client_key, server_key) = generate_keys(ConfigBuilder::default());
+        set_server_key(server_key);
+
+        let rows: Vec<Vec<&[u64]>> = ROWS.iter().map(|row| vec![&row[..]]).collect();
+        let template = EncryptedTemplate::encrypt(&rows, THRESHOLD, NUM_SUM_BLOCKS, &client_key);
+
+        let mut buffer = vec![];
+        safe_serialize(&template, &mut buffer, 1 << 30).unwrap();
+        let template: EncryptedTemplate = safe_deserialize(buffer.as_slice(), 1 << 30).unwrap();
+
+        let probe = EncryptedProbe::encrypt(&[2, 2], &template, &client_key);
+        assert!(authenticate(&template, probe).decrypt(&client_key));
+    }
+
+    #[test]
+    fn test_biometrics_compressed_template() {
+        let (client_key, server_key) = generate_keys(ConfigBuilder::default());
+        set_server_key(server_key);
+
+        let rows: Vec<Vec<&[u64]>> = ROWS.iter().map(|row| vec![&row[..]]).collect();
+        let template = EncryptedTemplate::encrypt(&rows, THRESHOLD, NUM_SUM_BLOCKS, &client_key);
+        let compressed =
+            CompressedEncryptedTemplate::encrypt(&rows, THRESHOLD, NUM_SUM_BLOCKS, &client_key);
+
+        let size = bincode::serialized_size(&template).unwrap();
+        let compressed_size = bincode::serialized_size(&compressed).unwrap();
+        assert!(compressed_size < size);
+
+        let mut buffer = vec![];
+        safe_serialize(&compressed, &mut buffer, 1 << 30).unwrap();
+        let compressed: CompressedEncryptedTemplate =
+            safe_deserialize(buffer.as_slice(), 1 << 30).unwrap();
+        let template = compressed.decompress();
+
+        let probe = EncryptedProbe::encrypt(&[2, 2], &template, &client_key);
+        assert!(authenticate(&template, probe).decrypt(&client_key));
+
+        let probe = EncryptedProbe::encrypt(&[1, 3], &template, &client_key);
+        assert!(!authenticate(&template, probe).decrypt(&client_key));
+    }
+}
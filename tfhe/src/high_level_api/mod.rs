@@ -80,6 +80,8 @@ mod booleans;
 mod errors;
 mod integers;
 
+/// Biometric authentication with the high level API keys.
+pub mod biometrics;
 pub(in crate::high_level_api) mod details;
 /// The tfhe prelude.
 pub mod prelude;
@@ -0,0 +1,350 @@
+//! Client side of the biometrics pipeline, so the features and the client key never leave the
+//! user's device.
+//!
+//! The decision returned by the server is a regular `FheBool`, it is decrypted with
+//! `FheBool.decrypt`. [`biometric_authenticate`] runs the server side in wasm, which is slow and
+//! meant for tests and demos.
+use crate::high_level_api as hlapi;
+use crate::high_level_api::biometrics;
+use crate::js_on_wasm_api::js_high_level_api::config::TfheConfig;
+use crate::js_on_wasm_api::js_high_level_api::integers::FheBool;
+use crate::js_on_wasm_api::js_high_level_api::keys::{
+    TfheClientKey, TfheCompressedServerKey, TfhePublicKey,
+};
+use crate::js_on_wasm_api::js_high_level_api::{catch_panic, catch_panic_result, into_js_error};
+use crate::shortint::parameters::{PARAM_MESSAGE_2_CARRY_2_PBS_KS, PARAM_MESSAGE_3_CARRY_3_PBS_KS};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct BiometricConfig(pub(crate) config::Config);
+
+#[wasm_bindgen]
+impl BiometricConfig {
+    /// Returns the data set configuration named `name`, one of `BMDB1`, `BMDB2`, `PUT` or `FRGC`.
+    #[wasm_bindgen]
+    pub fn preset(name: &str) -> Result<BiometricConfig, JsError> {
+        match name {
+            "BMDB1" => Ok(Self(config::BMDB1)),
+            "BMDB2" => Ok(Self(config::BMDB2)),
+            "PUT" => Ok(Self(config::PUT)),
+            "FRGC" => Ok(Self(config::FRGC)),
+            _ => Err(JsError::new(&format!("unknown biometric config: {name}"))),
+        }
+    }
+
+    /// Returns a configuration for a data set that has no preset.
+    #[wasm_bindgen]
+    pub fn new(
+        num_tables: usize,
+        num_blocks: usize,
+        num_blocks_sum: usize,
+        block_length: usize,
+        threshold: i64,
+    ) -> BiometricConfig {
+        Self(config::Config {
+            data_set_name: "custom",
+            num_blocks,
+            num_blocks_sum,
+            block_length,
+            num_tables,
+            threshold,
+            probe_encoding: config::ProbeEncoding::Block,
+        })
+    }
+
+    /// Config for the keys, its blocks have the block length of the data set and its
+    /// parameters allow a `TfhePublicKey`.
+    #[wasm_bindgen]
+    pub fn tfhe_config(&self) -> Result<TfheConfig, JsError> {
+        let params = match self.0.block_length {
+            2 => PARAM_MESSAGE_2_CARRY_2_PBS_KS,
+            3 => PARAM_MESSAGE_3_CARRY_3_PBS_KS,
+            block_length => {
+                return Err(JsError::new(&format!(
+                    "unsupported block length: {block_length}"
+                )))
+            }
+        };
+        Ok(TfheConfig(
+            hlapi::ConfigBuilder::default()
+                .use_custom_parameters(params, None)
+                .build(),
+        ))
+    }
+}
+
+#[wasm_bindgen]
+pub struct BiometricQuantizer(pub(crate) Vec<f64>);
+
+#[wasm_bindgen]
+impl BiometricQuantizer {
+    /// `qbins` are the upper bounds of the bins, in increasing order.
+    #[wasm_bindgen]
+    pub fn new(qbins: Vec<f64>) -> BiometricQuantizer {
+        Self(qbins)
+    }
+
+    /// Parses the comma separated bins of a `qbins.csv` file.
+    #[wasm_bindgen]
+    pub fn from_csv(csv: String) -> Result<BiometricQuantizer, JsError> {
+        let qbins = bio_aux::io::csv_string_to_real_vec(csv);
+        if qbins.is_empty() {
+            return Err(JsError::new("no bins in the quantizer file"));
+        }
+        Ok(Self(qbins))
+    }
+
+    #[wasm_bindgen]
+    pub fn quantize(&self, features: Vec<f64>) -> Result<Vec<u8>, JsError> {
+        bio_aux::io::quantize_feature_vector(features, &self.0)
+            .into_iter()
+            .map(|bin| u8::try_from(bin).map_err(into_js_error))
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+pub struct BiometricTables {
+    tables: bio_aux::HelrTableSet,
+    num_tables: usize,
+    num_blocks_sum: usize,
+}
+
+#[wasm_bindgen]
+impl BiometricTables {
+    /// Builds the HELR tables from `num_tables * rows * columns` row major values.
+    #[wasm_bindgen]
+    pub fn from_values(
+        config: &BiometricConfig,
+        values: Vec<i32>,
+        rows: usize,
+        columns: usize,
+    ) -> Result<BiometricTables, JsError> {
+        if values.len() != config.0.num_tables * rows * columns {
+            return Err(JsError::new(&format!(
+                "expected {} values, got {}",
+                config.0.num_tables * rows * columns,
+                values.len()
+            )));
+        }
+        let tables = values
+            .chunks_exact(rows * columns)
+            .map(|table| table.chunks_exact(columns).map(<[i32]>::to_vec).collect())
+            .collect();
+        catch_panic(|| Self::new(tables, config))
+    }
+
+    /// Parses the HELR table files of a data set concatenated in table order, i.e.
+    /// `table_0.csv` to `table_{num_tables - 1}.csv`.
+    #[wasm_bindgen]
+    pub fn from_csv(config: &BiometricConfig, csv: String) -> Result<BiometricTables, JsError> {
+        catch_panic_result(|| {
+            let rows = bio_aux::io::csv_to_helr_table(csv)
+                .ok_or_else(|| JsError::new("invalid HELR table file"))?;
+            if rows.is_empty() || rows.len() % config.0.num_tables != 0 {
+                return Err(JsError::new(&format!(
+                    "{} rows cannot be split into {} tables",
+                    rows.len(),
+                    config.0.num_tables
+                )));
+            }
+            let tables = rows
+                .chunks_exact(rows.len() / config.0.num_tables)
+                .map(<[Vec<i32>]>::to_vec)
+                .collect();
+            Ok(Self::new(tables, config))
+        })
+    }
+
+    fn new(tables: Vec<Vec<Vec<i32>>>, config: &BiometricConfig) -> Self {
+        Self {
+            tables: bio_aux::HelrTableSet::from_tables(tables, &config.0),
+            num_tables: config.0.num_tables,
+            num_blocks_sum: config.0.num_blocks_sum,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct BiometricTemplate(pub(crate) biometrics::EncryptedTemplate);
+
+#[wasm_bindgen]
+impl BiometricTemplate {
+    /// Quantizes the raw `features` of a template and encrypts its LUTs.
+    #[wasm_bindgen]
+    pub fn encrypt(
+        client_key: &TfheClientKey,
+        tables: &BiometricTables,
+        quantizer: &BiometricQuantizer,
+        features: Vec<f64>,
+    ) -> Result<BiometricTemplate, JsError> {
+        if features.len() != tables.num_tables {
+            return Err(JsError::new(&format!(
+                "there are {} tables, got {} features",
+                tables.num_tables,
+                features.len()
+            )));
+        }
+        let template = quantizer.quantize(features)?;
+        catch_panic(|| {
+            let rows = tables.tables.lut_rows(&template, true);
+            Self(biometrics::EncryptedTemplate::encrypt(
+                &rows,
+                tables.tables.threshold(),
+                tables.num_blocks_sum,
+                &client_key.0,
+            ))
+        })
+    }
+
+    #[wasm_bindgen]
+    pub fn num_features(&self) -> usize {
+        self.0.num_features()
+    }
+
+    #[wasm_bindgen]
+    pub fn serialize(&self) -> Result<Vec<u8>, JsError> {
+        catch_panic_result(|| bincode::serialize(&self.0).map_err(into_js_error))
+    }
+
+    #[wasm_bindgen]
+    pub fn deserialize(buffer: &[u8]) -> Result<BiometricTemplate, JsError> {
+        catch_panic_result(|| {
+            bincode::deserialize(buffer)
+                .map(Self)
+                .map_err(into_js_error)
+        })
+    }
+
+    #[wasm_bindgen]
+    pub fn safe_serialize(&self, serialized_size_limit: u64) -> Result<Vec<u8>, JsError> {
+        let mut buffer = vec![];
+        catch_panic_result(|| {
+            crate::safe_deserialization::safe_serialize(&self.0, &mut buffer, serialized_size_limit)
+                .map_err(into_js_error)
+        })?;
+
+        Ok(buffer)
+    }
+
+    #[wasm_bindgen]
+    pub fn safe_deserialize(
+        buffer: &[u8],
+        serialized_size_limit: u64,
+    ) -> Result<BiometricTemplate, JsError> {
+        catch_panic_result(|| {
+            crate::safe_deserialization::safe_deserialize(buffer, serialized_size_limit)
+                .map(Self)
+                .map_err(into_js_error)
+        })
+    }
+}
+
+fn quantize_probe(
+    quantizer: &BiometricQuantizer,
+    template: &BiometricTemplate,
+    features: Vec<f64>,
+) -> Result<Vec<u8>, JsError> {
+    if features.len() != template.0.num_features() {
+        return Err(JsError::new(&format!(
+            "the template has {} features, got {}",
+            template.0.num_features(),
+            features.len()
+        )));
+    }
+    quantizer.quantize(features)
+}
+
+#[wasm_bindgen]
+pub struct BiometricProbe(pub(crate) biometrics::EncryptedProbe);
+
+#[wasm_bindgen]
+impl BiometricProbe {
+    /// Quantizes the raw `features` of a probe and encrypts them for `template`.
+    #[wasm_bindgen]
+    pub fn encrypt_with_client_key(
+        client_key: &TfheClientKey,
+        template: &BiometricTemplate,
+        quantizer: &BiometricQuantizer,
+        features: Vec<f64>,
+    ) -> Result<BiometricProbe, JsError> {
+        let probe = quantize_probe(quantizer, template, features)?;
+        catch_panic(|| {
+            Self(biometrics::EncryptedProbe::encrypt(
+                &probe,
+                &template.0,
+                &client_key.0,
+            ))
+        })
+    }
+
+    /// Quantizes the raw `features` of a probe and encrypts them for `template` with a public
+    /// key, for devices that capture probes but do not hold the client key.
+    #[wasm_bindgen]
+    pub fn encrypt_with_public_key(
+        public_key: &TfhePublicKey,
+        template: &BiometricTemplate,
+        quantizer: &BiometricQuantizer,
+        features: Vec<f64>,
+    ) -> Result<BiometricProbe, JsError> {
+        let probe = quantize_probe(quantizer, template, features)?;
+        catch_panic(|| {
+            Self(biometrics::EncryptedProbe::encrypt_with_public_key(
+                &probe,
+                &template.0,
+                &public_key.0,
+            ))
+        })
+    }
+
+    #[wasm_bindgen]
+    pub fn serialize(&self) -> Result<Vec<u8>, JsError> {
+        catch_panic_result(|| bincode::serialize(&self.0).map_err(into_js_error))
+    }
+
+    #[wasm_bindgen]
+    pub fn deserialize(buffer: &[u8]) -> Result<BiometricProbe, JsError> {
+        catch_panic_result(|| {
+            bincode::deserialize(buffer)
+                .map(Self)
+                .map_err(into_js_error)
+        })
+    }
+
+    #[wasm_bindgen]
+    pub fn safe_serialize(&self, serialized_size_limit: u64) -> Result<Vec<u8>, JsError> {
+        let mut buffer = vec![];
+        catch_panic_result(|| {
+            crate::safe_deserialization::safe_serialize(&self.0, &mut buffer, serialized_size_limit)
+                .map_err(into_js_error)
+        })?;
+
+        Ok(buffer)
+    }
+
+    #[wasm_bindgen]
+    pub fn safe_deserialize(
+        buffer: &[u8],
+        serialized_size_limit: u64,
+    ) -> Result<BiometricProbe, JsError> {
+        catch_panic_result(|| {
+            crate::safe_deserialization::safe_deserialize(buffer, serialized_size_limit)
+                .map(Self)
+                .map_err(into_js_error)
+        })
+    }
+}
+
+/// Compares `probe` against `template` with `server_key`, like the server does.
+#[wasm_bindgen]
+pub fn biometric_authenticate(
+    template: &BiometricTemplate,
+    probe: &BiometricProbe,
+    server_key: &TfheCompressedServerKey,
+) -> Result<FheBool, JsError> {
+    catch_panic(|| {
+        hlapi::with_server_key_as_context(server_key.0.decompress(), || {
+            FheBool(biometrics::authenticate(&template.0, probe.0.clone()))
+        })
+    })
+}
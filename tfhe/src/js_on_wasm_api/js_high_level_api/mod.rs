@@ -1,5 +1,9 @@
 use wasm_bindgen::prelude::*;
 
+// using Self does not work well with #[wasm_bindgen] macro
+#[allow(clippy::use_self)]
+#[cfg(feature = "biometrics-client-js-wasm-api")]
+mod biometrics;
 pub(crate) mod config;
 pub(crate) mod integers;
 // using Self does not work well with #[wasm_bindgen] macro
@@ -1,4 +1,5 @@
 pub mod modulus_switch_compression;
+pub mod noise_distribution;
 pub mod noise_level;
 pub mod parametrized_test;
 pub mod parametrized_test_bivariate_pbs_compliant;
@@ -0,0 +1,532 @@
+//! Statistical checks of the noise of the shortint operations against the theoretical model.
+//!
+//! The noise of each sample is measured exactly with the secret keys, the variance of many samples
+//! is then compared to the variance predicted from the parameters' noise distributions.
+
+use crate::core_crypto::algorithms::polynomial_algorithms::polynomial_wrapping_monic_monomial_div_assign;
+use crate::core_crypto::commons::test_tools::variance;
+use crate::core_crypto::fft_impl::common::pbs_modulus_switch;
+use crate::core_crypto::fft_impl::fft128::crypto::bootstrap::blind_rotate_scratch;
+use crate::core_crypto::prelude::{
+    allocate_and_generate_new_lwe_bootstrap_key, convert_standard_lwe_bootstrap_key_to_fourier_128,
+    glwe_ciphertext_noise, keyswitch_lwe_ciphertext, lwe_ciphertext_noise, new_seeder,
+    ActivatedRandomGenerator, ComputationBuffers, ContiguousEntityContainer, DispersionParameter,
+    EncryptionRandomGenerator, Fft128, Fourier128LweBootstrapKey, GlweSize, LweCiphertext,
+    LweKeyswitchKeyOwned, LweSecretKeyView, MonomialDegree, Plaintext, PlaintextList, Variance,
+};
+use crate::shortint::keycache::KEY_CACHE;
+use crate::shortint::parameters::*;
+use crate::shortint::server_key::tests::parametrized_test::create_parametrized_test;
+use rand::Rng;
+use rayon::prelude::*;
+
+// This is 1 / 16 which is exactly representable in an f64 (even an f32)
+// 1 / 32 is too strict and fails the tests
+const RELATIVE_TOLERANCE: f64 = 0.0625;
+
+// The relative standard deviation of the variance estimator is about sqrt(2 / NB_SAMPLES), i.e.
+// 1.4%, well below the tolerance
+const NB_SAMPLES: usize = 10_000;
+
+// The 4 bits parameters and every set with 8192 coefficients or more are left out, encrypting,
+// keyswitching and bootstrapping the number of samples needed under their keys is too slow
+create_parametrized_test!(shortint_encryption_and_additions_noise_distribution {
+    PARAM_MESSAGE_1_CARRY_0_KS_PBS,
+    PARAM_MESSAGE_1_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_3_KS_PBS,
+    PARAM_MESSAGE_3_CARRY_3_KS_PBS
+});
+
+create_parametrized_test!(shortint_keyswitch_noise_distribution {
+    PARAM_MESSAGE_1_CARRY_0_KS_PBS,
+    PARAM_MESSAGE_1_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_3_KS_PBS,
+    PARAM_MESSAGE_3_CARRY_3_KS_PBS
+});
+
+// The blind rotation is checked with a bootstrapping key in the f128 Fourier domain, whose
+// rounding error is negligible, so the measured variance must match the model
+create_parametrized_test!(shortint_blind_rotation_noise_distribution {
+    PARAM_MESSAGE_1_CARRY_0_KS_PBS,
+    PARAM_MESSAGE_1_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_2_KS_PBS
+});
+
+// The PBS of the server key uses the f64 FFT, whose rounding error is only known through an upper
+// bound, so its output variance is checked to lie between the model and the model plus that bound
+create_parametrized_test!(shortint_pbs_noise_distribution {
+    PARAM_MESSAGE_1_CARRY_0_KS_PBS,
+    PARAM_MESSAGE_1_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_1_KS_PBS,
+    PARAM_MESSAGE_2_CARRY_2_KS_PBS
+});
+
+fn torus_noise(noise: i64) -> f64 {
+    noise as f64 / 2.0f64.powi(64)
+}
+
+fn hamming_weight(key: &[u64]) -> usize {
+    key.iter().filter(|&&bit| bit == 1).count()
+}
+
+/// Variance of the rounding error of a uniform torus element to its closest multiple of
+/// B^{-level}.
+fn decomposition_rounding_variance(
+    base_log: DecompositionBaseLog,
+    level: DecompositionLevelCount,
+) -> f64 {
+    let precision = 2.0f64.powi((base_log.0 * level.0) as i32);
+    let modulus = 2.0f64.powi(64);
+    1.0 / (12.0 * precision * precision) - 1.0 / (12.0 * modulus * modulus)
+}
+
+/// Mean square of the digit of the given level of the balanced decomposition of a uniform torus
+/// element.
+///
+/// The least significant digit, computed first, is uniform. The carry it propagates skews the next
+/// ones, whose mean square converges to its limit with a ratio of -1/B.
+fn decomposition_digit_square_mean(
+    base_log: DecompositionBaseLog,
+    level_count: DecompositionLevelCount,
+    level: usize,
+) -> f64 {
+    let base = 2.0f64.powi(base_log.0 as i32);
+    let first = (base * base + 2.0) / 12.0;
+    let second = (base * base - 1.0) / 12.0;
+    let limit = (base * base - (base - 2.0) / (base + 1.0)) / 12.0;
+    match level_count.0 - level {
+        0 => first,
+        index => limit + (second - limit) * (-1.0 / base).powi(index as i32 - 1),
+    }
+}
+
+fn decomposition_digit_square_sum(
+    base_log: DecompositionBaseLog,
+    level_count: DecompositionLevelCount,
+) -> f64 {
+    (1..=level_count.0)
+        .map(|level| decomposition_digit_square_mean(base_log, level_count, level))
+        .sum()
+}
+
+/// Variance added by a keyswitch with `ksk`.
+///
+/// The noise of the key is measured rather than taken from its distribution: with a few thousand
+/// ciphertexts in the key its actual variance is off by a few percent, which is the order of the
+/// tolerance.
+fn keyswitch_additive_variance(
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    input_lwe_secret_key: &LweSecretKeyView<'_, u64>,
+    output_lwe_secret_key: &LweSecretKeyView<'_, u64>,
+) -> Variance {
+    let base_log = ksk.decomposition_base_log();
+    let level_count = ksk.decomposition_level_count();
+
+    // The ciphertexts of a block encrypt the key element times B^-level, from the last level
+    let key_noise: f64 = ksk
+        .iter()
+        .zip(input_lwe_secret_key.as_ref())
+        .map(|(block, &key_element)| {
+            block
+                .iter()
+                .zip((1..=level_count.0).rev())
+                .map(|(ct, level)| {
+                    let expected =
+                        Plaintext(key_element << (u64::BITS as usize - base_log.0 * level));
+                    let noise =
+                        torus_noise(lwe_ciphertext_noise(output_lwe_secret_key, &ct, expected));
+                    decomposition_digit_square_mean(base_log, level_count, level) * noise * noise
+                })
+                .sum::<f64>()
+        })
+        .sum();
+    let rounding_noise = hamming_weight(input_lwe_secret_key.as_ref()) as f64
+        * decomposition_rounding_variance(base_log, level_count);
+    Variance(key_noise + rounding_noise)
+}
+
+/// Variance of the output of a blind rotation of a trivial accumulator, the input key and the
+/// output key being binary with the given numbers of ones.
+///
+/// The exact rotation computed with the secret key removes the noise of the input ciphertext and
+/// the one of the modulus switch.
+#[allow(clippy::too_many_arguments)]
+fn blind_rotation_variance(
+    input_lwe_dimension: LweDimension,
+    input_key_hamming_weight: usize,
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    output_key_hamming_weight: usize,
+    base_log: DecompositionBaseLog,
+    level: DecompositionLevelCount,
+    bsk_variance: Variance,
+) -> Variance {
+    // Each cmux adds the noise of the product of the decomposed glwe with the ggsw
+    let key_noise = (input_lwe_dimension.0 * glwe_size.0 * polynomial_size.0) as f64
+        * decomposition_digit_square_sum(base_log, level)
+        * bsk_variance.0;
+    // Only the cmuxes selecting the rotated accumulator add the rounding of the decomposition,
+    // multiplied by the output key
+    let rounding_noise = input_key_hamming_weight as f64
+        * (1 + output_key_hamming_weight) as f64
+        * decomposition_rounding_variance(base_log, level);
+    Variance(key_noise + rounding_noise)
+}
+
+/// Upper bound of the variance added by the rounding errors of the f64 FFT in the external
+/// products of a blind rotation.
+///
+/// This is the empirical fit used to optimize the parameters, it bounds the error of the current
+/// FFT implementation without modeling it.
+fn fft64_additive_variance_bound(
+    input_lwe_dimension: LweDimension,
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    base_log: DecompositionBaseLog,
+    level: DecompositionLevelCount,
+) -> Variance {
+    let base = 2.0f64.powi(base_log.0 as i32);
+    let polynomial_size = polynomial_size.0 as f64;
+    Variance(
+        input_lwe_dimension.0 as f64
+            * 2.0f64.powf(22.0 - 2.577_224_94)
+            * level.0 as f64
+            * base
+            * base
+            * polynomial_size
+            * polynomial_size
+            * glwe_size.0 as f64
+            / 2.0f64.powi(128),
+    )
+}
+
+fn check_variance(measured_variance: Variance, expected_variance: Variance) {
+    let var_abs_diff = (expected_variance.0 - measured_variance.0).abs();
+    let tolerance_threshold = RELATIVE_TOLERANCE * expected_variance.0;
+    assert!(
+        var_abs_diff < tolerance_threshold,
+        "Absolute difference for variance: {var_abs_diff}, \
+        tolerance threshold: {tolerance_threshold}, \
+        got variance: {measured_variance:?}, \
+        expected variance: {expected_variance:?}"
+    );
+}
+
+/// Check the measured variance against a model only known to be between `lower_variance` and
+/// `upper_variance`.
+fn check_variance_between(
+    measured_variance: Variance,
+    lower_variance: Variance,
+    upper_variance: Variance,
+) {
+    let lower_threshold = (1.0 - RELATIVE_TOLERANCE) * lower_variance.0;
+    let upper_threshold = (1.0 + RELATIVE_TOLERANCE) * upper_variance.0;
+    assert!(
+        (lower_threshold..upper_threshold).contains(&measured_variance.0),
+        "got variance: {measured_variance:?}, \
+        expected variance between: {lower_threshold} and {upper_threshold}"
+    );
+}
+
+fn shortint_encryption_and_additions_noise_distribution<P>(param: P)
+where
+    P: Into<PBSParameters>,
+{
+    let keys = KEY_CACHE.get_from_param(param);
+    let (cks, sks) = (keys.client_key(), keys.server_key());
+    let params = cks.parameters;
+    assert!(params.ciphertext_modulus().is_native_modulus());
+    assert_eq!(
+        params.encryption_key_choice(),
+        EncryptionKeyChoice::Big,
+        "Only the parameters encrypting under the large key are supported"
+    );
+
+    let message_modulus = params.message_modulus().0 as u64;
+    let delta = (1_u64 << 63) / (message_modulus * params.carry_modulus().0 as u64);
+    let encryption_variance = Variance(
+        params
+            .glwe_noise_distribution()
+            .gaussian_std_dev()
+            .get_variance(),
+    );
+    let nb_additions = params.max_noise_level().get();
+
+    let (fresh_noise, sum_noise): (Vec<_>, Vec<_>) = (0..NB_SAMPLES)
+        .into_par_iter()
+        .map(|_| {
+            let mut rng = rand::thread_rng();
+
+            let msg = rng.gen::<u64>() % message_modulus;
+            let mut sum = cks.unchecked_encrypt(msg);
+            let mut expected_sum = msg * delta;
+
+            let fresh_noise = torus_noise(lwe_ciphertext_noise(
+                &cks.large_lwe_secret_key(),
+                &sum.ct,
+                Plaintext(expected_sum),
+            ));
+
+            for _ in 1..nb_additions {
+                let msg = rng.gen::<u64>() % message_modulus;
+                let ct = cks.unchecked_encrypt(msg);
+                sum = sks.unchecked_add(&sum, &ct);
+                expected_sum = expected_sum.wrapping_add(msg * delta);
+            }
+
+            let sum_noise = torus_noise(lwe_ciphertext_noise(
+                &cks.large_lwe_secret_key(),
+                &sum.ct,
+                Plaintext(expected_sum),
+            ));
+            (fresh_noise, sum_noise)
+        })
+        .unzip();
+
+    check_variance(variance(&fresh_noise), encryption_variance);
+    check_variance(
+        variance(&sum_noise),
+        Variance(nb_additions as f64 * encryption_variance.0),
+    );
+}
+
+fn shortint_keyswitch_noise_distribution<P>(param: P)
+where
+    P: Into<PBSParameters>,
+{
+    let keys = KEY_CACHE.get_from_param(param);
+    let (cks, sks) = (keys.client_key(), keys.server_key());
+    let params = cks.parameters;
+    assert!(params.ciphertext_modulus().is_native_modulus());
+    assert_eq!(params.encryption_key_choice(), EncryptionKeyChoice::Big);
+
+    let message_modulus = params.message_modulus().0 as u64;
+    let delta = (1_u64 << 63) / (message_modulus * params.carry_modulus().0 as u64);
+    let large_lwe_secret_key = cks.large_lwe_secret_key();
+    let small_lwe_secret_key = cks.small_lwe_secret_key();
+
+    let encryption_variance = Variance(
+        params
+            .glwe_noise_distribution()
+            .gaussian_std_dev()
+            .get_variance(),
+    );
+    let keyswitch_variance = keyswitch_additive_variance(
+        &sks.key_switching_key,
+        &large_lwe_secret_key,
+        &small_lwe_secret_key,
+    );
+    let expected_variance = Variance(encryption_variance.0 + keyswitch_variance.0);
+
+    let noise: Vec<_> = (0..NB_SAMPLES)
+        .into_par_iter()
+        .map(|_| {
+            let msg = rand::thread_rng().gen::<u64>() % message_modulus;
+            let ct = cks.unchecked_encrypt(msg);
+
+            let mut output = LweCiphertext::new(
+                0u64,
+                sks.key_switching_key.output_lwe_size(),
+                params.ciphertext_modulus(),
+            );
+            keyswitch_lwe_ciphertext(&sks.key_switching_key, &ct.ct, &mut output);
+
+            torus_noise(lwe_ciphertext_noise(
+                &small_lwe_secret_key,
+                &output,
+                Plaintext(msg * delta),
+            ))
+        })
+        .collect();
+
+    check_variance(variance(&noise), expected_variance);
+}
+
+fn shortint_blind_rotation_noise_distribution<P>(param: P)
+where
+    P: Into<PBSParameters>,
+{
+    let keys = KEY_CACHE.get_from_param(param);
+    let (cks, sks) = (keys.client_key(), keys.server_key());
+    let params = cks.parameters;
+    assert!(params.ciphertext_modulus().is_native_modulus());
+    assert_eq!(params.encryption_key_choice(), EncryptionKeyChoice::Big);
+
+    let message_modulus = params.message_modulus().0 as u64;
+    let glwe_size = params.glwe_dimension().to_glwe_size();
+    let polynomial_size = params.polynomial_size();
+    let large_lwe_secret_key = cks.large_lwe_secret_key();
+    let small_lwe_secret_key = cks.small_lwe_secret_key();
+
+    let glwe_noise_distribution = params.glwe_noise_distribution();
+    let bsk_variance = Variance(glwe_noise_distribution.gaussian_std_dev().get_variance());
+
+    let mut seeder = new_seeder();
+    let mut encryption_generator =
+        EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder.as_mut());
+
+    let bsk = allocate_and_generate_new_lwe_bootstrap_key(
+        &small_lwe_secret_key,
+        &cks.glwe_secret_key,
+        params.pbs_base_log(),
+        params.pbs_level(),
+        glwe_noise_distribution,
+        params.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+    let mut fourier_bsk = Fourier128LweBootstrapKey::new(
+        bsk.input_lwe_dimension(),
+        bsk.glwe_size(),
+        bsk.polynomial_size(),
+        bsk.decomposition_base_log(),
+        bsk.decomposition_level_count(),
+    );
+    convert_standard_lwe_bootstrap_key_to_fourier_128(&bsk, &mut fourier_bsk);
+    drop(bsk);
+
+    let expected_variance = blind_rotation_variance(
+        small_lwe_secret_key.lwe_dimension(),
+        hamming_weight(small_lwe_secret_key.as_ref()),
+        glwe_size,
+        polynomial_size,
+        hamming_weight(large_lwe_secret_key.as_ref()),
+        params.pbs_base_log(),
+        params.pbs_level(),
+        bsk_variance,
+    );
+
+    let accumulator = sks.generate_lookup_table(|x| x).acc;
+
+    // The rounding errors of the decompositions are multiplied by the output key, which correlates
+    // the coefficients of a rotated accumulator: a rotation is only worth about a dozen
+    // independent samples
+    let nb_rotations = NB_SAMPLES.div_ceil(12);
+
+    let noise: Vec<_> = (0..nb_rotations)
+        .into_par_iter()
+        .flat_map_iter(|_| {
+            let msg = rand::thread_rng().gen::<u64>() % message_modulus;
+            let ct = cks.unchecked_encrypt(msg);
+
+            let mut input = LweCiphertext::new(
+                0u64,
+                sks.key_switching_key.output_lwe_size(),
+                params.ciphertext_modulus(),
+            );
+            keyswitch_lwe_ciphertext(&sks.key_switching_key, &ct.ct, &mut input);
+
+            // The rotation applied by the blind rotation, computed in the clear
+            let double_polynomial_size = 2 * polynomial_size.0;
+            let (mask, body) = input.get_mask_and_body();
+            let rotation = mask
+                .as_ref()
+                .iter()
+                .zip(small_lwe_secret_key.as_ref())
+                .filter(|(_, &key_bit)| key_bit == 1)
+                .fold(
+                    pbs_modulus_switch(*body.data, polynomial_size),
+                    |rotation, (&mask_element, _)| {
+                        (rotation + double_polynomial_size
+                            - pbs_modulus_switch(mask_element, polynomial_size))
+                            % double_polynomial_size
+                    },
+                );
+
+            let mut expected =
+                PlaintextList::from_container(accumulator.get_body().as_ref().to_vec());
+            polynomial_wrapping_monic_monomial_div_assign(
+                &mut expected.as_mut_polynomial(),
+                MonomialDegree(rotation),
+            );
+
+            let fft = Fft128::new(polynomial_size);
+            let fft = fft.as_view();
+            let mut buffers = ComputationBuffers::new();
+            buffers.resize(
+                blind_rotate_scratch::<u64>(glwe_size, polynomial_size, fft)
+                    .unwrap()
+                    .unaligned_bytes_required(),
+            );
+
+            let mut rotated = accumulator.clone();
+            fourier_bsk.blind_rotate_assign(&mut rotated, &input, fft, buffers.stack());
+
+            glwe_ciphertext_noise(&cks.glwe_secret_key, &rotated, &expected)
+                .into_iter()
+                .map(torus_noise)
+        })
+        .collect();
+
+    check_variance(variance(&noise), expected_variance);
+}
+
+fn shortint_pbs_noise_distribution<P>(param: P)
+where
+    P: Into<PBSParameters>,
+{
+    let keys = KEY_CACHE.get_from_param(param);
+    let (cks, sks) = (keys.client_key(), keys.server_key());
+    let params = cks.parameters;
+    assert!(params.ciphertext_modulus().is_native_modulus());
+    assert_eq!(params.encryption_key_choice(), EncryptionKeyChoice::Big);
+
+    let message_modulus = params.message_modulus().0 as u64;
+    let delta = (1_u64 << 63) / (message_modulus * params.carry_modulus().0 as u64);
+    let glwe_size = params.glwe_dimension().to_glwe_size();
+    let large_lwe_secret_key = cks.large_lwe_secret_key();
+    let small_lwe_secret_key = cks.small_lwe_secret_key();
+
+    let bsk_variance = Variance(
+        params
+            .glwe_noise_distribution()
+            .gaussian_std_dev()
+            .get_variance(),
+    );
+    let blind_rotation_variance = blind_rotation_variance(
+        small_lwe_secret_key.lwe_dimension(),
+        hamming_weight(small_lwe_secret_key.as_ref()),
+        glwe_size,
+        params.polynomial_size(),
+        hamming_weight(large_lwe_secret_key.as_ref()),
+        params.pbs_base_log(),
+        params.pbs_level(),
+        bsk_variance,
+    );
+    let fft_variance_bound = fft64_additive_variance_bound(
+        small_lwe_secret_key.lwe_dimension(),
+        glwe_size,
+        params.polynomial_size(),
+        params.pbs_base_log(),
+        params.pbs_level(),
+    );
+
+    // The identity LUT maps the whole box of a message to its encoding, the input noise and the
+    // modulus switch do not reach the output
+    let accumulator = sks.generate_lookup_table(|x| x);
+
+    let noise: Vec<_> = (0..NB_SAMPLES)
+        .into_par_iter()
+        .map(|_| {
+            let msg = rand::thread_rng().gen::<u64>() % message_modulus;
+            let ct = cks.unchecked_encrypt(msg);
+            let output = sks.apply_lookup_table(&ct, &accumulator);
+
+            torus_noise(lwe_ciphertext_noise(
+                &large_lwe_secret_key,
+                &output.ct,
+                Plaintext(msg * delta),
+            ))
+        })
+        .collect();
+
+    check_variance_between(
+        variance(&noise),
+        blind_rotation_variance,
+        Variance(blind_rotation_variance.0 + fft_variance_bound.0),
+    );
+}